@@ -1,10 +1,13 @@
 use std::fmt;
 use std::future::Future;
 use std::pin::Pin;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
 use std::task::{Context, Poll};
 use std::time::Duration;
 
 use bytes::Bytes;
+use futures_util::task::AtomicWaker;
 use http_body::Body as HttpBody;
 use http_body_util::combinators::BoxBody;
 //use sync_wrapper::SyncWrapper;
@@ -47,6 +50,34 @@ pin_project! {
     }
 }
 
+pin_project! {
+    /// A request body with a total timeout that starts when it is first
+    /// polled, once the request is being sent.
+    pub(crate) struct WriteTimeoutBody<B> {
+        #[pin]
+        inner: B,
+        #[pin]
+        sleep: Option<Sleep>,
+        timeout: Duration,
+    }
+}
+
+pin_project! {
+    /// A request body that signals once its last frame has been written.
+    pub(crate) struct WrittenBody<B> {
+        #[pin]
+        inner: B,
+        written: Arc<Written>,
+    }
+}
+
+/// Shared between a `WrittenBody` and whoever waits for it to be written.
+#[derive(Default)]
+pub(crate) struct Written {
+    done: AtomicBool,
+    waker: AtomicWaker,
+}
+
 /// Converts any `impl Body` into a `impl Stream` of just its DATA frames.
 #[cfg(any(feature = "stream", feature = "multipart",))]
 pub(crate) struct DataStream<B>(pub(crate) B);
@@ -62,6 +93,13 @@ impl Body {
         }
     }
 
+    pub(crate) fn reusable_bytes(&self) -> Option<&Bytes> {
+        match &self.inner {
+            Inner::Reusable(bytes) => Some(bytes),
+            Inner::Streaming(..) => None,
+        }
+    }
+
     /// Wrap a futures `Stream` in a box inside `Body`.
     ///
     /// # Example
@@ -391,6 +429,119 @@ where
     }
 }
 
+// ===== impl WriteTimeoutBody =====
+
+pub(crate) fn with_write_timeout<B>(body: B, timeout: Duration) -> WriteTimeoutBody<B> {
+    WriteTimeoutBody {
+        inner: body,
+        sleep: None,
+        timeout,
+    }
+}
+
+impl<B> hyper::body::Body for WriteTimeoutBody<B>
+where
+    B: hyper::body::Body,
+    B::Error: Into<Box<dyn std::error::Error + Send + Sync>>,
+{
+    type Data = B::Data;
+    type Error = crate::Error;
+
+    fn poll_frame(
+        self: Pin<&mut Self>,
+        cx: &mut Context,
+    ) -> Poll<Option<Result<hyper::body::Frame<Self::Data>, Self::Error>>> {
+        let mut this = self.project();
+
+        // Start the `Sleep` on the first poll, and never reset it.
+        if this.sleep.is_none() {
+            this.sleep.set(Some(tokio::time::sleep(*this.timeout)));
+        }
+        if let Poll::Ready(()) = this.sleep.as_pin_mut().unwrap().poll(cx) {
+            return Poll::Ready(Some(Err(crate::error::body(crate::error::TimedOut))));
+        }
+
+        Poll::Ready(
+            futures_core::ready!(this.inner.poll_frame(cx))
+                .map(|opt_chunk| opt_chunk.map_err(crate::error::body)),
+        )
+    }
+
+    #[inline]
+    fn size_hint(&self) -> http_body::SizeHint {
+        self.inner.size_hint()
+    }
+
+    #[inline]
+    fn is_end_stream(&self) -> bool {
+        self.inner.is_end_stream()
+    }
+}
+
+// ===== impl WrittenBody =====
+
+pub(crate) fn written<B: HttpBody>(body: B) -> (WrittenBody<B>, Arc<Written>) {
+    let written = Arc::new(Written::default());
+    // hyper won't poll a body that is already at its end.
+    if body.is_end_stream() {
+        written.set();
+    }
+    let body = WrittenBody {
+        inner: body,
+        written: written.clone(),
+    };
+    (body, written)
+}
+
+impl<B> hyper::body::Body for WrittenBody<B>
+where
+    B: hyper::body::Body,
+{
+    type Data = B::Data;
+    type Error = B::Error;
+
+    fn poll_frame(
+        self: Pin<&mut Self>,
+        cx: &mut Context,
+    ) -> Poll<Option<Result<hyper::body::Frame<Self::Data>, Self::Error>>> {
+        let mut this = self.project();
+        let item = futures_core::ready!(this.inner.as_mut().poll_frame(cx));
+        match item {
+            None | Some(Err(_)) => this.written.set(),
+            Some(Ok(_)) if this.inner.is_end_stream() => this.written.set(),
+            Some(Ok(_)) => (),
+        }
+        Poll::Ready(item)
+    }
+
+    #[inline]
+    fn size_hint(&self) -> http_body::SizeHint {
+        self.inner.size_hint()
+    }
+
+    #[inline]
+    fn is_end_stream(&self) -> bool {
+        self.inner.is_end_stream()
+    }
+}
+
+impl Written {
+    fn set(&self) {
+        self.done.store(true, Ordering::Release);
+        self.waker.wake();
+    }
+
+    /// Returns whether the body has been written, registering the waker
+    /// to be notified if it hasn't yet.
+    pub(crate) fn poll_written(&self, cx: &mut Context) -> bool {
+        if self.done.load(Ordering::Acquire) {
+            return true;
+        }
+        self.waker.register(cx.waker());
+        self.done.load(Ordering::Acquire)
+    }
+}
+
 pub(crate) type ResponseBody =
     http_body_util::combinators::BoxBody<Bytes, Box<dyn std::error::Error + Send + Sync>>;
 
@@ -1,19 +1,21 @@
 #[cfg(any(feature = "native-tls", feature = "__rustls",))]
 use std::any::Any;
 use std::net::IpAddr;
-use std::sync::Arc;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
 use std::time::Duration;
 use std::{collections::HashMap, convert::TryInto, net::SocketAddr};
 use std::{fmt, str};
 
 use bytes::Bytes;
+use futures_core::future::BoxFuture;
 use http::header::{
-    Entry, HeaderMap, HeaderValue, ACCEPT, ACCEPT_ENCODING, CONTENT_ENCODING, CONTENT_LENGTH,
-    CONTENT_TYPE, LOCATION, PROXY_AUTHORIZATION, RANGE, REFERER, TRANSFER_ENCODING, USER_AGENT,
+    Entry, HeaderMap, HeaderValue, ACCEPT, ACCEPT_ENCODING, AUTHORIZATION, CONTENT_ENCODING, CONTENT_LENGTH,
+    CONTENT_TYPE, HOST, LOCATION, PROXY_AUTHORIZATION, RANGE, REFERER, TRANSFER_ENCODING, USER_AGENT,
 };
 use http::uri::Scheme;
 use http::Uri;
-use hyper_util::client::legacy::connect::HttpConnector;
+use hyper_util::client::legacy::connect::{capture_connection, CaptureConnection, HttpConnector};
 #[cfg(feature = "default-tls")]
 use native_tls_crate::TlsConnector;
 use pin_project_lite::pin_project;
@@ -21,23 +23,40 @@ use std::future::Future;
 use std::pin::Pin;
 use std::task::{Context, Poll};
 use tokio::time::Sleep;
+use tower::util::BoxCloneSyncService;
+use tower::ServiceExt;
+use tower_service::Service;
 
 use super::decoder::Accepts;
+use super::pool_stats::{ConnHandle, PoolStats, PoolTracker};
 use super::request::{Request, RequestBuilder};
 use super::response::Response;
-use super::Body;
+use super::{body, Body};
 #[cfg(feature = "http3")]
 use crate::async_impl::h3_client::connect::H3Connector;
 #[cfg(feature = "http3")]
 use crate::async_impl::h3_client::{H3Client, H3ResponseFuture};
-use crate::connect::Connector;
+use crate::connect::{
+    BoxedConnectorService, Conn, ConnectRetry, ConnectionInfo, Connector, ConnectorLayerFn,
+    ConnectorService, PoolKey, RequestBinding, RequestConnect, RequestProxy, TransportFn,
+    WireDirection, WireLogFn, UNIX_SCHEME,
+};
 #[cfg(feature = "cookies")]
 use crate::cookie;
+#[cfg(feature = "dns-over-tls")]
+use crate::dns::dot::DotResolver;
 #[cfg(feature = "hickory-dns")]
 use crate::dns::hickory::HickoryDnsResolver;
-use crate::dns::{gai::GaiResolver, DnsResolverWithOverrides, DynResolver, Resolve};
-use crate::error;
+use crate::dns::{
+    gai::GaiResolver, DnsCache, DnsLookup, DnsLookupFn, DnsResolverWithHook,
+    DnsResolverWithLogging, DnsResolverWithOverrides, DynResolver, Resolve, ResolveWithTtl,
+};
+use crate::error::{self, BoxError};
 use crate::into_url::try_uri;
+use crate::netrc::Netrc;
+use crate::proxy::{
+    CustomProxyStream, ProxyRequest, ProxySelectedFn, ProxySelection, RequestDst, SharedProxies,
+};
 use crate::redirect::{self, remove_sensitive_headers};
 #[cfg(feature = "__tls")]
 use crate::tls::{self, TlsBackend};
@@ -90,25 +109,64 @@ enum HttpVersionPref {
 struct Config {
     // NOTE: When adding a new field, update `fmt::Debug for ClientBuilder`
     accepts: Accepts,
+    auto_accept_encoding: bool,
     headers: HeaderMap,
     #[cfg(feature = "__tls")]
     hostname_verification: bool,
     #[cfg(feature = "__tls")]
     certs_verification: bool,
     #[cfg(feature = "__tls")]
+    custom_verifier: Option<Arc<tls::CustomVerifierFn>>,
+    #[cfg(feature = "__rustls")]
+    tls_key_log: Option<tls::KeyLog>,
+    #[cfg(feature = "__rustls")]
+    tls_session_tickets: bool,
+    #[cfg(feature = "__rustls")]
+    tls_session_cache: Option<tls::SessionCache>,
+    #[cfg(feature = "__rustls")]
+    crls: Vec<tls::CertificateRevocationList>,
+    #[cfg(feature = "__rustls")]
+    tls_ocsp_stapling_required: bool,
+    #[cfg(feature = "__rustls")]
+    tls_ct_logs: Vec<tls::CtLog>,
+    #[cfg(feature = "__rustls")]
+    tls_ct_min_scts: usize,
+    #[cfg(feature = "__tls")]
     tls_sni: bool,
     connect_timeout: Option<Duration>,
+    connect_retry: Option<ConnectRetry>,
+    connect_timeouts_for: Vec<(String, Duration)>,
+    connect_timeout_per_address: Option<Duration>,
+    pool_max_connection_lifetime: Option<Duration>,
+    max_connections_per_host: Option<usize>,
+    max_connections: Option<usize>,
     connection_verbose: bool,
+    wire_log: Option<Arc<WireLogFn>>,
     pool_idle_timeout: Option<Duration>,
     pool_max_idle_per_host: usize,
     tcp_keepalive: Option<Duration>,
+    tcp_send_buffer_size: Option<usize>,
+    tcp_recv_buffer_size: Option<usize>,
+    ipv4_fallback: bool,
     #[cfg(any(feature = "native-tls", feature = "__rustls"))]
     identity: Option<Identity>,
+    #[cfg(any(feature = "native-tls", feature = "__rustls"))]
+    identities_for: Vec<(String, Identity)>,
     proxies: Vec<Proxy>,
     auto_sys_proxy: bool,
+    #[cfg(feature = "socks")]
+    proxy_resolve_via_system_dns: bool,
+    no_proxy_private_networks: bool,
+    system_proxy_refresh: Option<Duration>,
+    on_proxy_selected: Option<Arc<ProxySelectedFn>>,
+    netrc: Option<Option<PathBuf>>,
+    transport: Option<Arc<TransportFn>>,
+    connector_layers: Vec<Arc<ConnectorLayerFn>>,
     redirect_policy: redirect::Policy,
     referer: bool,
     read_timeout: Option<Duration>,
+    response_read_timeout: Option<Duration>,
+    request_write_timeout: Option<Duration>,
     timeout: Option<Duration>,
     #[cfg(feature = "__tls")]
     root_certs: Vec<Certificate>,
@@ -124,6 +182,8 @@ struct Config {
     max_tls_version: Option<tls::Version>,
     #[cfg(feature = "__tls")]
     tls_info: bool,
+    #[cfg(all(feature = "__tls", feature = "cert-expiry-check"))]
+    cert_expiry: tls::CertExpiry,
     #[cfg(feature = "__tls")]
     tls: TlsBackend,
     http_version_pref: HttpVersionPref,
@@ -146,9 +206,36 @@ struct Config {
     http2_keep_alive_timeout: Option<Duration>,
     #[cfg(feature = "http2")]
     http2_keep_alive_while_idle: bool,
+    #[cfg(feature = "http2")]
+    http2_rst_error_delay: Option<(Duration, f64)>,
     local_address: Option<IpAddr>,
-    #[cfg(any(target_os = "android", target_os = "fuchsia", target_os = "linux"))]
+    local_address_env: Option<String>,
+    #[cfg(any(
+        target_os = "android",
+        target_os = "fuchsia",
+        target_os = "ios",
+        target_os = "linux",
+        target_os = "macos",
+        target_os = "tvos",
+        target_os = "watchos",
+    ))]
     interface: Option<String>,
+    #[cfg(any(
+        target_os = "android",
+        target_os = "fuchsia",
+        target_os = "ios",
+        target_os = "linux",
+        target_os = "macos",
+        target_os = "tvos",
+        target_os = "watchos",
+    ))]
+    interface_env: Option<String>,
+    #[cfg(any(target_os = "android", target_os = "linux"))]
+    interface_refresh: Option<Duration>,
+    #[cfg(any(target_os = "android", target_os = "linux"))]
+    interface_pattern: Option<String>,
+    #[cfg(any(target_os = "android", target_os = "fuchsia", target_os = "linux"))]
+    socket_mark: Option<u32>,
     nodelay: bool,
     #[cfg(feature = "cookies")]
     cookie_store: Option<Arc<dyn cookie::CookieStore>>,
@@ -166,7 +253,19 @@ struct Config {
     #[cfg(feature = "http3")]
     quic_send_window: Option<u64>,
     dns_overrides: HashMap<String, Vec<SocketAddr>>,
+    hosts_file: Option<PathBuf>,
+    #[cfg(unix)]
+    unix_sockets: HashMap<String, std::path::PathBuf>,
     dns_resolver: Option<Arc<dyn Resolve>>,
+    #[cfg(feature = "dns-over-tls")]
+    dns_over_tls: Option<(SocketAddr, String)>,
+    dns_cache: bool,
+    dns_cache_min_ttl: Duration,
+    dns_cache_max_ttl: Duration,
+    dns_cache_negative_ttl: Duration,
+    on_dns_lookup: Option<Arc<DnsLookupFn>>,
+    dns_debug_log: bool,
+    dns_log_hash_hostnames: bool,
 }
 
 impl Default for ClientBuilder {
@@ -187,25 +286,62 @@ impl ClientBuilder {
             config: Config {
                 error: None,
                 accepts: Accepts::default(),
+                auto_accept_encoding: true,
                 headers,
                 #[cfg(feature = "__tls")]
                 hostname_verification: true,
                 #[cfg(feature = "__tls")]
                 certs_verification: true,
                 #[cfg(feature = "__tls")]
+                custom_verifier: None,
+                #[cfg(feature = "__rustls")]
+                tls_key_log: None,
+                #[cfg(feature = "__rustls")]
+                tls_session_tickets: true,
+                #[cfg(feature = "__rustls")]
+                tls_session_cache: None,
+                #[cfg(feature = "__rustls")]
+                crls: Vec::new(),
+                #[cfg(feature = "__rustls")]
+                tls_ocsp_stapling_required: false,
+                #[cfg(feature = "__rustls")]
+                tls_ct_logs: Vec::new(),
+                #[cfg(feature = "__rustls")]
+                tls_ct_min_scts: 2,
+                #[cfg(feature = "__tls")]
                 tls_sni: true,
                 connect_timeout: None,
+                connect_retry: None,
+                connect_timeouts_for: Vec::new(),
+                connect_timeout_per_address: None,
+                pool_max_connection_lifetime: None,
+                max_connections_per_host: None,
+                max_connections: None,
                 connection_verbose: false,
+                wire_log: None,
                 pool_idle_timeout: Some(Duration::from_secs(90)),
                 pool_max_idle_per_host: std::usize::MAX,
                 // TODO: Re-enable default duration once hyper's HttpConnector is fixed
                 // to no longer error when an option fails.
                 tcp_keepalive: None, //Some(Duration::from_secs(60)),
+                tcp_send_buffer_size: None,
+                tcp_recv_buffer_size: None,
+                ipv4_fallback: false,
                 proxies: Vec::new(),
                 auto_sys_proxy: true,
+                #[cfg(feature = "socks")]
+                proxy_resolve_via_system_dns: true,
+                no_proxy_private_networks: false,
+                system_proxy_refresh: None,
+                on_proxy_selected: None,
+                netrc: None,
+                transport: None,
+                connector_layers: Vec::new(),
                 redirect_policy: redirect::Policy::default(),
                 referer: true,
                 read_timeout: None,
+                response_read_timeout: None,
+                request_write_timeout: None,
                 timeout: None,
                 #[cfg(feature = "__tls")]
                 root_certs: Vec::new(),
@@ -217,12 +353,16 @@ impl ClientBuilder {
                 tls_built_in_certs_native: true,
                 #[cfg(any(feature = "native-tls", feature = "__rustls"))]
                 identity: None,
+                #[cfg(any(feature = "native-tls", feature = "__rustls"))]
+                identities_for: Vec::new(),
                 #[cfg(feature = "__tls")]
                 min_tls_version: None,
                 #[cfg(feature = "__tls")]
                 max_tls_version: None,
                 #[cfg(feature = "__tls")]
                 tls_info: false,
+                #[cfg(all(feature = "__tls", feature = "cert-expiry-check"))]
+                cert_expiry: Default::default(),
                 #[cfg(feature = "__tls")]
                 tls: TlsBackend::default(),
                 http_version_pref: HttpVersionPref::All,
@@ -245,15 +385,45 @@ impl ClientBuilder {
                 http2_keep_alive_timeout: None,
                 #[cfg(feature = "http2")]
                 http2_keep_alive_while_idle: false,
+                #[cfg(feature = "http2")]
+                http2_rst_error_delay: None,
                 local_address: None,
-                #[cfg(any(target_os = "android", target_os = "fuchsia", target_os = "linux"))]
+                local_address_env: None,
+                #[cfg(any(
+                    target_os = "android",
+                    target_os = "fuchsia",
+                    target_os = "ios",
+                    target_os = "linux",
+                    target_os = "macos",
+                    target_os = "tvos",
+                    target_os = "watchos",
+                ))]
                 interface: None,
+                #[cfg(any(
+                    target_os = "android",
+                    target_os = "fuchsia",
+                    target_os = "ios",
+                    target_os = "linux",
+                    target_os = "macos",
+                    target_os = "tvos",
+                    target_os = "watchos",
+                ))]
+                interface_env: None,
+                #[cfg(any(target_os = "android", target_os = "linux"))]
+                interface_refresh: None,
+                #[cfg(any(target_os = "android", target_os = "linux"))]
+                interface_pattern: None,
+                #[cfg(any(target_os = "android", target_os = "fuchsia", target_os = "linux"))]
+                socket_mark: None,
                 nodelay: true,
                 hickory_dns: cfg!(feature = "hickory-dns"),
                 #[cfg(feature = "cookies")]
                 cookie_store: None,
                 https_only: false,
                 dns_overrides: HashMap::new(),
+                hosts_file: None,
+                #[cfg(unix)]
+                unix_sockets: HashMap::new(),
                 #[cfg(feature = "http3")]
                 tls_enable_early_data: false,
                 #[cfg(feature = "http3")]
@@ -265,6 +435,15 @@ impl ClientBuilder {
                 #[cfg(feature = "http3")]
                 quic_send_window: None,
                 dns_resolver: None,
+                #[cfg(feature = "dns-over-tls")]
+                dns_over_tls: None,
+                dns_cache: false,
+                dns_cache_min_ttl: Duration::ZERO,
+                dns_cache_max_ttl: Duration::from_secs(60),
+                dns_cache_negative_ttl: Duration::from_secs(5),
+                on_dns_lookup: None,
+                dns_debug_log: false,
+                dns_log_hash_hostnames: false,
             },
         }
     }
@@ -276,44 +455,126 @@ impl ClientBuilder {
     /// This method fails if a TLS backend cannot be initialized, or the resolver
     /// cannot load the system configuration.
     pub fn build(self) -> crate::Result<Client> {
-        let config = self.config;
+        #[allow(unused_mut)]
+        let mut config = self.config;
 
         if let Some(err) = config.error {
             return Err(err);
         }
 
-        let mut proxies = config.proxies;
+        if let Some(ref var) = config.local_address_env {
+            if let Some(value) = read_env(var)? {
+                let addr = value.parse().map_err(|_| {
+                    crate::error::builder(format!("invalid IP address in {var}: {value:?}"))
+                })?;
+                config.local_address = Some(addr);
+            }
+        }
+
+        #[cfg(any(
+            target_os = "android",
+            target_os = "fuchsia",
+            target_os = "ios",
+            target_os = "linux",
+            target_os = "macos",
+            target_os = "tvos",
+            target_os = "watchos",
+        ))]
+        if let Some(ref var) = config.interface_env {
+            if let Some(value) = read_env(var)? {
+                if !is_valid_interface_name(&value) {
+                    return Err(crate::error::builder(format!(
+                        "invalid interface name in {var}: {value:?}"
+                    )));
+                }
+                config.interface = Some(value);
+            }
+        }
+
+        // A custom transport decides how destinations are reached itself.
+        if config.transport.is_some() {
+            config.proxies.clear();
+            config.auto_sys_proxy = false;
+        }
+        let mut proxies = std::mem::take(&mut config.proxies);
+        #[cfg(feature = "__tls")]
+        for proxy in &mut proxies {
+            if proxy.has_tls_options() {
+                configure_proxy_tls(&config, proxy)?;
+            }
+        }
         if config.auto_sys_proxy {
             proxies.push(Proxy::system());
         }
-        let proxies = Arc::new(proxies);
+        if config.no_proxy_private_networks {
+            for proxy in &mut proxies {
+                proxy.add_private_networks_to_no_proxy();
+            }
+        }
+        let mut proxies = SharedProxies::new(proxies);
+        if let (true, Some(interval)) = (config.auto_sys_proxy, config.system_proxy_refresh) {
+            proxies = proxies.refresh_system(interval, config.no_proxy_private_networks);
+        }
+        let netrc = match config.netrc {
+            Some(ref path) => Some(Arc::new(Netrc::load(path.as_deref())?)),
+            None => None,
+        };
+        if let Some(ref netrc) = netrc {
+            proxies = proxies.with_netrc(netrc.clone());
+        }
 
         #[allow(unused)]
         #[cfg(feature = "http3")]
         let mut h3_connector = None;
 
+        let (mut resolver, mut ttl_resolver) = base_resolver(&config)?;
+        if let Some(dns_resolver) = config.dns_resolver.take() {
+            resolver = dns_resolver;
+            ttl_resolver = None;
+        }
+        let dns_cache = if config.dns_cache {
+            let cache = DnsCache::new(
+                resolver,
+                ttl_resolver,
+                config.dns_cache_min_ttl,
+                config.dns_cache_max_ttl,
+                config.dns_cache_negative_ttl,
+            );
+            resolver = Arc::new(cache.clone());
+            Some(cache)
+        } else {
+            None
+        };
+        if let Some(hook) = config.on_dns_lookup.take() {
+            resolver = Arc::new(DnsResolverWithHook::new(resolver, dns_cache.clone(), hook));
+        }
+        if let Some(ref path) = config.hosts_file {
+            for (name, addrs) in crate::dns::hosts::load(path)? {
+                config.dns_overrides.entry(name).or_insert(addrs);
+            }
+        }
+        if !config.dns_overrides.is_empty() {
+            resolver = Arc::new(DnsResolverWithOverrides::new(
+                resolver,
+                std::mem::take(&mut config.dns_overrides),
+            ));
+        }
+        if config.dns_debug_log {
+            resolver = Arc::new(DnsResolverWithLogging::new(
+                resolver,
+                config.dns_log_hash_hostnames,
+            ));
+        }
+        #[cfg(feature = "socks")]
+        let socks_resolver = DynResolver::new(resolver.clone());
+        let failover_resolver = resolver.clone();
+
         let mut connector = {
             #[cfg(feature = "__tls")]
             fn user_agent(headers: &HeaderMap) -> Option<HeaderValue> {
                 headers.get(USER_AGENT).cloned()
             }
 
-            let mut resolver: Arc<dyn Resolve> = match config.hickory_dns {
-                false => Arc::new(GaiResolver::new()),
-                #[cfg(feature = "hickory-dns")]
-                true => Arc::new(HickoryDnsResolver::default()),
-                #[cfg(not(feature = "hickory-dns"))]
-                true => unreachable!("hickory-dns shouldn't be enabled unless the feature is"),
-            };
-            if let Some(dns_resolver) = config.dns_resolver {
-                resolver = dns_resolver;
-            }
-            if !config.dns_overrides.is_empty() {
-                resolver = Arc::new(DnsResolverWithOverrides::new(
-                    resolver,
-                    config.dns_overrides,
-                ));
-            }
             let mut http = HttpConnector::new_with_resolver(DynResolver::new(resolver.clone()));
             http.set_connect_timeout(config.connect_timeout);
 
@@ -370,72 +631,72 @@ impl ClientBuilder {
             match config.tls {
                 #[cfg(feature = "default-tls")]
                 TlsBackend::Default => {
-                    let mut tls = TlsConnector::builder();
-
-                    #[cfg(all(feature = "native-tls-alpn", not(feature = "http3")))]
-                    {
-                        match config.http_version_pref {
-                            HttpVersionPref::Http1 => {
-                                tls.request_alpns(&["http/1.1"]);
-                            }
-                            #[cfg(feature = "http2")]
-                            HttpVersionPref::Http2 => {
-                                tls.request_alpns(&["h2"]);
-                            }
-                            HttpVersionPref::All => {
-                                tls.request_alpns(&["h2", "http/1.1"]);
-                            }
+                    // The custom verifier checks servers once connected, so
+                    // native-tls must let them through, but not proxies.
+                    let proxy_tls = match config.custom_verifier {
+                        Some(_) => {
+                            let proxy_tls = native_tls_builder(
+                                &config,
+                                &config.root_certs,
+                                #[cfg(any(feature = "native-tls", feature = "__rustls"))]
+                                config.identity.as_ref(),
+                            )?;
+                            Some(proxy_tls.build().map_err(crate::error::builder)?)
                         }
-                    }
-
-                    tls.danger_accept_invalid_hostnames(!config.hostname_verification);
-
-                    tls.danger_accept_invalid_certs(!config.certs_verification);
-
-                    tls.use_sni(config.tls_sni);
-
-                    tls.disable_built_in_roots(!config.tls_built_in_root_certs);
-
-                    for cert in config.root_certs {
-                        cert.add_to_native_tls(&mut tls);
-                    }
+                        None => None,
+                    };
 
-                    #[cfg(feature = "native-tls")]
-                    {
-                        if let Some(id) = config.identity {
-                            id.add_to_native_tls(&mut tls)?;
-                        }
-                    }
-                    #[cfg(all(feature = "__rustls", not(feature = "native-tls")))]
-                    {
-                        // Default backend + rustls Identity doesn't work.
-                        if let Some(_id) = config.identity {
-                            return Err(crate::error::builder("incompatible TLS identity type"));
+                    // Servers given their own identity get the same settings
+                    // as the others, save for the identity.
+                    let origin_tls = |#[cfg(any(feature = "native-tls", feature = "__rustls"))]
+                                      identity: Option<&Identity>| {
+                        #[allow(unused_mut)]
+                        let mut tls = native_tls_builder(
+                            &config,
+                            &config.root_certs,
+                            #[cfg(any(feature = "native-tls", feature = "__rustls"))]
+                            identity,
+                        )?;
+                        if config.custom_verifier.is_some() {
+                            tls.danger_accept_invalid_certs(true);
+                            tls.danger_accept_invalid_hostnames(true);
                         }
-                    }
-
-                    if let Some(min_tls_version) = config.min_tls_version {
-                        let protocol = min_tls_version.to_native_tls().ok_or_else(|| {
-                            // TLS v1.3. This would be entirely reasonable,
-                            // native-tls just doesn't support it.
-                            // https://github.com/sfackler/rust-native-tls/issues/140
-                            crate::error::builder("invalid minimum TLS version for backend")
-                        })?;
-                        tls.min_protocol_version(Some(protocol));
-                    }
 
-                    if let Some(max_tls_version) = config.max_tls_version {
-                        let protocol = max_tls_version.to_native_tls().ok_or_else(|| {
-                            // TLS v1.3.
-                            // We could arguably do max_protocol_version(None), given
-                            // that 1.4 does not exist yet, but that'd get messy in the
-                            // future.
-                            crate::error::builder("invalid maximum TLS version for backend")
-                        })?;
-                        tls.max_protocol_version(Some(protocol));
-                    }
+                        #[cfg(all(feature = "native-tls-alpn", not(feature = "http3")))]
+                        {
+                            match config.http_version_pref {
+                                HttpVersionPref::Http1 => {
+                                    tls.request_alpns(&["http/1.1"]);
+                                }
+                                #[cfg(feature = "http2")]
+                                HttpVersionPref::Http2 => {
+                                    tls.request_alpns(&["h2"]);
+                                }
+                                HttpVersionPref::All => {
+                                    tls.request_alpns(&["h2", "http/1.1"]);
+                                }
+                            }
+                        }
+                        Ok::<_, crate::Error>(tls)
+                    };
 
-                    Connector::new_default_tls(
+                    #[cfg(any(feature = "native-tls", feature = "__rustls"))]
+                    let host_tls = config
+                        .identities_for
+                        .iter()
+                        .map(|(pattern, identity)| {
+                            let host_tls = origin_tls(Some(identity))?;
+                            let host_tls = host_tls.build().map_err(crate::error::builder)?;
+                            Ok((pattern.clone(), host_tls))
+                        })
+                        .collect::<crate::Result<Vec<_>>>()?;
+
+                    let tls = origin_tls(
+                        #[cfg(any(feature = "native-tls", feature = "__rustls"))]
+                        config.identity.as_ref(),
+                    )?;
+
+                    let mut connector = Connector::new_default_tls(
                         http,
                         tls,
                         proxies.clone(),
@@ -444,12 +705,50 @@ impl ClientBuilder {
                         #[cfg(any(
                             target_os = "android",
                             target_os = "fuchsia",
-                            target_os = "linux"
+                            target_os = "ios",
+                            target_os = "linux",
+                            target_os = "macos",
+                            target_os = "tvos",
+                            target_os = "watchos",
                         ))]
                         config.interface.as_deref(),
                         config.nodelay,
                         config.tls_info,
-                    )?
+                    )?;
+                    if let Some(proxy_tls) = proxy_tls {
+                        connector.set_proxy_native_tls(proxy_tls);
+                        connector.set_custom_verifier(config.custom_verifier.clone());
+                    }
+                    #[cfg(any(feature = "native-tls", feature = "__rustls"))]
+                    connector.set_host_native_tls(host_tls);
+
+                    // Requests accepting invalid certificates get connectors
+                    // of their own.
+                    #[cfg(feature = "danger-request-invalid-certs")]
+                    {
+                        let insecure = |tls: crate::Result<native_tls_crate::TlsConnectorBuilder>| {
+                            let mut tls = tls?;
+                            tls.danger_accept_invalid_certs(true);
+                            tls.danger_accept_invalid_hostnames(true);
+                            tls.build().map_err(crate::error::builder)
+                        };
+                        #[cfg(any(feature = "native-tls", feature = "__rustls"))]
+                        let host_tls = config
+                            .identities_for
+                            .iter()
+                            .map(|(pattern, identity)| {
+                                Ok((pattern.clone(), insecure(origin_tls(Some(identity)))?))
+                            })
+                            .collect::<crate::Result<Vec<_>>>()?;
+                        #[cfg(not(any(feature = "native-tls", feature = "__rustls")))]
+                        let host_tls = Vec::new();
+                        let tls = insecure(origin_tls(
+                            #[cfg(any(feature = "native-tls", feature = "__rustls"))]
+                            config.identity.as_ref(),
+                        ))?;
+                        connector.set_insecure_native_tls(tls, host_tls);
+                    }
+                    connector
                 }
                 #[cfg(feature = "native-tls")]
                 TlsBackend::BuiltNativeTls(conn) => Connector::from_built_default_tls(
@@ -458,7 +757,15 @@ impl ClientBuilder {
                     proxies.clone(),
                     user_agent(&config.headers),
                     config.local_address,
-                    #[cfg(any(target_os = "android", target_os = "fuchsia", target_os = "linux"))]
+                    #[cfg(any(
+                        target_os = "android",
+                        target_os = "fuchsia",
+                        target_os = "ios",
+                        target_os = "linux",
+                        target_os = "macos",
+                        target_os = "tvos",
+                        target_os = "watchos",
+                    ))]
                     config.interface.as_deref(),
                     config.nodelay,
                     config.tls_info,
@@ -488,7 +795,11 @@ impl ClientBuilder {
                         #[cfg(any(
                             target_os = "android",
                             target_os = "fuchsia",
-                            target_os = "linux"
+                            target_os = "ios",
+                            target_os = "linux",
+                            target_os = "macos",
+                            target_os = "tvos",
+                            target_os = "watchos",
                         ))]
                         config.interface.as_deref(),
                         config.nodelay,
@@ -497,112 +808,20 @@ impl ClientBuilder {
                 }
                 #[cfg(feature = "__rustls")]
                 TlsBackend::Rustls => {
-                    use crate::tls::{IgnoreHostname, NoVerifier};
-
-                    // Set root certificates.
-                    let mut root_cert_store = rustls::RootCertStore::empty();
-                    for cert in config.root_certs {
-                        cert.add_to_rustls(&mut root_cert_store)?;
-                    }
-
-                    #[cfg(feature = "rustls-tls-webpki-roots")]
-                    if config.tls_built_in_certs_webpki {
-                        root_cert_store.extend(webpki_roots::TLS_SERVER_ROOTS.iter().cloned());
-                    }
-
-                    #[cfg(feature = "rustls-tls-native-roots")]
-                    if config.tls_built_in_certs_native {
-                        let mut valid_count = 0;
-                        let mut invalid_count = 0;
-                        for cert in rustls_native_certs::load_native_certs()
-                            .map_err(crate::error::builder)?
-                        {
-                            // Continue on parsing errors, as native stores often include ancient or syntactically
-                            // invalid certificates, like root certificates without any X509 extensions.
-                            // Inspiration: https://github.com/rustls/rustls/blob/633bf4ba9d9521a95f68766d04c22e2b01e68318/rustls/src/anchors.rs#L105-L112
-                            match root_cert_store.add(cert.into()) {
-                                Ok(_) => valid_count += 1,
-                                Err(err) => {
-                                    invalid_count += 1;
-                                    log::debug!("rustls failed to parse DER certificate: {err:?}");
-                                }
-                            }
-                        }
-                        if valid_count == 0 && invalid_count > 0 {
-                            return Err(crate::error::builder(
-                                "zero valid certificates found in native root store",
-                            ));
-                        }
-                    }
-
-                    // Set TLS versions.
-                    let mut versions = rustls::ALL_VERSIONS.to_vec();
-
-                    if let Some(min_tls_version) = config.min_tls_version {
-                        versions.retain(|&supported_version| {
-                            match tls::Version::from_rustls(supported_version.version) {
-                                Some(version) => version >= min_tls_version,
-                                // Assume it's so new we don't know about it, allow it
-                                // (as of writing this is unreachable)
-                                None => true,
-                            }
-                        });
-                    }
-
-                    if let Some(max_tls_version) = config.max_tls_version {
-                        versions.retain(|&supported_version| {
-                            match tls::Version::from_rustls(supported_version.version) {
-                                Some(version) => version <= max_tls_version,
-                                None => false,
-                            }
-                        });
-                    }
-
-                    if versions.is_empty() {
-                        return Err(crate::error::builder("empty supported tls versions"));
-                    }
-
-                    // Allow user to have installed a runtime default.
-                    // If not, we use ring.
-                    let provider = rustls::crypto::CryptoProvider::get_default()
-                        .map(|arc| arc.clone())
-                        .unwrap_or_else(|| {
-                            #[cfg(not(feature = "__rustls-ring"))]
-                            panic!("No provider set");
-
-                            #[cfg(feature = "__rustls-ring")]
-                            Arc::new(rustls::crypto::ring::default_provider())
-                        });
-
-                    // Build TLS config
-                    let signature_algorithms = provider.signature_verification_algorithms;
-                    let config_builder = rustls::ClientConfig::builder_with_provider(provider)
-                        .with_protocol_versions(&versions)
-                        .map_err(|_| crate::error::builder("invalid TLS versions"))?;
-
-                    let config_builder = if !config.certs_verification {
-                        config_builder
-                            .dangerous()
-                            .with_custom_certificate_verifier(Arc::new(NoVerifier))
-                    } else if !config.hostname_verification {
-                        config_builder
-                            .dangerous()
-                            .with_custom_certificate_verifier(Arc::new(IgnoreHostname::new(
-                                root_cert_store,
-                                signature_algorithms,
-                            )))
-                    } else {
-                        config_builder.with_root_certificates(root_cert_store)
-                    };
-
-                    // Finalize TLS config
-                    let mut tls = if let Some(id) = config.identity {
-                        id.add_to_rustls(config_builder)?
-                    } else {
-                        config_builder.with_no_client_auth()
-                    };
-
-                    tls.enable_sni = config.tls_sni;
+                    let mut tls =
+                        rustls_config(&config, &config.root_certs, config.identity.as_ref())?;
+
+                    // Proxies are verified as usual.
+                    let proxy_tls = config.custom_verifier.as_ref().map(|verify| {
+                        let proxy_tls = tls.clone();
+                        let algorithms = tls.crypto_provider().signature_verification_algorithms;
+                        tls.dangerous()
+                            .set_certificate_verifier(Arc::new(tls::CustomVerifier::new(
+                                verify.clone(),
+                                algorithms,
+                            )));
+                        proxy_tls
+                    });
 
                     // ALPN protocol
                     match config.http_version_pref {
@@ -642,7 +861,22 @@ impl ClientBuilder {
                         )?;
                     }
 
-                    Connector::new_rustls_tls(
+                    // Servers with their own identity get the very same
+                    // settings, save for the client certificate.
+                    let host_tls = config
+                        .identities_for
+                        .iter()
+                        .map(|(pattern, identity)| {
+                            let with_identity =
+                                rustls_config(&config, &config.root_certs, Some(identity))?;
+                            let mut host_tls = tls.clone();
+                            host_tls.client_auth_cert_resolver =
+                                with_identity.client_auth_cert_resolver;
+                            Ok((pattern.clone(), Arc::new(host_tls)))
+                        })
+                        .collect::<crate::Result<Vec<_>>>()?;
+
+                    let mut connector = Connector::new_rustls_tls(
                         http,
                         tls,
                         proxies.clone(),
@@ -651,12 +885,21 @@ impl ClientBuilder {
                         #[cfg(any(
                             target_os = "android",
                             target_os = "fuchsia",
-                            target_os = "linux"
+                            target_os = "ios",
+                            target_os = "linux",
+                            target_os = "macos",
+                            target_os = "tvos",
+                            target_os = "watchos",
                         ))]
                         config.interface.as_deref(),
                         config.nodelay,
                         config.tls_info,
-                    )
+                    );
+                    if let Some(proxy_tls) = proxy_tls {
+                        connector.set_proxy_tls(Arc::new(proxy_tls));
+                    }
+                    connector.set_host_rustls_tls(host_tls);
+                    connector
                 }
                 #[cfg(any(feature = "native-tls", feature = "__rustls",))]
                 TlsBackend::UnknownPreconfigured => {
@@ -671,14 +914,51 @@ impl ClientBuilder {
                 http,
                 proxies.clone(),
                 config.local_address,
-                #[cfg(any(target_os = "android", target_os = "fuchsia", target_os = "linux"))]
+                #[cfg(any(
+                    target_os = "android",
+                    target_os = "fuchsia",
+                    target_os = "ios",
+                    target_os = "linux",
+                    target_os = "macos",
+                    target_os = "tvos",
+                    target_os = "watchos",
+                ))]
                 config.interface.as_deref(),
                 config.nodelay,
             )
         };
 
         connector.set_timeout(config.connect_timeout);
+        connector.set_connect_retry(config.connect_retry.take());
+        connector.set_host_timeouts(std::mem::take(&mut config.connect_timeouts_for));
+        connector.set_ipv4_fallback(config.ipv4_fallback, failover_resolver.clone());
+        connector
+            .set_connect_timeout_per_address(config.connect_timeout_per_address, failover_resolver);
+        connector.set_max_connection_lifetime(config.pool_max_connection_lifetime);
+        connector.set_connection_limits(config.max_connections_per_host, config.max_connections);
         connector.set_verbose(config.connection_verbose);
+        connector.set_wire_log(config.wire_log.take());
+        #[cfg(feature = "socks")]
+        connector.set_socks_local_dns(config.proxy_resolve_via_system_dns);
+        #[cfg(feature = "socks")]
+        connector.set_socks_resolver(socks_resolver);
+        #[cfg(all(feature = "__tls", feature = "cert-expiry-check"))]
+        connector.set_cert_expiry(config.cert_expiry);
+        #[cfg(unix)]
+        connector.set_unix_sockets(config.unix_sockets);
+        connector.set_on_proxy_selected(config.on_proxy_selected);
+        connector.set_transport(config.transport);
+        #[cfg(any(target_os = "android", target_os = "linux"))]
+        if let Some(refresh) = config.interface_refresh {
+            let pattern = config.interface_pattern.clone().ok_or_else(|| {
+                crate::error::builder(
+                    "`interface_auto_detect` needs a `preferred_interface_pattern`",
+                )
+            })?;
+            connector.set_interface_detector(Some(
+                crate::connect::interface_detect::InterfaceDetector::new(pattern, refresh),
+            ));
+        }
 
         let mut builder =
             hyper_util::client::legacy::Client::builder(hyper_util::rt::TokioExecutor::new());
@@ -719,6 +999,9 @@ impl ClientBuilder {
         builder.pool_idle_timeout(config.pool_idle_timeout);
         builder.pool_max_idle_per_host(config.pool_max_idle_per_host);
         connector.set_keepalive(config.tcp_keepalive);
+        connector.set_buffer_sizes(config.tcp_send_buffer_size, config.tcp_recv_buffer_size);
+        #[cfg(any(target_os = "android", target_os = "fuchsia", target_os = "linux"))]
+        connector.set_socket_mark(config.socket_mark);
 
         if config.http09_responses {
             builder.http09_responses(true);
@@ -740,11 +1023,16 @@ impl ClientBuilder {
             builder.http1_allow_spaces_after_header_name_in_responses(true);
         }
 
-        let proxies_maybe_http_auth = proxies.iter().any(|p| p.maybe_has_http_auth());
+        let pool_tracker = connector.pool_tracker();
+        let hyper = builder.build(ConnectorService::new(
+            connector.for_request(RequestConnect::direct()),
+            &config.connector_layers,
+        ));
 
         Ok(Client {
             inner: Arc::new(ClientRef {
                 accepts: config.accepts,
+                auto_accept_encoding: config.auto_accept_encoding,
                 #[cfg(feature = "cookies")]
                 cookie_store: config.cookie_store,
                 // Use match instead of map since config is partially moved
@@ -756,15 +1044,28 @@ impl ClientBuilder {
                     }
                     None => None,
                 },
-                hyper: builder.build(connector),
+                hyper,
                 headers: config.headers,
                 redirect_policy: config.redirect_policy,
                 referer: config.referer,
                 read_timeout: config.read_timeout,
+                response_read_timeout: config.response_read_timeout,
+                request_write_timeout: config.request_write_timeout,
+                #[cfg(feature = "http2")]
+                http2_rst_error_delay: config.http2_rst_error_delay,
                 request_timeout: config.timeout,
                 proxies,
-                proxies_maybe_http_auth,
+                no_proxy_private_networks: config.no_proxy_private_networks,
                 https_only: config.https_only,
+                netrc,
+                pool_tracker,
+                dns_cache,
+                request_pools: RequestPools {
+                    builder,
+                    connector,
+                    layers: config.connector_layers,
+                    clients: Mutex::new(PoolClients::default()),
+                },
             }),
         })
     }
@@ -959,6 +1260,8 @@ impl ClientBuilder {
     ///
     /// - When sending a request and if the request's headers do not already contain
     ///   an `Accept-Encoding` **and** `Range` values, the `Accept-Encoding` header is set to `deflate`.
+    ///   Other enabled encodings are listed alongside it, so `gzip(true).deflate(true)`
+    ///   sends `Accept-Encoding: gzip, deflate`.
     ///   The request body is **not** automatically compressed.
     /// - When receiving a response, if it's headers contain a `Content-Encoding` value that
     ///   equals to `deflate`, both values `Content-Encoding` and `Content-Length` are removed from the
@@ -1044,6 +1347,20 @@ impl ClientBuilder {
         }
     }
 
+    /// Set whether an `Accept-Encoding` header listing the enabled
+    /// decompression algorithms is added to requests.
+    ///
+    /// When disabled, responses are still decompressed according to their
+    /// `Content-Encoding` header, but the server isn't told they may be
+    /// compressed. This helps with servers that reject requests advertising
+    /// encodings they don't support.
+    ///
+    /// Default is `true`.
+    pub fn auto_accept_encoding(mut self, enable: bool) -> ClientBuilder {
+        self.config.auto_accept_encoding = enable;
+        self
+    }
+
     // Redirect options
 
     /// Set a `RedirectPolicy` for this client.
@@ -1088,6 +1405,123 @@ impl ClientBuilder {
         self
     }
 
+    /// Bypass every proxy for link-local and private network addresses.
+    ///
+    /// When enabled, `10.0.0.0/8`, `172.16.0.0/12`, `192.168.0.0/16`,
+    /// `169.254.0.0/16` and `fe80::/10` are appended to the `NoProxy` list of
+    /// each proxy, including the system one. Like other `NoProxy` entries,
+    /// they only match URLs whose host is an IP address in those networks.
+    ///
+    /// Default is `false`.
+    pub fn no_proxy_private_networks(mut self, enabled: bool) -> ClientBuilder {
+        self.config.no_proxy_private_networks = enabled;
+        self
+    }
+
+    /// Re-read the system proxy settings once they are older than `interval`.
+    ///
+    /// By default, the system proxy settings are read once, and used for as
+    /// long as the process runs. With this, the environment variables, and
+    /// the Windows registry, macOS system configuration or desktop settings
+    /// on Linux, are read again on a background thread once a connection is
+    /// made after `interval` has elapsed, so that a long-lived `Client`
+    /// follows a laptop moving between networks. Connections keep using the
+    /// previous settings until the new ones have been read.
+    ///
+    /// This has no effect if the system proxy isn't used, such as when a
+    /// proxy is added with `ClientBuilder::proxy`.
+    ///
+    /// Default is `None`.
+    pub fn system_proxy_refresh<D>(mut self, interval: D) -> ClientBuilder
+    where
+        D: Into<Option<Duration>>,
+    {
+        self.config.system_proxy_refresh = interval.into();
+        self
+    }
+
+    /// Read credentials from a netrc file, like curl's `--netrc`.
+    ///
+    /// Proxies without credentials in their URL use the login of their
+    /// host in the file, and so do requests without an `Authorization`
+    /// header. A `default` entry applies to every other host. After a
+    /// redirect, only the `machine` entry of the new host is used, and only
+    /// over https.
+    ///
+    /// With `None`, the file is `$NETRC`, or `.netrc` in the home directory
+    /// (`_netrc` on Windows), and it's fine for it not to exist. `build`
+    /// fails if a given path can't be read.
+    ///
+    /// Default is not reading a netrc file.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # fn run() -> Result<(), reqwest::Error> {
+    /// let client = reqwest::Client::builder()
+    ///     .netrc(None)
+    ///     .build()?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn netrc<P>(mut self, path: P) -> ClientBuilder
+    where
+        P: Into<Option<PathBuf>>,
+    {
+        self.config.netrc = Some(path.into());
+        self
+    }
+
+    /// Call `callback` each time a new connection is opened, with the proxy
+    /// it goes through, or `None` if it goes direct.
+    ///
+    /// Pooled connections are reused without calling it again. The proxy of
+    /// the connection a response came over is available from
+    /// `Response::proxy_info`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # fn run() -> Result<(), reqwest::Error> {
+    /// let client = reqwest::Client::builder()
+    ///     .on_proxy_selected(|selection| match selection.proxy() {
+    ///         Some(proxy) => println!("{:?} through {proxy}", selection.destination()),
+    ///         None => println!("{:?} direct", selection.destination()),
+    ///     })
+    ///     .build()?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn on_proxy_selected<F>(mut self, callback: F) -> ClientBuilder
+    where
+        F: Fn(&ProxySelection) + Send + Sync + 'static,
+    {
+        self.config.on_proxy_selected = Some(Arc::new(callback));
+        self
+    }
+
+    /// Set whether destination hostnames may be resolved locally when
+    /// connecting through a SOCKS5 proxy.
+    ///
+    /// With `socks5://` proxies, the destination is resolved with the system
+    /// resolver and its IP address sent to the proxy. When this is `false`,
+    /// the hostname is always sent to the proxy instead, as with `socks5h://`,
+    /// so no DNS query for the destination leaves this machine.
+    ///
+    /// HTTP and HTTPS proxies always receive the hostname as is.
+    ///
+    /// Default is `true`.
+    ///
+    /// # Optional
+    ///
+    /// This requires the optional `socks` feature to be enabled.
+    #[cfg(feature = "socks")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "socks")))]
+    pub fn proxy_resolve_via_system_dns(mut self, enabled: bool) -> ClientBuilder {
+        self.config.proxy_resolve_via_system_dns = enabled;
+        self
+    }
+
     // Timeout options
 
     /// Enables a total request timeout.
@@ -1113,6 +1547,39 @@ impl ClientBuilder {
         self
     }
 
+    /// Set a timeout for receiving the response.
+    ///
+    /// The timeout starts once the last byte of the request body has been
+    /// written, and requires the complete response body to be received
+    /// before the deadline is reached. It applies to HTTP/1 and HTTP/2.
+    ///
+    /// Unlike `read_timeout`, it is a single deadline for the whole
+    /// response rather than one reset by each read. Unlike `timeout`, it
+    /// leaves out connecting and sending the request, so slow uploads don't
+    /// count against it.
+    ///
+    /// Default is no timeout.
+    pub fn response_read_timeout(mut self, timeout: Duration) -> ClientBuilder {
+        self.config.response_read_timeout = Some(timeout);
+        self
+    }
+
+    /// Set a timeout for sending the request body.
+    ///
+    /// The timeout starts when the request is sent, once connected, and
+    /// requires the whole body to be written before the deadline is reached.
+    /// It applies to HTTP/1 and HTTP/2.
+    ///
+    /// Unlike `timeout`, it leaves out connecting and receiving the
+    /// response, so slow downloads don't count against it. `read_timeout`
+    /// only covers reads, never the request being written.
+    ///
+    /// Default is no timeout.
+    pub fn request_write_timeout(mut self, timeout: Duration) -> ClientBuilder {
+        self.config.request_write_timeout = Some(timeout);
+        self
+    }
+
     /// Set a timeout for only the connect phase of a `Client`.
     ///
     /// Default is `None`.
@@ -1126,46 +1593,211 @@ impl ClientBuilder {
         self
     }
 
-    /// Set whether connections should emit verbose logs.
+    /// Set a timeout for only the connect phase to hosts matching
+    /// `host_pattern`, overriding the one set with
+    /// [`connect_timeout`](ClientBuilder::connect_timeout).
     ///
-    /// Enabling this option will emit [log][] messages at the `TRACE` level
-    /// for read and write operations on connections.
+    /// The pattern may contain `*` wildcards, like `"*.example.com"`, and is
+    /// matched against the host of the URL, ignoring ASCII case. When
+    /// several patterns match, the one added first wins. A timeout set on
+    /// the request itself still takes precedence.
     ///
-    /// [log]: https://crates.io/crates/log
-    pub fn connection_verbose(mut self, verbose: bool) -> ClientBuilder {
-        self.config.connection_verbose = verbose;
-        self
-    }
-
-    // HTTP options
-
-    /// Set an optional timeout for idle sockets being kept-alive.
+    /// # Example
     ///
-    /// Pass `None` to disable timeout.
+    /// ```
+    /// # #[cfg(all(feature = "__rustls", not(feature = "__rustls-ring")))]
+    /// # let _ = rustls::crypto::ring::default_provider().install_default();
+    /// use std::time::Duration;
     ///
-    /// Default is 90 seconds.
-    pub fn pool_idle_timeout<D>(mut self, val: D) -> ClientBuilder
-    where
-        D: Into<Option<Duration>>,
-    {
-        self.config.pool_idle_timeout = val.into();
+    /// let client = reqwest::Client::builder()
+    ///     .connect_timeout(Duration::from_secs(2))
+    ///     .connect_timeout_for("*.far-away.example", Duration::from_secs(10))
+    ///     .build()
+    ///     .unwrap();
+    /// ```
+    pub fn connect_timeout_for(mut self, host_pattern: &str, timeout: Duration) -> ClientBuilder {
+        self.config
+            .connect_timeouts_for
+            .push((host_pattern.to_owned(), timeout));
         self
     }
 
-    /// Sets the maximum idle connection per host allowed in the pool.
-    pub fn pool_max_idle_per_host(mut self, max: usize) -> ClientBuilder {
-        self.config.pool_max_idle_per_host = max;
+    /// Try the addresses of a host one at a time, giving each at most
+    /// `timeout` to connect, when it has several.
+    ///
+    /// Without this, the connect timeout is split evenly between the
+    /// addresses, and IPv6 and IPv4 addresses are raced ("happy eyeballs").
+    /// With it, addresses are tried in the order they were resolved, moving
+    /// on to the next one when connecting is refused, unreachable or takes
+    /// longer than `timeout`. The address tried first rotates from one
+    /// connection to a host to the next, spreading connections across its
+    /// addresses round-robin. The connect timeout still bounds all attempts
+    /// together.
+    ///
+    /// This applies to connections made without a proxy.
+    ///
+    /// Default is `None`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # #[cfg(all(feature = "__rustls", not(feature = "__rustls-ring")))]
+    /// # let _ = rustls::crypto::ring::default_provider().install_default();
+    /// use std::time::Duration;
+    ///
+    /// let client = reqwest::Client::builder()
+    ///     .connect_timeout(Duration::from_secs(10))
+    ///     .connect_timeout_per_address(Duration::from_millis(500))
+    ///     .build()
+    ///     .unwrap();
+    /// ```
+    pub fn connect_timeout_per_address(mut self, timeout: Duration) -> ClientBuilder {
+        self.config.connect_timeout_per_address = Some(timeout);
         self
     }
 
-    /// Send headers as title case instead of lowercase.
-    pub fn http1_title_case_headers(mut self) -> ClientBuilder {
-        self.config.http1_title_case_headers = true;
+    /// Set a policy for retrying connections that fail to be established,
+    /// such as ones refused while a server restarts.
+    ///
+    /// Default is to not retry.
+    pub fn connect_retry(mut self, retry: ConnectRetry) -> ClientBuilder {
+        self.config.connect_retry = Some(retry);
         self
     }
 
-    /// Set whether HTTP/1 connections will accept obsolete line folding for
-    /// header values.
+    /// Set whether connections should emit verbose logs.
+    ///
+    /// Enabling this option will emit [log][] messages at the `TRACE` level
+    /// for read and write operations on connections.
+    ///
+    /// [log]: https://crates.io/crates/log
+    pub fn connection_verbose(mut self, verbose: bool) -> ClientBuilder {
+        self.config.connection_verbose = verbose;
+        self
+    }
+
+    /// Calls `hook` with the bytes read and written on every connection,
+    /// as they go over the wire, such as to show them like `curl --verbose`.
+    ///
+    /// `hook` is given the direction of the bytes, an id telling apart the
+    /// connections, and the bytes themselves. For `https` URLs, these are
+    /// the plain bytes inside TLS. It is called while the connection is
+    /// being polled, so it should be quick.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # #[cfg(all(feature = "__rustls", not(feature = "__rustls-ring")))]
+    /// # let _ = rustls::crypto::ring::default_provider().install_default();
+    /// use reqwest::WireDirection;
+    ///
+    /// let client = reqwest::Client::builder()
+    ///     .wire_log(|direction, id, bytes| {
+    ///         let arrow = match direction {
+    ///             WireDirection::Read => "<",
+    ///             WireDirection::Write => ">",
+    ///         };
+    ///         eprintln!("{id:08x} {arrow} {}", String::from_utf8_lossy(bytes));
+    ///     })
+    ///     .build()?;
+    /// # Ok::<(), reqwest::Error>(())
+    /// ```
+    pub fn wire_log<F>(mut self, hook: F) -> ClientBuilder
+    where
+        F: Fn(WireDirection, u32, &[u8]) + Send + Sync + 'static,
+    {
+        self.config.wire_log = Some(Arc::new(hook));
+        self
+    }
+
+    // HTTP options
+
+    /// Set an optional timeout for idle sockets being kept-alive.
+    ///
+    /// Pass `None` to disable timeout.
+    ///
+    /// Default is 90 seconds.
+    pub fn pool_idle_timeout<D>(mut self, val: D) -> ClientBuilder
+    where
+        D: Into<Option<Duration>>,
+    {
+        self.config.pool_idle_timeout = val.into();
+        self
+    }
+
+    /// Sets the maximum idle connection per host allowed in the pool.
+    ///
+    /// Connections through each proxy are pooled apart, with their own
+    /// limit.
+    pub fn pool_max_idle_per_host(mut self, max: usize) -> ClientBuilder {
+        self.config.pool_max_idle_per_host = max;
+        self
+    }
+
+    /// Sets the longest a connection is kept in the pool.
+    ///
+    /// A connection older than this is closed once it becomes idle, instead
+    /// of being reused, so long-lived clients pick up DNS changes and spread
+    /// over new backends. A request in progress is never interrupted.
+    ///
+    /// Default is no limit. Does not apply to HTTP/2 or HTTP/3 connections.
+    pub fn pool_max_connection_lifetime(mut self, lifetime: Duration) -> ClientBuilder {
+        self.config.pool_max_connection_lifetime = Some(lifetime);
+        self
+    }
+
+    /// Sets the maximum number of connections open to each host at once.
+    ///
+    /// Once a host has this many connections, a request needing a new one
+    /// waits for one of them to become idle, or to close. The wait counts
+    /// against the connect timeout, or lasts at most 30 seconds without one,
+    /// and fails with a timeout error past it.
+    ///
+    /// Connections are counted per scheme, host and port, whether idle in
+    /// the pool or in use. An idle connection only frees its slot for
+    /// requests it can't serve itself, such as ones through another proxy,
+    /// once it is closed; see [`ClientBuilder::pool_idle_timeout`] and
+    /// [`ClientBuilder::pool_max_idle_per_host`] to close them sooner.
+    ///
+    /// Default is no limit. `ClientBuilder::build` returns an error for a
+    /// limit of 0. Does not apply to HTTP/3 connections.
+    pub fn max_connections_per_host(mut self, max: usize) -> ClientBuilder {
+        if max == 0 {
+            self.config.error = Some(crate::error::builder(
+                "max_connections_per_host must be at least 1",
+            ));
+        }
+        self.config.max_connections_per_host = Some(max);
+        self
+    }
+
+    /// Sets the maximum number of connections open at once, to all hosts.
+    ///
+    /// Beyond it, requests needing a new connection wait as with
+    /// [`ClientBuilder::max_connections_per_host`]. Connections idle in the
+    /// pool count against the limit, but one to another host is closed for
+    /// each request waiting, rather than left until
+    /// [`ClientBuilder::pool_idle_timeout`]. HTTP/2 connections are left
+    /// open.
+    ///
+    /// Default is no limit. `ClientBuilder::build` returns an error for a
+    /// limit of 0.
+    pub fn max_connections(mut self, max: usize) -> ClientBuilder {
+        if max == 0 {
+            self.config.error = Some(crate::error::builder("max_connections must be at least 1"));
+        }
+        self.config.max_connections = Some(max);
+        self
+    }
+
+    /// Send headers as title case instead of lowercase.
+    pub fn http1_title_case_headers(mut self) -> ClientBuilder {
+        self.config.http1_title_case_headers = true;
+        self
+    }
+
+    /// Set whether HTTP/1 connections will accept obsolete line folding for
+    /// header values.
     ///
     /// Newline codepoints (`\r` and `\n`) will be transformed to spaces when
     /// parsing.
@@ -1178,6 +1810,18 @@ impl ClientBuilder {
         self
     }
 
+    /// Set whether HTTP/1 connections will accept obsolete line folding for
+    /// header values.
+    ///
+    /// Obsolete line folding was deprecated by RFC 7230, so this is `false`
+    /// by default and must be opted into explicitly.
+    ///
+    /// This is a shorthand for
+    /// [`ClientBuilder::http1_allow_obsolete_multiline_headers_in_responses`].
+    pub fn http1_allow_obsolete_multiline_headers(self, value: bool) -> ClientBuilder {
+        self.http1_allow_obsolete_multiline_headers_in_responses(value)
+    }
+
     /// Sets whether invalid header lines should be silently ignored in HTTP/1 responses.
     pub fn http1_ignore_invalid_headers_in_responses(mut self, value: bool) -> ClientBuilder {
         self.config.http1_ignore_invalid_headers_in_responses = value;
@@ -1210,6 +1854,18 @@ impl ClientBuilder {
         self
     }
 
+    /// Set whether HTTP/0.9 responses are accepted.
+    ///
+    /// When enabled, a response that doesn't start with a status line is
+    /// treated as the body of a `200 OK` response, and
+    /// `Response::version()` returns `HTTP/0.9`.
+    ///
+    /// Default is `false`.
+    pub fn accept_http_0_9(mut self, enabled: bool) -> ClientBuilder {
+        self.config.http09_responses = enabled;
+        self
+    }
+
     /// Only use HTTP/2.
     #[cfg(feature = "http2")]
     #[cfg_attr(docsrs, doc(cfg(feature = "http2")))]
@@ -1263,10 +1919,21 @@ impl ClientBuilder {
     /// Sets the maximum frame size to use for HTTP2.
     ///
     /// Default is currently 16,384 but may change internally to optimize for common uses.
+    ///
+    /// The size must be between 16,384 and 16,777,215 (RFC 7540, Section 6.5.2),
+    /// otherwise `ClientBuilder::build` will return an error.
     #[cfg(feature = "http2")]
     #[cfg_attr(docsrs, doc(cfg(feature = "http2")))]
     pub fn http2_max_frame_size(mut self, sz: impl Into<Option<u32>>) -> ClientBuilder {
-        self.config.http2_max_frame_size = sz.into();
+        let sz = sz.into();
+        if let Some(sz) = sz {
+            if !(16_384..=16_777_215).contains(&sz) {
+                self.config.error = Some(crate::error::builder(format!(
+                    "http2 max frame size {sz} is out of range (16384..=16777215)"
+                )));
+            }
+        }
+        self.config.http2_max_frame_size = sz;
         self
     }
 
@@ -1309,6 +1976,22 @@ impl ClientBuilder {
         self
     }
 
+    /// Sets a delay before retrying a request whose HTTP2 stream was reset
+    /// by the server with `REFUSED_STREAM`.
+    ///
+    /// Such requests are retried automatically, by default right away. A
+    /// random extra delay of up to `jitter_fraction` times `delay` is added,
+    /// so that many requests refused at once aren't all retried together.
+    /// The fraction is taken between 0 and 1.
+    ///
+    /// Default is no delay.
+    #[cfg(feature = "http2")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "http2")))]
+    pub fn http2_rst_error_delay(mut self, delay: Duration, jitter_fraction: f64) -> ClientBuilder {
+        self.config.http2_rst_error_delay = Some((delay, jitter_fraction));
+        self
+    }
+
     // TCP options
 
     /// Set whether sockets have `TCP_NODELAY` enabled.
@@ -1340,8 +2023,26 @@ impl ClientBuilder {
         self
     }
 
+    /// Bind to the local IP address given by an environment variable.
+    ///
+    /// The variable is read when the client is built. If it is set, it
+    /// overrides [`local_address`](ClientBuilder::local_address); if it is
+    /// unset or empty, this does nothing.
+    ///
+    /// # Errors
+    ///
+    /// `ClientBuilder::build` fails if the variable's value is not an IP
+    /// address.
+    pub fn local_address_from_env(mut self, var: &str) -> ClientBuilder {
+        self.config.local_address_env = Some(var.to_string());
+        self
+    }
+
     /// Bind to an interface by `SO_BINDTODEVICE`.
     ///
+    /// On macOS and iOS, `IP_BOUND_IF` and `IPV6_BOUND_IF` are used
+    /// instead, with the index of the named interface.
+    ///
     /// # Example
     ///
     /// ```
@@ -1352,12 +2053,114 @@ impl ClientBuilder {
     ///     .interface(interface)
     ///     .build().unwrap();
     /// ```
-    #[cfg(any(target_os = "android", target_os = "fuchsia", target_os = "linux"))]
+    #[cfg(any(
+        target_os = "android",
+        target_os = "fuchsia",
+        target_os = "ios",
+        target_os = "linux",
+        target_os = "macos",
+        target_os = "tvos",
+        target_os = "watchos",
+    ))]
     pub fn interface(mut self, interface: &str) -> ClientBuilder {
         self.config.interface = Some(interface.to_string());
         self
     }
 
+    /// Bind to the interface named by an environment variable, such as one
+    /// set by a container orchestrator.
+    ///
+    /// The variable is read when the client is built. If it is set, it
+    /// overrides [`interface`](ClientBuilder::interface); if it is unset or
+    /// empty, this does nothing.
+    ///
+    /// # Errors
+    ///
+    /// `ClientBuilder::build` fails if the variable's value is not a valid
+    /// interface name.
+    #[cfg(any(
+        target_os = "android",
+        target_os = "fuchsia",
+        target_os = "ios",
+        target_os = "linux",
+        target_os = "macos",
+        target_os = "tvos",
+        target_os = "watchos",
+    ))]
+    pub fn local_interface_from_env(mut self, var: &str) -> ClientBuilder {
+        self.config.interface_env = Some(var.to_string());
+        self
+    }
+
+    /// Pick the interface to bind to from the interfaces that are currently up.
+    ///
+    /// The list of network interfaces is read when the client is built, and
+    /// read again in the background once a new connection is made at least
+    /// `refresh_interval` after the last read, so interfaces that appear later
+    /// (a VPN, for instance) are picked up. Connections use the first
+    /// interface, by name, that matches the
+    /// [`preferred_interface_pattern`](ClientBuilder::preferred_interface_pattern)
+    /// and whose operational state is `up`. If none does, the interface set
+    /// with [`interface`](ClientBuilder::interface), if any, is used.
+    ///
+    /// Building the client fails if no pattern is set.
+    ///
+    /// Default is disabled.
+    #[cfg(any(target_os = "android", target_os = "linux"))]
+    pub fn interface_auto_detect(mut self, refresh_interval: Duration) -> ClientBuilder {
+        self.config.interface_refresh = Some(refresh_interval);
+        self
+    }
+
+    /// Set the pattern of interface names to pick from when
+    /// [`interface_auto_detect`](ClientBuilder::interface_auto_detect) is enabled.
+    ///
+    /// The pattern may contain `*` and `?` wildcards, like `"eth*"`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # #[cfg(all(feature = "__rustls", not(feature = "__rustls-ring")))]
+    /// # let _ = rustls::crypto::ring::default_provider().install_default();
+    /// use std::time::Duration;
+    ///
+    /// let client = reqwest::Client::builder()
+    ///     .interface_auto_detect(Duration::from_secs(30))
+    ///     .preferred_interface_pattern("wg*")
+    ///     .build().unwrap();
+    /// ```
+    #[cfg(any(target_os = "android", target_os = "linux"))]
+    pub fn preferred_interface_pattern(mut self, pattern: &str) -> ClientBuilder {
+        self.config.interface_pattern = Some(pattern.to_string());
+        self
+    }
+
+    /// Set the `SO_MARK` option on outgoing TCP sockets.
+    ///
+    /// Packets sent by the client then carry this mark, which policy routing
+    /// rules (`ip rule add fwmark ...`) and netfilter can match on. The mark
+    /// is set before connecting, so it also steers the initial route lookup.
+    /// It applies to connections to servers and to HTTP and SOCKS proxies,
+    /// but not to streams opened by custom proxies or a custom connector.
+    ///
+    /// Setting the mark requires the `CAP_NET_ADMIN` capability; without it,
+    /// connecting fails.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # #[cfg(all(feature = "__rustls", not(feature = "__rustls-ring")))]
+    /// # let _ = rustls::crypto::ring::default_provider().install_default();
+    /// let client = reqwest::Client::builder()
+    ///     .socket_mark(0x100)
+    ///     .build().unwrap();
+    /// ```
+    #[cfg(any(target_os = "android", target_os = "fuchsia", target_os = "linux"))]
+    pub fn socket_mark(mut self, mark: u32) -> ClientBuilder {
+        self.config.socket_mark = Some(mark);
+        self
+    }
+
     /// Set that all sockets have `SO_KEEPALIVE` set with the supplied duration.
     ///
     /// If `None`, the option will not be set.
@@ -1369,6 +2172,48 @@ impl ClientBuilder {
         self
     }
 
+    /// Set the `SO_SNDBUF` option on outgoing TCP sockets.
+    ///
+    /// It applies to connections to servers and to HTTP and SOCKS proxies,
+    /// but not to streams opened by custom proxies or a custom connector.
+    ///
+    /// Default is the OS default.
+    pub fn tcp_send_buffer_size(mut self, size: usize) -> ClientBuilder {
+        self.config.tcp_send_buffer_size = Some(size);
+        self
+    }
+
+    /// Set the `SO_RCVBUF` option on outgoing TCP sockets.
+    ///
+    /// It is set before connecting, so that it can affect the TCP window
+    /// offered to the server. It applies to connections to servers and to
+    /// HTTP and SOCKS proxies, but not to streams opened by custom proxies or
+    /// a custom connector.
+    ///
+    /// Default is the OS default.
+    pub fn tcp_recv_buffer_size(mut self, size: usize) -> ClientBuilder {
+        self.config.tcp_recv_buffer_size = Some(size);
+        self
+    }
+
+    /// Fall back to IPv4 when connecting over IPv6 fails or stalls.
+    ///
+    /// When a host resolves to IPv6 addresses, an IPv4 connection attempt
+    /// is started if the IPv6 one hasn't completed within 200ms, or
+    /// immediately if it fails. Whichever connects first is used. Only the
+    /// IPv4 addresses returned by the resolver of the client are tried, so
+    /// there's nothing to fall back to for a host without any. This keeps
+    /// requests fast on networks where IPv6 is routed but broken.
+    ///
+    /// Together with `connect_timeout_per_address`, the addresses are tried
+    /// one at a time instead.
+    ///
+    /// Default is `false`.
+    pub fn fallback_to_ipv4_on_ipv6_failure(mut self, enabled: bool) -> ClientBuilder {
+        self.config.ipv4_fallback = enabled;
+        self
+    }
+
     // TLS options
 
     /// Add a custom root certificate.
@@ -1394,6 +2239,57 @@ impl ClientBuilder {
         self
     }
 
+    /// Add a certificate revocation list, to fail connections to servers
+    /// whose certificate, or that of an issuer, it revokes.
+    ///
+    /// Certificates that no list was given for are accepted. Revocation
+    /// lists can't be used along with `danger_accept_invalid_hostnames` or
+    /// `dangerous_custom_verifier`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use std::fs::File;
+    /// # use std::io::Read;
+    /// # fn build_client() -> Result<(), Box<dyn std::error::Error>> {
+    /// let mut buf = Vec::new();
+    /// File::open("revoked.pem")?.read_to_end(&mut buf)?;
+    /// let crls = reqwest::tls::CertificateRevocationList::from_pem_bundle(&buf)?;
+    ///
+    /// let client = reqwest::Client::builder()
+    ///     .use_rustls_tls()
+    ///     .add_crls(crls)
+    ///     .build()?;
+    /// # drop(client);
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// # Optional
+    ///
+    /// This requires the `rustls-tls(-...)` Cargo feature enabled, and the
+    /// `rustls` backend to be used. native-tls has no revocation settings,
+    /// so building a client with it fails.
+    #[cfg(feature = "__rustls")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "rustls-tls")))]
+    pub fn add_crl(mut self, crl: tls::CertificateRevocationList) -> ClientBuilder {
+        self.config.crls.push(crl);
+        self
+    }
+
+    /// Add several certificate revocation lists.
+    ///
+    /// See `add_crl` for details.
+    #[cfg(feature = "__rustls")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "rustls-tls")))]
+    pub fn add_crls(
+        mut self,
+        crls: impl IntoIterator<Item = tls::CertificateRevocationList>,
+    ) -> ClientBuilder {
+        self.config.crls.extend(crls);
+        self
+    }
+
     /// Controls the use of built-in/preloaded certificates during certificate validation.
     ///
     /// Defaults to `true` -- built-in system certs will be used.
@@ -1469,6 +2365,32 @@ impl ClientBuilder {
         self
     }
 
+    /// Sets the identity to be used for client certificate authentication
+    /// with servers whose host matches `host_pattern`, in place of the one
+    /// set with [`identity`](ClientBuilder::identity).
+    ///
+    /// The pattern may contain `*` wildcards, like `"*.internal.example"`,
+    /// and is matched against the host of the URL, ignoring ASCII case.
+    /// When several patterns match, the one added first wins. Connections
+    /// are only reused for the host they were opened to, so they always
+    /// carry the identity chosen for it.
+    ///
+    /// This doesn't apply to HTTP/3 connections, nor to clients given a
+    /// preconfigured TLS backend.
+    ///
+    /// # Optional
+    ///
+    /// This requires the optional `native-tls` or `rustls-tls(-...)` feature to be
+    /// enabled.
+    #[cfg(any(feature = "native-tls", feature = "__rustls"))]
+    #[cfg_attr(docsrs, doc(cfg(any(feature = "native-tls", feature = "rustls-tls"))))]
+    pub fn identity_for(mut self, host_pattern: &str, identity: Identity) -> ClientBuilder {
+        self.config
+            .identities_for
+            .push((host_pattern.to_owned(), identity));
+        self
+    }
+
     /// Controls the use of hostname verification.
     ///
     /// Defaults to `false`.
@@ -1531,6 +2453,67 @@ impl ClientBuilder {
         self
     }
 
+    /// Leaves the decision to trust a server certificate to `verify`.
+    ///
+    /// `verify` is called with the DER encoded certificate chain presented
+    /// by the server, leaf first, and the host it was connected to. The
+    /// connection fails with the error it returns. With `rustls`, the chain
+    /// is everything the server sent, and the handshake signatures are still
+    /// checked. With `native-tls`, `verify` only sees the leaf certificate,
+    /// checked once the handshake is done, and connections whose leaf
+    /// certificate can't be read are rejected.
+    ///
+    /// This replaces the checks against root certificates and of host names,
+    /// as well as `danger_accept_invalid_certs` and
+    /// `danger_accept_invalid_hostnames`. It only applies to the servers
+    /// requests are sent to; HTTPS proxies are verified as usual. It has no
+    /// effect with a preconfigured TLS backend.
+    ///
+    /// # Warning
+    ///
+    /// `verify` is the only thing standing between the client and a
+    /// man-in-the-middle attack. A function that accepts a certificate
+    /// without checking it to be the expected one for the host makes the
+    /// connection as unsafe as `danger_accept_invalid_certs`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # fn doc(pinned: Vec<u8>) -> Result<(), reqwest::Error> {
+    /// let client = reqwest::Client::builder()
+    ///     .dangerous_custom_verifier(move |chain, host| {
+    ///         if host == "internal.example" && chain[0] == &pinned[..] {
+    ///             Ok(())
+    ///         } else {
+    ///             Err(format!("untrusted certificate for {host}").into())
+    ///         }
+    ///     })
+    ///     .build()?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// # Optional
+    ///
+    /// This requires the optional `default-tls`, `native-tls`, or `rustls-tls(-...)`
+    /// feature to be enabled.
+    #[cfg(feature = "__tls")]
+    #[cfg_attr(
+        docsrs,
+        doc(cfg(any(
+            feature = "default-tls",
+            feature = "native-tls",
+            feature = "rustls-tls"
+        )))
+    )]
+    pub fn dangerous_custom_verifier<F>(mut self, verify: F) -> ClientBuilder
+    where
+        F: Fn(&[&[u8]], &str) -> Result<(), BoxError> + Send + Sync + 'static,
+    {
+        self.config.custom_verifier = Some(Arc::new(verify));
+        self
+    }
+
     /// Controls the use of TLS server name indication.
     ///
     /// Defaults to `true`.
@@ -1712,31 +2695,272 @@ impl ClientBuilder {
         self
     }
 
-    /// Restrict the Client to be used with HTTPS only requests.
+    /// Logs the secrets of TLS sessions to the file named by the
+    /// `SSLKEYLOGFILE` environment variable, if it is set.
     ///
-    /// Defaults to false.
-    pub fn https_only(mut self, enabled: bool) -> ClientBuilder {
-        self.config.https_only = enabled;
-        self
-    }
-
-    #[doc(hidden)]
-    #[cfg(feature = "hickory-dns")]
-    #[cfg_attr(docsrs, doc(cfg(feature = "hickory-dns")))]
-    #[deprecated(note = "use `hickory_dns` instead")]
-    pub fn trust_dns(mut self, enable: bool) -> ClientBuilder {
-        self.config.hickory_dns = enable;
-        self
-    }
-
-    /// Enables the [hickory-dns](hickory_resolver) async resolver instead of a default threadpool
-    /// using `getaddrinfo`.
+    /// The file uses the NSS key log format, which tools such as Wireshark
+    /// can decrypt captured traffic with.
     ///
-    /// If the `hickory-dns` feature is turned on, the default option is enabled.
+    /// # Warning
+    ///
+    /// Anyone who can read the file can decrypt the sessions. Only use this
+    /// while debugging.
     ///
     /// # Optional
     ///
-    /// This requires the optional `hickory-dns` feature to be enabled
+    /// This requires the `rustls-tls(-...)` Cargo feature enabled, and the
+    /// `rustls` backend to be used. native-tls has no way to log secrets,
+    /// so building a client with it fails.
+    #[cfg(feature = "__rustls")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "rustls-tls")))]
+    pub fn tls_key_log_file(mut self) -> ClientBuilder {
+        self.config.tls_key_log = Some(tls::KeyLog::File);
+        self
+    }
+
+    /// Calls `log` with the secrets of TLS sessions, as the NSS key log
+    /// label, the client random of the session, and the secret.
+    ///
+    /// # Warning
+    ///
+    /// The secrets are enough to decrypt the sessions. Only use this while
+    /// debugging.
+    ///
+    /// # Optional
+    ///
+    /// This requires the `rustls-tls(-...)` Cargo feature enabled, and the
+    /// `rustls` backend to be used. native-tls has no way to log secrets,
+    /// so building a client with it fails.
+    #[cfg(feature = "__rustls")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "rustls-tls")))]
+    pub fn tls_key_log<F>(mut self, log: F) -> ClientBuilder
+    where
+        F: Fn(&str, &[u8], &[u8]) + Send + Sync + 'static,
+    {
+        self.config.tls_key_log = Some(tls::KeyLog::Callback(Arc::new(log)));
+        self
+    }
+
+    /// Controls whether TLS 1.2 sessions may be resumed with session
+    /// tickets, rather than only with session ids.
+    ///
+    /// TLS 1.3 sessions can only be resumed with tickets, so this doesn't
+    /// affect them; use `tls_session_cache_size(0)` to not resume sessions
+    /// at all.
+    ///
+    /// Defaults to `true`.
+    ///
+    /// # Optional
+    ///
+    /// This requires the `rustls-tls(-...)` Cargo feature enabled, and the
+    /// `rustls` backend to be used. native-tls has no session resumption
+    /// settings, so building a client with it fails.
+    #[cfg(feature = "__rustls")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "rustls-tls")))]
+    pub fn tls_session_tickets(mut self, enabled: bool) -> ClientBuilder {
+        self.config.tls_session_tickets = enabled;
+        self
+    }
+
+    /// Sets how many TLS sessions the client keeps to resume, or disables
+    /// resumption if `size` is 0.
+    ///
+    /// Defaults to 256.
+    ///
+    /// # Optional
+    ///
+    /// This requires the `rustls-tls(-...)` Cargo feature enabled, and the
+    /// `rustls` backend to be used. native-tls has no session resumption
+    /// settings, so building a client with it fails.
+    #[cfg(feature = "__rustls")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "rustls-tls")))]
+    pub fn tls_session_cache_size(mut self, size: usize) -> ClientBuilder {
+        self.config.tls_session_cache = Some(tls::SessionCache::new(size));
+        self
+    }
+
+    /// Keeps the TLS sessions to resume in `cache`, which may be shared with
+    /// other clients.
+    ///
+    /// Clients only resume each other's sessions if they verify servers the
+    /// same way: with the same root certificates and `danger_accept_invalid_*`
+    /// settings, no client identity, and no custom verifier.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # fn doc() -> Result<(), reqwest::Error> {
+    /// let cache = reqwest::tls::SessionCache::new(1024);
+    /// let a = reqwest::Client::builder()
+    ///     .use_rustls_tls()
+    ///     .tls_session_cache(cache.clone())
+    ///     .build()?;
+    /// let b = reqwest::Client::builder()
+    ///     .use_rustls_tls()
+    ///     .tls_session_cache(cache)
+    ///     .build()?;
+    /// # drop((a, b));
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// # Optional
+    ///
+    /// This requires the `rustls-tls(-...)` Cargo feature enabled, and the
+    /// `rustls` backend to be used. native-tls has no session resumption
+    /// settings, so building a client with it fails.
+    #[cfg(feature = "__rustls")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "rustls-tls")))]
+    pub fn tls_session_cache(mut self, cache: tls::SessionCache) -> ClientBuilder {
+        self.config.tls_session_cache = Some(cache);
+        self
+    }
+
+    /// Requires servers to staple an OCSP response to their certificate,
+    /// and fails connections to those whose response says it was revoked.
+    ///
+    /// The response isn't checked to be signed by the issuer of the
+    /// certificate, nor to be recent, so this only guards against servers
+    /// presenting a certificate they know to be revoked. Use `add_crl` to
+    /// check revocation against a trusted source. Nothing is fetched from
+    /// OCSP responders or CRL distribution points.
+    ///
+    /// Defaults to `false`.
+    ///
+    /// # Optional
+    ///
+    /// This requires the `rustls-tls(-...)` Cargo feature enabled, and the
+    /// `rustls` backend to be used. native-tls has no revocation settings,
+    /// so building a client with it fails.
+    #[cfg(feature = "__rustls")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "rustls-tls")))]
+    pub fn tls_ocsp_stapling_required(mut self, required: bool) -> ClientBuilder {
+        self.config.tls_ocsp_stapling_required = required;
+        self
+    }
+
+    /// Requires the certificates of servers to carry signed certificate
+    /// timestamps (SCTs) from these Certificate Transparency logs, as
+    /// browsers do.
+    ///
+    /// Only certificates that chain to a built-in root certificate are
+    /// checked; those trusted through `add_root_certificate` are left
+    /// alone. SCTs are looked for in the certificate itself, where public
+    /// CAs embed them. Ones the server sends in the TLS handshake aren't
+    /// seen. See `tls_ct_min_scts` for how many are required.
+    ///
+    /// Calling this several times adds up the logs. Logs are known by their
+    /// public key, which log lists publish, and SCTs from other logs are
+    /// ignored, so the list should be kept up to date.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # fn doc() -> Result<(), reqwest::Error> {
+    /// // Public keys of logs, from a log list.
+    /// let keys = [
+    ///     "MFkwEwYHKoZIzj0CAQYIKoZIzj0DAQcDQgAE6ZymtWvwBD8qsrvbRw7uvccuaPJq\
+    ///      qxv81E5/nrqVZLbf9rHkn6Dh9iEcSo/XZC6T6AsG8fWHYHCBDxaCvmYUFQ==",
+    /// ];
+    /// let logs = keys
+    ///     .iter()
+    ///     .map(|key| reqwest::tls::CtLog::from_base64(key))
+    ///     .collect::<Result<Vec<_>, _>>()?;
+    /// let client = reqwest::Client::builder()
+    ///     .use_rustls_tls()
+    ///     .tls_ct_logs(logs)
+    ///     .build()?;
+    /// # drop(client);
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// # Optional
+    ///
+    /// This requires the `rustls-tls(-...)` Cargo feature enabled, and the
+    /// `rustls` backend to be used. native-tls has no Certificate
+    /// Transparency settings, so building a client with it fails.
+    #[cfg(feature = "__rustls")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "rustls-tls")))]
+    pub fn tls_ct_logs(mut self, logs: impl IntoIterator<Item = tls::CtLog>) -> ClientBuilder {
+        self.config.tls_ct_logs.extend(logs);
+        self
+    }
+
+    /// Sets how many of the logs given to `tls_ct_logs` must have signed an
+    /// SCT of a server certificate.
+    ///
+    /// Defaults to 2.
+    ///
+    /// # Optional
+    ///
+    /// This requires the `rustls-tls(-...)` Cargo feature enabled.
+    #[cfg(feature = "__rustls")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "rustls-tls")))]
+    pub fn tls_ct_min_scts(mut self, min: usize) -> ClientBuilder {
+        self.config.tls_ct_min_scts = min;
+        self
+    }
+
+    /// Log a warning when a server certificate expires within `days` days.
+    ///
+    /// The check runs once per new connection, after the TLS handshake.
+    ///
+    /// # Optional
+    ///
+    /// This requires the optional `cert-expiry-check` feature, and one of the
+    /// `default-tls`, `native-tls`, or `rustls-tls(-...)` features, to be
+    /// enabled.
+    #[cfg(all(feature = "__tls", feature = "cert-expiry-check"))]
+    #[cfg_attr(docsrs, doc(cfg(feature = "cert-expiry-check")))]
+    pub fn ssl_expiry_warning_days(mut self, days: u64) -> ClientBuilder {
+        self.config.cert_expiry.warning_days = Some(days);
+        self
+    }
+
+    /// Fail connecting to servers whose certificate expires within `days`
+    /// days.
+    ///
+    /// The connection is closed after the TLS handshake, and the request
+    /// fails with a connect error.
+    ///
+    /// # Optional
+    ///
+    /// This requires the optional `cert-expiry-check` feature, and one of the
+    /// `default-tls`, `native-tls`, or `rustls-tls(-...)` features, to be
+    /// enabled.
+    #[cfg(all(feature = "__tls", feature = "cert-expiry-check"))]
+    #[cfg_attr(docsrs, doc(cfg(feature = "cert-expiry-check")))]
+    pub fn ssl_expiry_fail_days(mut self, days: u64) -> ClientBuilder {
+        self.config.cert_expiry.fail_days = Some(days);
+        self
+    }
+
+    /// Restrict the Client to be used with HTTPS only requests.
+    ///
+    /// Defaults to false.
+    pub fn https_only(mut self, enabled: bool) -> ClientBuilder {
+        self.config.https_only = enabled;
+        self
+    }
+
+    #[doc(hidden)]
+    #[cfg(feature = "hickory-dns")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "hickory-dns")))]
+    #[deprecated(note = "use `hickory_dns` instead")]
+    pub fn trust_dns(mut self, enable: bool) -> ClientBuilder {
+        self.config.hickory_dns = enable;
+        self
+    }
+
+    /// Enables the [hickory-dns](hickory_resolver) async resolver instead of a default threadpool
+    /// using `getaddrinfo`.
+    ///
+    /// If the `hickory-dns` feature is turned on, the default option is enabled.
+    ///
+    /// # Optional
+    ///
+    /// This requires the optional `hickory-dns` feature to be enabled
     ///
     /// # Warning
     ///
@@ -1774,6 +2998,11 @@ impl ClientBuilder {
 
     /// Override DNS resolution for specific domains to a particular IP address.
     ///
+    /// `domain` may be a wildcard such as `*.service.consul`, which matches
+    /// every subdomain of `service.consul`, but not `service.consul` itself.
+    /// Exact domains take precedence over wildcards, and longer wildcards
+    /// over shorter ones.
+    ///
     /// Warning
     ///
     /// Since the DNS protocol has no notion of ports, if you wish to send
@@ -1786,6 +3015,10 @@ impl ClientBuilder {
 
     /// Override DNS resolution for specific domains to particular IP addresses.
     ///
+    /// The addresses are tried in turn, so later ones serve as fallbacks
+    /// when connecting to earlier ones fails. `domain` may be a wildcard, as
+    /// with `resolve`.
+    ///
     /// Warning
     ///
     /// Since the DNS protocol has no notion of ports, if you wish to send
@@ -1799,6 +3032,160 @@ impl ClientBuilder {
         self
     }
 
+    /// Look up names in the hosts file at `path` before using DNS, like the
+    /// system does with `/etc/hosts`.
+    ///
+    /// Each line of the file holds an IP address followed by the names it's
+    /// for, and `#` starts a comment. A name listed on several lines gets all
+    /// their addresses. Overrides passed to `resolve` and `resolve_to_addrs`
+    /// take precedence over the file.
+    ///
+    /// The file is read once, by `ClientBuilder::build`, which fails if it
+    /// can't be read.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # fn run() -> Result<(), reqwest::Error> {
+    /// let client = reqwest::Client::builder()
+    ///     .hosts_file("/etc/hosts.test")
+    ///     .build()?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn hosts_file<P: AsRef<Path>>(mut self, path: P) -> ClientBuilder {
+        self.config.hosts_file = Some(path.as_ref().to_path_buf());
+        self
+    }
+
+    /// Open connections with `connector` instead of over TCP.
+    ///
+    /// `connector` is called with the URI of each new connection's
+    /// destination, and returns the stream to send requests over, such as an
+    /// in-memory pipe in tests, or a tunnel reqwest doesn't know about. For
+    /// `https` URLs, TLS is negotiated over that stream as usual.
+    ///
+    /// Proxies and `connect_host_via_unix_socket` are not used by a client
+    /// with its own transport: `connector` decides how each destination is
+    /// reached.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use std::future::Future;
+    /// use std::pin::Pin;
+    /// use std::task::{Context, Poll};
+    ///
+    /// use tokio::net::TcpStream;
+    ///
+    /// /// Sends every request to a server on localhost, whatever its URL.
+    /// #[derive(Clone)]
+    /// struct Localhost;
+    ///
+    /// impl tower_service::Service<http::Uri> for Localhost {
+    ///     type Response = TcpStream;
+    ///     type Error = std::io::Error;
+    ///     type Future = Pin<Box<dyn Future<Output = std::io::Result<TcpStream>> + Send>>;
+    ///
+    ///     fn poll_ready(&mut self, _cx: &mut Context<'_>) -> Poll<std::io::Result<()>> {
+    ///         Poll::Ready(Ok(()))
+    ///     }
+    ///
+    ///     fn call(&mut self, _dst: http::Uri) -> Self::Future {
+    ///         Box::pin(TcpStream::connect("127.0.0.1:8080"))
+    ///     }
+    /// }
+    ///
+    /// let client = reqwest::Client::builder()
+    ///     .connector(Localhost)
+    ///     .build()?;
+    /// # Ok::<(), reqwest::Error>(())
+    /// ```
+    pub fn connector<S>(mut self, connector: S) -> ClientBuilder
+    where
+        S: tower_service::Service<Uri> + Clone + Send + Sync + 'static,
+        S::Response: CustomProxyStream,
+        S::Error: Into<BoxError>,
+        S::Future: Send + 'static,
+    {
+        self.config.transport = Some(Arc::new(move |dst| {
+            let mut connector = connector.clone();
+            Box::pin(async move {
+                futures_util::future::poll_fn(|cx| connector.poll_ready(cx))
+                    .await
+                    .map_err(Into::into)?;
+                let stream = connector.call(dst).await.map_err(Into::into)?;
+                Ok(Box::new(stream) as Box<dyn CustomProxyStream>)
+            })
+        }));
+        self
+    }
+
+    /// Wraps the connector of the client in a [`tower::Layer`].
+    ///
+    /// The layer sees every connection being established, with the URI of
+    /// its destination, which lets middleware limit, time, log or fail
+    /// connection attempts. It must hand the connection of the inner
+    /// connector back unchanged. Each call wraps the layers added before.
+    ///
+    /// Errors of the layer fail the request as connection errors.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # #[cfg(all(feature = "__rustls", not(feature = "__rustls-ring")))]
+    /// # let _ = rustls::crypto::ring::default_provider().install_default();
+    /// use tower::util::MapRequestLayer;
+    ///
+    /// let client = reqwest::Client::builder()
+    ///     .connector_layer(MapRequestLayer::new(|dst: http::Uri| {
+    ///         println!("connecting to {dst}");
+    ///         dst
+    ///     }))
+    ///     .build()?;
+    /// # Ok::<(), reqwest::Error>(())
+    /// ```
+    pub fn connector_layer<L>(mut self, layer: L) -> ClientBuilder
+    where
+        L: tower::Layer<BoxedConnectorService> + Send + Sync + 'static,
+        L::Service: Service<Uri, Response = Conn> + Clone + Send + Sync + 'static,
+        <L::Service as Service<Uri>>::Error: Into<BoxError>,
+        <L::Service as Service<Uri>>::Future: Send + 'static,
+    {
+        self.config.connector_layers.push(Arc::new(move |service| {
+            BoxCloneSyncService::new(layer.layer(service).map_err(Into::into))
+        }));
+        self
+    }
+
+    /// Connect to `host` through the Unix domain socket at `path`, instead
+    /// of over TCP.
+    ///
+    /// Requests to other hosts are not affected, and the port in the URL is
+    /// ignored. Proxies are not used for `host`. For `https` URLs, TLS is
+    /// negotiated over the socket as usual.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # #[cfg(all(feature = "__rustls", not(feature = "__rustls-ring")))]
+    /// # let _ = rustls::crypto::ring::default_provider().install_default();
+    /// let client = reqwest::Client::builder()
+    ///     .connect_host_via_unix_socket("docker", "/var/run/docker.sock")
+    ///     .build().unwrap();
+    /// ```
+    #[cfg(unix)]
+    pub fn connect_host_via_unix_socket<P: AsRef<std::path::Path>>(
+        mut self,
+        host: &str,
+        path: P,
+    ) -> ClientBuilder {
+        self.config
+            .unix_sockets
+            .insert(host.to_ascii_lowercase(), path.as_ref().to_path_buf());
+        self
+    }
+
     /// Override the DNS resolver implementation.
     ///
     /// Pass an `Arc` wrapping a trait object implementing `Resolve`.
@@ -1809,6 +3196,143 @@ impl ClientBuilder {
         self
     }
 
+    /// Resolve names with a DNS-over-TLS ([RFC 7858]) server instead of the
+    /// system or hickory-dns resolver.
+    ///
+    /// The server at `upstream`, usually on port 853, must present a
+    /// certificate valid for `server_name`. It is verified like the servers
+    /// the client connects to over rustls, with the same root certificates.
+    ///
+    /// A lookup, connecting to the server included, fails after the
+    /// `connect_timeout` of the client, or 5 seconds without one.
+    ///
+    /// A resolver passed to `dns_resolver` takes precedence over this one.
+    /// Overrides for specific names passed to `resolve` and `resolve_to_addrs`
+    /// still apply.
+    ///
+    /// # Errors
+    ///
+    /// `ClientBuilder::build` fails if `server_name` isn't a valid DNS name
+    /// or IP address.
+    ///
+    /// # Optional
+    ///
+    /// This requires the optional `dns-over-tls` feature to be enabled.
+    ///
+    /// [RFC 7858]: https://www.rfc-editor.org/rfc/rfc7858
+    #[cfg(feature = "dns-over-tls")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "dns-over-tls")))]
+    pub fn dns_over_tls(mut self, upstream: SocketAddr, server_name: &str) -> ClientBuilder {
+        self.config.dns_over_tls = Some((upstream, server_name.to_owned()));
+        self
+    }
+
+    /// Cache the answers of the DNS resolver in the client, shared with its
+    /// clones.
+    ///
+    /// Addresses are kept for the TTL of their records, within the bounds
+    /// set by `dns_cache_min_ttl` and `dns_cache_max_ttl`. The system
+    /// resolver and resolvers passed to `dns_resolver` don't report TTLs, so
+    /// their answers are kept for the maximum. Answers that a name doesn't
+    /// exist or has no addresses are kept for `dns_cache_negative_ttl`. Use
+    /// `Client::flush_dns_cache` to forget everything.
+    ///
+    /// Overrides passed to `resolve` and `resolve_to_addrs` are not cached.
+    ///
+    /// Default is `false`.
+    pub fn dns_cache(mut self, enable: bool) -> ClientBuilder {
+        self.config.dns_cache = enable;
+        self
+    }
+
+    /// Set the shortest time answers are kept in the DNS cache, even when
+    /// their records have a lower TTL.
+    ///
+    /// Default is zero: records with a zero TTL aren't cached.
+    pub fn dns_cache_min_ttl(mut self, ttl: Duration) -> ClientBuilder {
+        self.config.dns_cache_min_ttl = ttl;
+        self
+    }
+
+    /// Set the longest time answers are kept in the DNS cache, and how long
+    /// answers without a TTL are kept.
+    ///
+    /// A maximum below `dns_cache_min_ttl` is raised to it.
+    ///
+    /// Default is 60 seconds.
+    pub fn dns_cache_max_ttl(mut self, ttl: Duration) -> ClientBuilder {
+        self.config.dns_cache_max_ttl = ttl;
+        self
+    }
+
+    /// Set how long answers that a name doesn't exist or has no addresses
+    /// are kept in the DNS cache. Zero turns off caching of them.
+    ///
+    /// Other failures, such as timeouts, are never cached. Such answers are
+    /// told apart for DNS over TLS, hickory-dns, and resolvers passed to
+    /// `dns_resolver` that fail with an `std::io::Error` of kind `NotFound`.
+    /// The system resolver doesn't tell them apart, so its failures aren't
+    /// cached.
+    ///
+    /// Default is 5 seconds.
+    pub fn dns_cache_negative_ttl(mut self, ttl: Duration) -> ClientBuilder {
+        self.config.dns_cache_negative_ttl = ttl;
+        self
+    }
+
+    /// Call `callback` after each DNS lookup made by the client, with the
+    /// name, how long it took, the addresses found or the error, and whether
+    /// the answer came from the DNS cache.
+    ///
+    /// Names overridden with `resolve` and `resolve_to_addrs` aren't looked
+    /// up, so they aren't reported.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # fn run() -> Result<(), reqwest::Error> {
+    /// let client = reqwest::Client::builder()
+    ///     .on_dns_lookup(|lookup| {
+    ///         if lookup.duration().as_millis() > 100 {
+    ///             eprintln!("slow lookup of {}: {:?}", lookup.name(), lookup.duration());
+    ///         }
+    ///     })
+    ///     .build()?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn on_dns_lookup<F>(mut self, callback: F) -> ClientBuilder
+    where
+        F: Fn(&DnsLookup<'_>) + Send + Sync + 'static,
+    {
+        self.config.on_dns_lookup = Some(Arc::new(callback));
+        self
+    }
+
+    /// Log every DNS lookup made by the client.
+    ///
+    /// Each lookup, and its result, is logged at the `debug` level. Failed
+    /// lookups are logged at the `warn` level. This includes names
+    /// overridden with `resolve` and `resolve_to_addrs`.
+    ///
+    /// Default is `false`.
+    pub fn dns_resolver_debug_log(mut self, enable: bool) -> ClientBuilder {
+        self.config.dns_debug_log = enable;
+        self
+    }
+
+    /// Log a hash of hostnames instead of the names themselves, when DNS
+    /// lookups are logged with `dns_resolver_debug_log`.
+    ///
+    /// The same name always hashes to the same value, so lookups can still
+    /// be told apart.
+    ///
+    /// Default is `false`.
+    pub fn dns_log_hash_hostnames(mut self, enable: bool) -> ClientBuilder {
+        self.config.dns_log_hash_hostnames = enable;
+        self
+    }
+
     /// Whether to send data on the first flight ("early data") in TLS 1.3 handshakes
     /// for HTTP/3 connections.
     ///
@@ -1879,7 +3403,7 @@ impl ClientBuilder {
     }
 }
 
-type HyperClient = hyper_util::client::legacy::Client<Connector, super::Body>;
+type HyperClient = hyper_util::client::legacy::Client<ConnectorService, super::Body>;
 
 impl Default for Client {
     fn default() -> Self {
@@ -1887,6 +3411,394 @@ impl Default for Client {
     }
 }
 
+/// Reads an environment variable, treating an empty value as unset.
+/// A resolver, and the same resolver as a `ResolveWithTtl` if it reports TTLs.
+type BaseResolver = (Arc<dyn Resolve>, Option<Arc<dyn ResolveWithTtl>>);
+
+/// Picks the resolver for `config`, before any caching or overrides.
+fn base_resolver(config: &Config) -> crate::Result<BaseResolver> {
+    #[cfg(feature = "dns-over-tls")]
+    if let Some((upstream, ref server_name)) = config.dns_over_tls {
+        let tls = rustls_config(config, &config.root_certs, None)?;
+        let resolver = Arc::new(DotResolver::new(
+            upstream,
+            server_name,
+            tls,
+            config.connect_timeout,
+        )?);
+        return Ok((resolver.clone(), Some(resolver)));
+    }
+
+    match config.hickory_dns {
+        false => Ok((Arc::new(GaiResolver::new()), None)),
+        #[cfg(feature = "hickory-dns")]
+        true => {
+            let resolver = Arc::new(HickoryDnsResolver::default());
+            Ok((resolver.clone(), Some(resolver)))
+        }
+        #[cfg(not(feature = "hickory-dns"))]
+        true => unreachable!("hickory-dns shouldn't be enabled unless the feature is"),
+    }
+}
+
+fn read_env(var: &str) -> crate::Result<Option<String>> {
+    match std::env::var(var) {
+        Ok(value) if value.is_empty() => Ok(None),
+        Ok(value) => Ok(Some(value)),
+        Err(std::env::VarError::NotPresent) => Ok(None),
+        Err(err) => Err(crate::error::builder(format!("{var}: {err}"))),
+    }
+}
+
+#[cfg(any(
+    target_os = "android",
+    target_os = "fuchsia",
+    target_os = "ios",
+    target_os = "linux",
+    target_os = "macos",
+    target_os = "tvos",
+    target_os = "watchos",
+))]
+fn is_valid_interface_name(name: &str) -> bool {
+    // IFNAMSIZ, including the trailing NUL
+    name.len() < 16 && !name.contains(|c: char| c == '/' || c == ':' || c.is_whitespace())
+}
+
+/// Builds a native-tls connector from the TLS settings of `config`, with
+/// `root_certs` and `identity` in place of the client's, but no ALPN.
+#[cfg(feature = "default-tls")]
+fn native_tls_builder(
+    config: &Config,
+    root_certs: &[Certificate],
+    #[cfg(any(feature = "native-tls", feature = "__rustls"))] identity: Option<&Identity>,
+) -> crate::Result<native_tls_crate::TlsConnectorBuilder> {
+    #[cfg(feature = "__rustls")]
+    if config.tls_key_log.is_some() {
+        return Err(crate::error::builder(
+            "TLS key logging isn't supported by the native-tls backend",
+        ));
+    }
+    #[cfg(feature = "__rustls")]
+    if !config.tls_session_tickets || config.tls_session_cache.is_some() {
+        return Err(crate::error::builder(
+            "TLS session resumption settings aren't supported by the native-tls backend",
+        ));
+    }
+    #[cfg(feature = "__rustls")]
+    if !config.crls.is_empty() || config.tls_ocsp_stapling_required {
+        return Err(crate::error::builder(
+            "revocation checks aren't supported by the native-tls backend",
+        ));
+    }
+    #[cfg(feature = "__rustls")]
+    if !config.tls_ct_logs.is_empty() {
+        return Err(crate::error::builder(
+            "Certificate Transparency checks aren't supported by the native-tls backend",
+        ));
+    }
+
+    let mut tls = TlsConnector::builder();
+
+    tls.danger_accept_invalid_hostnames(!config.hostname_verification);
+
+    tls.danger_accept_invalid_certs(!config.certs_verification);
+
+    tls.use_sni(config.tls_sni);
+
+    tls.disable_built_in_roots(!config.tls_built_in_root_certs);
+
+    for cert in root_certs {
+        cert.clone().add_to_native_tls(&mut tls);
+    }
+
+    #[cfg(feature = "native-tls")]
+    {
+        if let Some(id) = identity {
+            id.clone().add_to_native_tls(&mut tls)?;
+        }
+    }
+    #[cfg(all(feature = "__rustls", not(feature = "native-tls")))]
+    {
+        // Default backend + rustls Identity doesn't work.
+        if identity.is_some() {
+            return Err(crate::error::builder("incompatible TLS identity type"));
+        }
+    }
+
+    if let Some(min_tls_version) = config.min_tls_version {
+        let protocol = min_tls_version.to_native_tls().ok_or_else(|| {
+            // TLS v1.3. This would be entirely reasonable,
+            // native-tls just doesn't support it.
+            // https://github.com/sfackler/rust-native-tls/issues/140
+            crate::error::builder("invalid minimum TLS version for backend")
+        })?;
+        tls.min_protocol_version(Some(protocol));
+    }
+
+    if let Some(max_tls_version) = config.max_tls_version {
+        let protocol = max_tls_version.to_native_tls().ok_or_else(|| {
+            // TLS v1.3.
+            // We could arguably do max_protocol_version(None), given
+            // that 1.4 does not exist yet, but that'd get messy in the
+            // future.
+            crate::error::builder("invalid maximum TLS version for backend")
+        })?;
+        tls.max_protocol_version(Some(protocol));
+    }
+
+    Ok(tls)
+}
+
+/// Builds a rustls configuration from the TLS settings of `config`, with
+/// `root_certs` and `identity` in place of the client's, but no ALPN.
+#[cfg(feature = "__rustls")]
+fn rustls_config(
+    config: &Config,
+    root_certs: &[Certificate],
+    identity: Option<&Identity>,
+) -> crate::Result<rustls::ClientConfig> {
+    use crate::tls::{IgnoreHostname, NoVerifier, RequireOcsp, RequireSct, VerifierKey};
+    use rustls::client::danger::ServerCertVerifier;
+
+    // Set root certificates.
+    let mut root_cert_store = rustls::RootCertStore::empty();
+    for cert in root_certs {
+        cert.clone().add_to_rustls(&mut root_cert_store)?;
+    }
+    let private_roots = root_cert_store.clone();
+
+    #[cfg(feature = "rustls-tls-webpki-roots")]
+    if config.tls_built_in_certs_webpki {
+        root_cert_store.extend(webpki_roots::TLS_SERVER_ROOTS.iter().cloned());
+    }
+
+    #[cfg(feature = "rustls-tls-native-roots")]
+    if config.tls_built_in_certs_native {
+        let mut valid_count = 0;
+        let mut invalid_count = 0;
+        for cert in rustls_native_certs::load_native_certs()
+            .map_err(crate::error::builder)?
+        {
+            // Continue on parsing errors, as native stores often include ancient or syntactically
+            // invalid certificates, like root certificates without any X509 extensions.
+            // Inspiration: https://github.com/rustls/rustls/blob/633bf4ba9d9521a95f68766d04c22e2b01e68318/rustls/src/anchors.rs#L105-L112
+            match root_cert_store.add(cert.into()) {
+                Ok(_) => valid_count += 1,
+                Err(err) => {
+                    invalid_count += 1;
+                    log::debug!("rustls failed to parse DER certificate: {err:?}");
+                }
+            }
+        }
+        if valid_count == 0 && invalid_count > 0 {
+            return Err(crate::error::builder(
+                "zero valid certificates found in native root store",
+            ));
+        }
+    }
+
+    // Set TLS versions.
+    let mut versions = rustls::ALL_VERSIONS.to_vec();
+
+    if let Some(min_tls_version) = config.min_tls_version {
+        versions.retain(|&supported_version| {
+            match tls::Version::from_rustls(supported_version.version) {
+                Some(version) => version >= min_tls_version,
+                // Assume it's so new we don't know about it, allow it
+                // (as of writing this is unreachable)
+                None => true,
+            }
+        });
+    }
+
+    if let Some(max_tls_version) = config.max_tls_version {
+        versions.retain(|&supported_version| {
+            match tls::Version::from_rustls(supported_version.version) {
+                Some(version) => version <= max_tls_version,
+                None => false,
+            }
+        });
+    }
+
+    if versions.is_empty() {
+        return Err(crate::error::builder("empty supported tls versions"));
+    }
+
+    // Allow user to have installed a runtime default.
+    // If not, we use ring.
+    let provider = rustls::crypto::CryptoProvider::get_default()
+        .cloned()
+        .unwrap_or_else(|| {
+            #[cfg(not(feature = "__rustls-ring"))]
+            panic!("No provider set");
+
+            #[cfg(feature = "__rustls-ring")]
+            Arc::new(rustls::crypto::ring::default_provider())
+        });
+
+    // Build TLS config
+    let signature_algorithms = provider.signature_verification_algorithms;
+    let root_cert_store = Arc::new(root_cert_store);
+    // rustls only resumes sessions with the very verifier and client
+    // certificate resolver they were established with, so clients sharing a
+    // session cache share those as well if they verify servers alike.
+    let shared_key = match (&config.tls_session_cache, identity) {
+        (Some(_), None) => Some(VerifierKey {
+            roots: root_cert_store.roots.clone(),
+            certs_verification: config.certs_verification,
+            hostname_verification: config.hostname_verification,
+            crls: config.crls.clone(),
+            ocsp_stapling_required: config.tls_ocsp_stapling_required,
+            ct_logs: config.tls_ct_logs.clone(),
+            ct_min_scts: config.tls_ct_min_scts,
+        }),
+        _ => None,
+    };
+
+    // A custom verifier replaces whatever would check revocation.
+    if config.custom_verifier.is_some()
+        && (!config.crls.is_empty() || config.tls_ocsp_stapling_required)
+    {
+        return Err(crate::error::builder(
+            "revocation checks can't be combined with a custom verifier",
+        ));
+    }
+    if config.custom_verifier.is_some() && !config.tls_ct_logs.is_empty() {
+        return Err(crate::error::builder(
+            "Certificate Transparency checks can't be combined with a custom verifier",
+        ));
+    }
+
+    // `None` leaves verification to rustls.
+    let verifier: Option<Arc<dyn ServerCertVerifier>> = if !config.certs_verification {
+        Some(Arc::new(NoVerifier))
+    } else if !config.hostname_verification {
+        if !config.crls.is_empty() {
+            return Err(crate::error::builder(
+                "certificate revocation lists require hostname verification",
+            ));
+        }
+        Some(Arc::new(IgnoreHostname::new(
+            root_cert_store.clone(),
+            signature_algorithms,
+        )))
+    } else if !config.crls.is_empty()
+        || config.tls_ocsp_stapling_required
+        || !config.tls_ct_logs.is_empty()
+        || (shared_key.is_some() && !root_cert_store.is_empty())
+    {
+        let verifier = rustls::client::WebPkiServerVerifier::builder_with_provider(
+            root_cert_store.clone(),
+            provider.clone(),
+        )
+        .with_crls(config.crls.iter().map(|crl| crl.as_rustls().clone()))
+        // Only reject what the lists revoke.
+        .allow_unknown_revocation_status()
+        .build()
+        .map_err(crate::error::builder)?;
+        Some(verifier)
+    } else {
+        None
+    };
+    let verifier = match verifier {
+        Some(verifier) if config.tls_ocsp_stapling_required => {
+            Some(Arc::new(RequireOcsp::new(verifier)) as Arc<dyn ServerCertVerifier>)
+        }
+        verifier => verifier,
+    };
+    let verifier = match verifier {
+        Some(verifier) if config.certs_verification && !config.tls_ct_logs.is_empty() => {
+            // Roots given to the client aren't held to public CA rules.
+            let private = if private_roots.is_empty() {
+                None
+            } else {
+                Some(Arc::new(IgnoreHostname::new(
+                    Arc::new(private_roots),
+                    signature_algorithms,
+                )) as Arc<dyn ServerCertVerifier>)
+            };
+            Some(Arc::new(RequireSct::new(
+                verifier,
+                private,
+                root_cert_store.clone(),
+                &config.tls_ct_logs,
+                config.tls_ct_min_scts,
+                &provider,
+            )?) as Arc<dyn ServerCertVerifier>)
+        }
+        verifier => verifier,
+    };
+
+    let config_builder = rustls::ClientConfig::builder_with_provider(provider)
+        .with_protocol_versions(&versions)
+        .map_err(|_| crate::error::builder("invalid TLS versions"))?;
+
+    let config_builder = match verifier {
+        Some(ref verifier) => config_builder
+            .dangerous()
+            .with_custom_certificate_verifier(verifier.clone()),
+        None => config_builder.with_root_certificates(root_cert_store),
+    };
+
+    // Finalize TLS config
+    let mut tls = if let Some(id) = identity {
+        id.clone().add_to_rustls(config_builder)?
+    } else {
+        config_builder.with_no_client_auth()
+    };
+
+    tls.enable_sni = config.tls_sni;
+
+    if let Some(ref key_log) = config.tls_key_log {
+        tls.key_log = key_log.to_rustls();
+    }
+
+    if let Some(ref cache) = config.tls_session_cache {
+        tls.resumption = cache.to_rustls();
+        if let (Some(key), Some(verifier)) = (shared_key, verifier) {
+            cache.share_verifier(key, verifier, &mut tls);
+        }
+    }
+    if !config.tls_session_tickets {
+        let resumption = std::mem::take(&mut tls.resumption);
+        tls.resumption = resumption.tls12_resumption(rustls::client::Tls12Resumption::SessionIdOnly);
+    }
+
+    Ok(tls)
+}
+
+/// Builds the TLS configuration of a proxy with its own root certificates
+/// or identity, from the client's TLS settings.
+#[cfg(feature = "__tls")]
+fn configure_proxy_tls(config: &Config, proxy: &mut Proxy) -> crate::Result<()> {
+    let mut root_certs = config.root_certs.clone();
+    root_certs.extend_from_slice(proxy.tls_root_certs());
+    #[cfg(any(feature = "native-tls", feature = "__rustls"))]
+    let identity = proxy.tls_identity().or(config.identity.as_ref());
+
+    match config.tls {
+        #[cfg(feature = "default-tls")]
+        TlsBackend::Default => {
+            let tls = native_tls_builder(
+                config,
+                &root_certs,
+                #[cfg(any(feature = "native-tls", feature = "__rustls"))]
+                identity,
+            )?;
+            proxy.set_native_tls(tls.build().map_err(crate::error::builder)?);
+        }
+        #[cfg(feature = "__rustls")]
+        TlsBackend::Rustls => proxy.set_tls(rustls_config(config, &root_certs, identity)?),
+        #[allow(unreachable_patterns)]
+        _ => {
+            return Err(crate::error::builder(
+                "proxy TLS options can't be used with a preconfigured TLS backend",
+            ))
+        }
+    }
+    Ok(())
+}
+
 impl Client {
     /// Constructs a new `Client`.
     ///
@@ -1994,9 +3906,90 @@ impl Client {
         self.execute_request(request)
     }
 
-    pub(super) fn execute_request(&self, req: Request) -> Pending {
-        let (method, url, mut headers, body, timeout, version) = req.pieces();
-        if url.scheme() != "http" && url.scheme() != "https" {
+    /// Returns the proxies this `Client` currently uses, in the order they
+    /// are tried.
+    ///
+    /// When no proxy was added with `ClientBuilder::proxy`, this includes the
+    /// proxy read from the environment, if automatic use of the system proxy
+    /// wasn't disabled.
+    pub fn proxies(&self) -> Vec<Proxy> {
+        self.inner.proxies.load().to_vec()
+    }
+
+    /// Returns true if this `Client` has any proxy configured.
+    pub fn has_proxy(&self) -> bool {
+        !self.inner.proxies.load().is_empty()
+    }
+
+    /// Replaces the proxies of this `Client`, and of every clone of it.
+    ///
+    /// This lets a long-lived `Client` follow proxy changes, such as a VPN
+    /// going up or down, without being rebuilt. Requests use the new
+    /// proxies, and only reuse pooled connections made through the same
+    /// proxy.
+    ///
+    /// The list is used as given: the system proxy is not added back, but
+    /// `ClientBuilder::no_proxy_private_networks` still applies.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # fn run() -> Result<(), reqwest::Error> {
+    /// let client = reqwest::Client::builder().no_proxy().build()?;
+    ///
+    /// // later, once the VPN is up
+    /// client.set_proxies(vec![reqwest::Proxy::all("http://vpn.prox:3128")?]);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn set_proxies(&self, mut proxies: Vec<Proxy>) {
+        if self.inner.no_proxy_private_networks {
+            for proxy in &mut proxies {
+                proxy.add_private_networks_to_no_proxy();
+            }
+        }
+        self.inner.proxies.store(proxies);
+    }
+
+    /// Returns statistics about the connections of this `Client`, shared
+    /// with its clones: how many are idle or active per host, and how many
+    /// were created, reused and closed so far.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # async fn run() -> Result<(), reqwest::Error> {
+    /// let client = reqwest::Client::new();
+    /// client.get("https://hyper.rs").send().await?.text().await?;
+    ///
+    /// let stats = client.pool_stats();
+    /// for host in stats.hosts() {
+    ///     println!("{}: {} idle, {} active", host.host(), host.idle(), host.active());
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn pool_stats(&self) -> PoolStats {
+        self.inner.pool_tracker.stats()
+    }
+
+    /// Forgets every DNS answer cached by this `Client` and its clones, so
+    /// the next request to each host looks it up again.
+    ///
+    /// Does nothing unless the cache was enabled with
+    /// `ClientBuilder::dns_cache`.
+    pub fn flush_dns_cache(&self) {
+        if let Some(ref cache) = self.inner.dns_cache {
+            cache.flush();
+        }
+    }
+
+    pub(super) fn execute_request(&self, mut req: Request) -> Pending {
+        let redirect_policy = req.take_redirect_policy();
+        let unix_socket = req.take_unix_socket();
+        let binding = req.take_binding();
+        let (method, url, mut headers, body, timeout, connect_timeout, version) = req.pieces();
+        if !is_supported_scheme(&url) || (unix_socket.is_some() && url.scheme() != "http") {
             return Pending::new_err(error::url_bad_scheme(url));
         }
 
@@ -2013,6 +4006,14 @@ impl Client {
             }
         }
 
+        if let Some(ref netrc) = self.inner.netrc {
+            if !headers.contains_key(AUTHORIZATION) {
+                if let Some(auth) = url.host_str().and_then(|host| netrc.basic_auth(host)) {
+                    headers.insert(AUTHORIZATION, auth);
+                }
+            }
+        }
+
         // Add cookies from the cookie store.
         #[cfg(feature = "cookies")]
         {
@@ -2023,7 +4024,11 @@ impl Client {
             }
         }
 
-        let accept_encoding = self.inner.accepts.as_str();
+        let accept_encoding = self
+            .inner
+            .accepts
+            .as_str()
+            .filter(|_| self.inner.auto_accept_encoding);
 
         if let Some(accept_encoding) = accept_encoding {
             if !headers.contains_key(ACCEPT_ENCODING) && !headers.contains_key(RANGE) {
@@ -2031,10 +4036,13 @@ impl Client {
             }
         }
 
-        let uri = match try_uri(&url) {
-            Ok(uri) => uri,
-            _ => return Pending::new_err(error::url_invalid_uri(url)),
+        let uri = match request_uri(&url, unix_socket.as_deref()) {
+            Some(uri) => uri,
+            None => return Pending::new_err(error::url_invalid_uri(url)),
         };
+        if (unix_socket.is_some() || url.scheme() == UNIX_SCHEME) && !headers.contains_key(HOST) {
+            set_unix_socket_host(&mut headers, &url);
+        }
 
         let (reusable, body) = match body {
             Some(body) => {
@@ -2043,25 +4051,38 @@ impl Client {
             }
             None => (None, Body::empty()),
         };
+        let (body, written) = self.inner.wrap_request_body(body);
 
-        self.proxy_auth(&uri, &mut headers);
+        let proxy_request = self.inner.proxy_request(&method, &url, &headers, &body);
+        self.proxy_auth(&uri, proxy_request.as_ref(), &mut headers);
+        let request_proxy = self.inner.request_proxy(&uri, proxy_request.as_ref());
 
         let builder = hyper::Request::builder()
             .method(method.clone())
             .uri(uri)
             .version(version);
 
+        let connect_deadline;
         let in_flight = match version {
             #[cfg(feature = "http3")]
             http::Version::HTTP_3 if self.inner.h3_client.is_some() => {
                 let mut req = builder.body(body).expect("valid request parts");
                 *req.headers_mut() = headers.clone();
+                connect_deadline = None;
                 ResponseFuture::H3(self.inner.h3_client.as_ref().unwrap().request(req))
             }
             _ => {
                 let mut req = builder.body(body).expect("valid request parts");
                 *req.headers_mut() = headers.clone();
-                ResponseFuture::Default(self.inner.hyper.request(req))
+                let (in_flight, deadline) = self.inner.send_or_select(
+                    req,
+                    request_proxy.as_ref(),
+                    proxy_request,
+                    binding.as_ref(),
+                    connect_timeout,
+                );
+                connect_deadline = deadline;
+                in_flight
             }
         };
 
@@ -2088,35 +4109,62 @@ impl Client {
                 retry_count: 0,
 
                 client: self.inner.clone(),
+                redirect_policy,
 
                 in_flight,
                 total_timeout,
                 read_timeout_fut,
                 read_timeout: self.inner.read_timeout,
+                response_read_timeout_fut: None,
+                retry_delay: None,
+                written,
+                connect_timeout,
+                connect_deadline,
+                request_proxy,
+                unix_socket,
+                binding,
             }),
         }
     }
 
-    fn proxy_auth(&self, dst: &Uri, headers: &mut HeaderMap) {
-        if !self.inner.proxies_maybe_http_auth {
+    fn proxy_auth(&self, dst: &Uri, req: Option<&ProxyRequest>, headers: &mut HeaderMap) {
+        let proxies = self.inner.proxies.load();
+        if self.inner.netrc.is_none()
+            && !proxies
+                .iter()
+                .any(|p| p.maybe_has_http_auth() || p.maybe_has_http_headers())
+        {
             return;
         }
 
-        // Only set the header here if the destination scheme is 'http',
-        // since otherwise, the header will be included in the CONNECT tunnel
+        // Only set the headers here if the destination scheme is 'http',
+        // since otherwise, they will be included in the CONNECT tunnel
         // request instead.
         if dst.scheme() != Some(&Scheme::HTTP) {
             return;
         }
 
-        if headers.contains_key(PROXY_AUTHORIZATION) {
-            return;
-        }
-
-        for proxy in self.inner.proxies.iter() {
+        let dst = &RequestDst::new(dst, req);
+        for proxy in proxies.iter() {
             if proxy.is_match(dst) {
-                if let Some(header) = proxy.http_basic_auth(dst) {
-                    headers.insert(PROXY_AUTHORIZATION, header);
+                if !headers.contains_key(PROXY_AUTHORIZATION) {
+                    let header = proxy.http_basic_auth(dst).or_else(|| {
+                        let scheme = self.inner.proxies.fill_credentials(proxy.intercept(dst)?);
+                        scheme.maybe_http_auth().cloned()
+                    });
+                    if let Some(header) = header {
+                        headers.insert(PROXY_AUTHORIZATION, header);
+                    }
+                }
+
+                if let Some(proxy_headers) = proxy.http_headers(dst) {
+                    for name in proxy_headers.keys() {
+                        if !headers.contains_key(name) {
+                            for value in proxy_headers.get_all(name) {
+                                headers.append(name, value.clone());
+                            }
+                        }
+                    }
                 }
 
                 break;
@@ -2183,10 +4231,43 @@ impl Config {
 
         f.field("accepts", &self.accepts);
 
+        if !self.auto_accept_encoding {
+            f.field("auto_accept_encoding", &false);
+        }
+
         if !self.proxies.is_empty() {
             f.field("proxies", &self.proxies);
         }
 
+        #[cfg(feature = "socks")]
+        if !self.proxy_resolve_via_system_dns {
+            f.field("proxy_resolve_via_system_dns", &false);
+        }
+
+        if self.no_proxy_private_networks {
+            f.field("no_proxy_private_networks", &true);
+        }
+
+        if let Some(ref interval) = self.system_proxy_refresh {
+            f.field("system_proxy_refresh", interval);
+        }
+
+        if self.on_proxy_selected.is_some() {
+            f.field("on_proxy_selected", &true);
+        }
+
+        if self.wire_log.is_some() {
+            f.field("wire_log", &true);
+        }
+
+        if let Some(ref path) = self.netrc {
+            f.field("netrc", path);
+        }
+
+        if self.transport.is_some() {
+            f.field("connector", &true);
+        }
+
         if !self.redirect_policy.is_default() {
             f.field("redirect_policy", &self.redirect_policy);
         }
@@ -2226,6 +4307,36 @@ impl Config {
             f.field("connect_timeout", d);
         }
 
+        if !self.connect_timeouts_for.is_empty() {
+            f.field("connect_timeouts_for", &self.connect_timeouts_for);
+        }
+
+        if let Some(ref d) = self.connect_timeout_per_address {
+            f.field("connect_timeout_per_address", d);
+        }
+
+        #[cfg(any(feature = "native-tls", feature = "__rustls"))]
+        if !self.identities_for.is_empty() {
+            let patterns = self.identities_for.iter().map(|(pattern, _)| pattern);
+            f.field("identities_for", &patterns.collect::<Vec<_>>());
+        }
+
+        if let Some(ref lifetime) = self.pool_max_connection_lifetime {
+            f.field("pool_max_connection_lifetime", lifetime);
+        }
+
+        if let Some(ref max) = self.max_connections_per_host {
+            f.field("max_connections_per_host", max);
+        }
+
+        if let Some(ref max) = self.max_connections {
+            f.field("max_connections", max);
+        }
+
+        if let Some(ref r) = self.connect_retry {
+            f.field("connect_retry", r);
+        }
+
         if let Some(ref d) = self.timeout {
             f.field("timeout", d);
         }
@@ -2234,15 +4345,67 @@ impl Config {
             f.field("local_address", v);
         }
 
-        #[cfg(any(target_os = "android", target_os = "fuchsia", target_os = "linux"))]
+        if let Some(ref v) = self.local_address_env {
+            f.field("local_address_from_env", v);
+        }
+
+        #[cfg(any(
+            target_os = "android",
+            target_os = "fuchsia",
+            target_os = "ios",
+            target_os = "linux",
+            target_os = "macos",
+            target_os = "tvos",
+            target_os = "watchos",
+        ))]
         if let Some(ref v) = self.interface {
             f.field("interface", v);
         }
 
+        #[cfg(any(
+            target_os = "android",
+            target_os = "fuchsia",
+            target_os = "ios",
+            target_os = "linux",
+            target_os = "macos",
+            target_os = "tvos",
+            target_os = "watchos",
+        ))]
+        if let Some(ref v) = self.interface_env {
+            f.field("local_interface_from_env", v);
+        }
+
+        #[cfg(any(target_os = "android", target_os = "linux"))]
+        if let Some(ref v) = self.interface_refresh {
+            f.field("interface_auto_detect", v);
+        }
+
+        #[cfg(any(target_os = "android", target_os = "linux"))]
+        if let Some(ref v) = self.interface_pattern {
+            f.field("preferred_interface_pattern", v);
+        }
+
+        #[cfg(any(target_os = "android", target_os = "fuchsia", target_os = "linux"))]
+        if let Some(ref v) = self.socket_mark {
+            f.field("socket_mark", v);
+        }
+
         if self.nodelay {
             f.field("tcp_nodelay", &true);
         }
 
+        if let Some(ref v) = self.tcp_send_buffer_size {
+            f.field("tcp_send_buffer_size", v);
+        }
+
+        if let Some(ref v) = self.tcp_recv_buffer_size {
+            f.field("tcp_recv_buffer_size", v);
+        }
+
+        if self.ipv4_fallback {
+            f.field("fallback_to_ipv4_on_ipv6_failure", &true);
+        }
+
         #[cfg(feature = "__tls")]
         {
             if !self.hostname_verification {
@@ -2256,6 +4419,41 @@ impl Config {
                 f.field("danger_accept_invalid_certs", &true);
             }
 
+            if self.custom_verifier.is_some() {
+                f.field("dangerous_custom_verifier", &true);
+            }
+
+            #[cfg(feature = "__rustls")]
+            if let Some(ref key_log) = self.tls_key_log {
+                f.field("tls_key_log", key_log);
+            }
+
+            #[cfg(feature = "__rustls")]
+            if !self.tls_session_tickets {
+                f.field("tls_session_tickets", &false);
+            }
+
+            #[cfg(feature = "__rustls")]
+            if let Some(ref cache) = self.tls_session_cache {
+                f.field("tls_session_cache", cache);
+            }
+
+            #[cfg(feature = "__rustls")]
+            if !self.crls.is_empty() {
+                f.field("crls", &self.crls.len());
+            }
+
+            #[cfg(feature = "__rustls")]
+            if self.tls_ocsp_stapling_required {
+                f.field("tls_ocsp_stapling_required", &true);
+            }
+
+            #[cfg(feature = "__rustls")]
+            if !self.tls_ct_logs.is_empty() {
+                f.field("tls_ct_logs", &self.tls_ct_logs.len());
+                f.field("tls_ct_min_scts", &self.tls_ct_min_scts);
+            }
+
             if let Some(ref min_tls_version) = self.min_tls_version {
                 f.field("min_tls_version", min_tls_version);
             }
@@ -2267,6 +4465,11 @@ impl Config {
             f.field("tls_sni", &self.tls_sni);
 
             f.field("tls_info", &self.tls_info);
+
+            #[cfg(feature = "cert-expiry-check")]
+            if self.cert_expiry.is_enabled() {
+                f.field("cert_expiry", &self.cert_expiry);
+            }
         }
 
         #[cfg(all(feature = "default-tls", feature = "__rustls"))]
@@ -2278,6 +4481,34 @@ impl Config {
             f.field("dns_overrides", &self.dns_overrides);
         }
 
+        if let Some(ref path) = self.hosts_file {
+            f.field("hosts_file", path);
+        }
+
+        #[cfg(unix)]
+        if !self.unix_sockets.is_empty() {
+            f.field("unix_sockets", &self.unix_sockets);
+        }
+
+        #[cfg(feature = "dns-over-tls")]
+        if let Some(ref dns_over_tls) = self.dns_over_tls {
+            f.field("dns_over_tls", dns_over_tls);
+        }
+
+        if self.dns_cache {
+            f.field("dns_cache_min_ttl", &self.dns_cache_min_ttl);
+            f.field("dns_cache_max_ttl", &self.dns_cache_max_ttl);
+            f.field("dns_cache_negative_ttl", &self.dns_cache_negative_ttl);
+        }
+
+        if self.on_dns_lookup.is_some() {
+            f.field("on_dns_lookup", &true);
+        }
+
+        if self.dns_debug_log {
+            f.field("dns_resolver_debug_log", &true);
+        }
+
         #[cfg(feature = "http3")]
         {
             if self.tls_enable_early_data {
@@ -2289,6 +4520,7 @@ impl Config {
 
 struct ClientRef {
     accepts: Accepts,
+    auto_accept_encoding: bool,
     #[cfg(feature = "cookies")]
     cookie_store: Option<Arc<dyn cookie::CookieStore>>,
     headers: HeaderMap,
@@ -2299,12 +4531,157 @@ struct ClientRef {
     referer: bool,
     request_timeout: Option<Duration>,
     read_timeout: Option<Duration>,
-    proxies: Arc<Vec<Proxy>>,
-    proxies_maybe_http_auth: bool,
+    response_read_timeout: Option<Duration>,
+    request_write_timeout: Option<Duration>,
+    #[cfg(feature = "http2")]
+    http2_rst_error_delay: Option<(Duration, f64)>,
+    proxies: SharedProxies,
+    no_proxy_private_networks: bool,
     https_only: bool,
+    netrc: Option<Arc<Netrc>>,
+    pool_tracker: Arc<PoolTracker>,
+    dns_cache: Option<DnsCache>,
+    request_pools: RequestPools,
+}
+
+/// A connection pool for each proxy, per-request binding and connect
+/// timeout, so that connections through a proxy, such as CONNECT tunnels,
+/// or bound to a local address or interface, are only reused by requests
+/// sent the same way.
+///
+/// The connector of each pool makes its connections that way, see
+/// `Connector::for_request`.
+struct RequestPools {
+    builder: hyper_util::client::legacy::Builder,
+    connector: Connector,
+    layers: Vec<Arc<ConnectorLayerFn>>,
+    clients: Mutex<PoolClients>,
+}
+
+/// The clients of `RequestPools`, with when each was last used.
+#[derive(Default)]
+struct PoolClients {
+    clients: HashMap<PoolKey, (HyperClient, u64)>,
+    uses: u64,
+}
+
+/// The most pools kept apart from the one of the client.
+const MAX_REQUEST_POOLS: usize = 256;
+
+impl RequestPools {
+    fn client(&self, key: PoolKey, request: RequestConnect) -> HyperClient {
+        let mut pools = self.clients.lock().unwrap();
+        pools.uses += 1;
+        let uses = pools.uses;
+        // Don't grow without bounds with the settings of requests, and give
+        // up the pool used the longest ago.
+        if pools.clients.len() >= MAX_REQUEST_POOLS && !pools.clients.contains_key(&key) {
+            let oldest = pools
+                .clients
+                .iter()
+                .min_by_key(|(_, (_, used))| *used)
+                .map(|(key, _)| key.clone());
+            if let Some(oldest) = oldest {
+                pools.clients.remove(&oldest);
+            }
+        }
+        let (client, used) = pools.clients.entry(key).or_insert_with(|| {
+            let connector = self.connector.for_request(request);
+            let client = self
+                .builder
+                .build(ConnectorService::new(connector, &self.layers));
+            (client, 0)
+        });
+        *used = uses;
+        client.clone()
+    }
 }
 
 impl ClientRef {
+    /// Chooses the proxy of `req`, a request to `uri`.
+    fn request_proxy(&self, uri: &Uri, req: Option<&ProxyRequest>) -> Option<RequestProxy> {
+        RequestProxy::select(&self.proxies, uri, req)
+    }
+
+    /// Sends `req` over the pool of the requests going the way of `proxy`
+    /// and bound by `binding`, along with the deadline of the connect
+    /// timeout of the request, if it has one.
+    fn send_hyper(
+        &self,
+        mut req: hyper::Request<Body>,
+        proxy: Option<&RequestProxy>,
+        binding: Option<&RequestBinding>,
+        connect_timeout: Option<Duration>,
+    ) -> (HyperResponseFuture, Option<ConnectDeadline>) {
+        // Connectors bound their attempts by the connect timeout of the
+        // client, so a request allowing longer takes a pool whose connector
+        // applies none.
+        let no_connect_timeout = match (
+            connect_timeout,
+            self.request_pools.connector.timeout_for(req.uri()),
+        ) {
+            (Some(timeout), Some(client)) => timeout > client,
+            _ => false,
+        };
+        let request = RequestConnect {
+            proxy: proxy.cloned(),
+            binding: binding.cloned(),
+            no_connect_timeout,
+        };
+        let hyper = match request.pool_key() {
+            None => self.hyper.clone(),
+            Some(key) => self.request_pools.client(key, request),
+        };
+        let deadline = connect_timeout.map(|timeout| ConnectDeadline {
+            sleep: Box::pin(tokio::time::sleep(timeout)),
+            connection: capture_connection(&mut req),
+        });
+        (hyper.request(req), deadline)
+    }
+
+    /// Sends `req` like `send_hyper`, or, if the way of the request is
+    /// chosen by an async custom proxy, waits for that choice before taking
+    /// the pool of connections going that way.
+    fn send_or_select(
+        &self,
+        req: hyper::Request<Body>,
+        proxy: Option<&RequestProxy>,
+        proxy_request: Option<ProxyRequest>,
+        binding: Option<&RequestBinding>,
+        connect_timeout: Option<Duration>,
+    ) -> (ResponseFuture, Option<ConnectDeadline>) {
+        if proxy.is_some() {
+            let (in_flight, deadline) = self.send_hyper(req, proxy, binding, connect_timeout);
+            return (ResponseFuture::Default(in_flight), deadline);
+        }
+        let selecting = RequestProxy::select_async(&self.proxies, req.uri().clone(), proxy_request);
+        (
+            ResponseFuture::Selecting(selecting, Some(Box::new(req))),
+            None,
+        )
+    }
+
+    /// Describes a request for the custom proxies that may choose a proxy
+    /// by it, if there are any.
+    fn proxy_request(
+        &self,
+        method: &Method,
+        url: &Url,
+        headers: &HeaderMap,
+        body: &Body,
+    ) -> Option<ProxyRequest> {
+        if self.proxies.load().iter().any(Proxy::is_custom) {
+            Some(ProxyRequest::new(
+                method.clone(),
+                url.clone(),
+                headers.clone(),
+                http_body::Body::size_hint(body).exact(),
+            ))
+        } else {
+            None
+        }
+    }
+
     fn fmt_fields(&self, f: &mut fmt::DebugStruct<'_, '_>) {
         // Instead of deriving Debug, only print fields when their output
         // would provide relevant or interesting data.
@@ -2318,8 +4695,13 @@ impl ClientRef {
 
         f.field("accepts", &self.accepts);
 
-        if !self.proxies.is_empty() {
-            f.field("proxies", &self.proxies);
+        if !self.auto_accept_encoding {
+            f.field("auto_accept_encoding", &false);
+        }
+
+        let proxies = self.proxies.load();
+        if !proxies.is_empty() {
+            f.field("proxies", &proxies);
         }
 
         if !self.redirect_policy.is_default() {
@@ -2339,6 +4721,42 @@ impl ClientRef {
         if let Some(ref d) = self.read_timeout {
             f.field("read_timeout", d);
         }
+
+        if let Some(ref d) = self.response_read_timeout {
+            f.field("response_read_timeout", d);
+        }
+
+        if let Some(ref d) = self.request_write_timeout {
+            f.field("request_write_timeout", d);
+        }
+
+        #[cfg(feature = "http2")]
+        if let Some((ref d, _)) = self.http2_rst_error_delay {
+            f.field("http2_rst_error_delay", d);
+        }
+    }
+
+    #[cfg(feature = "http2")]
+    fn http2_rst_retry_delay(&self) -> Option<Duration> {
+        let (delay, jitter_fraction) = self.http2_rst_error_delay?;
+        let random = crate::util::fast_random() as f64 / u64::MAX as f64;
+        Some(jittered_delay(delay, jitter_fraction, random))
+    }
+
+    /// Wraps a request body with the configured write timeout, and tracks
+    /// when it has been written if a read timeout needs to start then.
+    fn wrap_request_body(&self, body: Body) -> (Body, Option<Arc<body::Written>>) {
+        let body = match self.request_write_timeout {
+            Some(timeout) => Body::streaming(body::with_write_timeout(body, timeout)),
+            None => body,
+        };
+        match self.response_read_timeout {
+            Some(_) => {
+                let (body, written) = body::written(body);
+                (Body::streaming(body), Some(written))
+            }
+            None => (body, None),
+        }
     }
 }
 
@@ -2366,6 +4784,7 @@ pin_project! {
         retry_count: usize,
 
         client: Arc<ClientRef>,
+        redirect_policy: Option<Arc<redirect::Policy>>,
 
         #[pin]
         in_flight: ResponseFuture,
@@ -2374,11 +4793,36 @@ pin_project! {
         #[pin]
         read_timeout_fut: Option<Pin<Box<Sleep>>>,
         read_timeout: Option<Duration>,
+        #[pin]
+        response_read_timeout_fut: Option<Pin<Box<Sleep>>>,
+        retry_delay: Option<Pin<Box<Sleep>>>,
+        written: Option<Arc<body::Written>>,
+        connect_timeout: Option<Duration>,
+        connect_deadline: Option<ConnectDeadline>,
+        request_proxy: Option<RequestProxy>,
+        unix_socket: Option<PathBuf>,
+        binding: Option<RequestBinding>,
     }
 }
 
+/// The connect timeout of a request, running until the request gets a
+/// connection.
+///
+/// It is applied around the connect rather than by the connector, so that
+/// requests with different connect timeouts share their pools.
+struct ConnectDeadline {
+    sleep: Pin<Box<Sleep>>,
+    connection: CaptureConnection,
+}
+
 enum ResponseFuture {
     Default(HyperResponseFuture),
+    /// Waiting for async custom proxies to choose the way of the request,
+    /// which is then sent over the pool of that way.
+    Selecting(
+        BoxFuture<'static, RequestProxy>,
+        Option<Box<hyper::Request<Body>>>,
+    ),
     #[cfg(feature = "http3")]
     H3(H3ResponseFuture),
 }
@@ -2396,6 +4840,53 @@ impl PendingRequest {
         self.project().read_timeout_fut
     }
 
+    fn response_read_timeout(self: Pin<&mut Self>) -> Pin<&mut Option<Pin<Box<Sleep>>>> {
+        self.project().response_read_timeout_fut
+    }
+
+    /// Prepares a body for a retried or redirected request, restarting the
+    /// response read timeout once it has been written.
+    fn wrap_request_body(mut self: Pin<&mut Self>, body: Body) -> Body {
+        let (body, written) = self.client.wrap_request_body(body);
+        *self.as_mut().project().written = written;
+        self.as_mut().response_read_timeout().set(None);
+        body
+    }
+
+    /// Starts the response read timeout once the request body has been
+    /// written.
+    fn start_response_read_timeout(mut self: Pin<&mut Self>, cx: &mut Context<'_>) {
+        let written = match self.written {
+            Some(ref written) => written.poll_written(cx),
+            None => return,
+        };
+        if written {
+            *self.as_mut().project().written = None;
+            if let Some(timeout) = self.client.response_read_timeout {
+                self.as_mut()
+                    .response_read_timeout()
+                    .set(Some(Box::pin(tokio::time::sleep(timeout))));
+            }
+        }
+    }
+
+    /// Whether the connect timeout of the request elapsed before it got a
+    /// connection.
+    fn connect_timed_out(self: Pin<&mut Self>, cx: &mut Context<'_>) -> bool {
+        let slot = self.project().connect_deadline;
+        let connected = match slot {
+            Some(deadline) => deadline.connection.connection_metadata().is_some(),
+            None => return false,
+        };
+        if connected {
+            *slot = None;
+            return false;
+        }
+        slot.as_mut().map_or(false, |deadline| {
+            deadline.sleep.as_mut().poll(cx).is_ready()
+        })
+    }
+
     fn urls(self: Pin<&mut Self>) -> &mut Vec<Url> {
         self.project().urls
     }
@@ -2428,9 +4919,18 @@ impl PendingRequest {
             return false;
         }
         self.retry_count += 1;
+        #[cfg(feature = "http2")]
+        if is_refused_stream(err) {
+            *self.as_mut().project().retry_delay = self
+                .client
+                .http2_rst_retry_delay()
+                .map(|delay| Box::pin(tokio::time::sleep(delay)));
+        }
+        let body = self.as_mut().wrap_request_body(body);
 
         // If it parsed once, it should parse again
-        let uri = try_uri(&self.url).expect("URL was already validated as URI");
+        let uri = request_uri(&self.url, self.unix_socket.as_deref())
+            .expect("URL was already validated as URI");
 
         *self.as_mut().in_flight().get_mut() = match *self.as_mut().in_flight().as_ref() {
             #[cfg(feature = "http3")]
@@ -2456,7 +4956,14 @@ impl PendingRequest {
                     .body(body)
                     .expect("valid request parts");
                 *req.headers_mut() = self.headers.clone();
-                ResponseFuture::Default(self.client.hyper.request(req))
+                let (in_flight, deadline) = self.client.send_hyper(
+                    req,
+                    self.request_proxy.as_ref(),
+                    self.binding.as_ref(),
+                    self.connect_timeout,
+                );
+                *self.as_mut().project().connect_deadline = deadline;
+                ResponseFuture::Default(in_flight)
             }
         };
 
@@ -2492,8 +4999,7 @@ fn is_retryable_error(err: &(dyn std::error::Error + 'static)) -> bool {
 
             // REFUSED_STREAM was sent from the server, which is safe to retry.
             // https://www.rfc-editor.org/rfc/rfc9113.html#section-8.7-3.2
-            if err.is_reset() && err.is_remote() && err.reason() == Some(h2::Reason::REFUSED_STREAM)
-            {
+            if is_refused_stream_reset(err) {
                 return true;
             }
         }
@@ -2501,6 +5007,19 @@ fn is_retryable_error(err: &(dyn std::error::Error + 'static)) -> bool {
     false
 }
 
+#[cfg(feature = "http2")]
+fn is_refused_stream_reset(err: &h2::Error) -> bool {
+    err.is_reset() && err.is_remote() && err.reason() == Some(h2::Reason::REFUSED_STREAM)
+}
+
+#[cfg(feature = "http2")]
+fn is_refused_stream(err: &(dyn std::error::Error + 'static)) -> bool {
+    err.source()
+        .and_then(|err| err.source())
+        .and_then(|cause| cause.downcast_ref::<h2::Error>())
+        .map_or(false, is_refused_stream_reset)
+}
+
 impl Pending {
     pub(super) fn new_err(err: crate::Error) -> Pending {
         Pending {
@@ -2547,21 +5066,83 @@ impl Future for PendingRequest {
             }
         }
 
+        self.as_mut().start_response_read_timeout(cx);
+        if let Some(delay) = self.as_mut().response_read_timeout().as_mut().as_pin_mut() {
+            if let Poll::Ready(()) = delay.poll(cx) {
+                return Poll::Ready(Err(
+                    crate::error::request(crate::error::TimedOut).with_url(self.url.clone())
+                ));
+            }
+        }
+
         loop {
+            if let Some(delay) = self.retry_delay.as_mut() {
+                if delay.as_mut().poll(cx).is_pending() {
+                    return Poll::Pending;
+                }
+                self.retry_delay = None;
+            }
+
             let res = match self.as_mut().in_flight().get_mut() {
-                ResponseFuture::Default(r) => match Pin::new(r).poll(cx) {
-                    Poll::Ready(Err(e)) => {
-                        #[cfg(feature = "http2")]
-                        if self.as_mut().retry_error(&e) {
-                            continue;
+                ResponseFuture::Default(r) => {
+                    match Pin::new(r).poll(cx) {
+                        Poll::Ready(Err(e)) => {
+                            #[cfg(feature = "http2")]
+                            if self.as_mut().retry_error(&e) {
+                                continue;
+                            }
+                            return Poll::Ready(Err(
+                                crate::error::request(e).with_url(self.url.clone())
+                            ));
+                        }
+                        Poll::Ready(Ok(res)) => {
+                            let mut res = res.map(super::body::boxed);
+                            match res.extensions().get::<ConnHandle>().cloned() {
+                                Some(conn) => {
+                                    ConnectionInfo::complete(
+                                        res.extensions_mut(),
+                                        conn.is_reused(),
+                                    );
+                                    let upgraded = res.status() == StatusCode::SWITCHING_PROTOCOLS
+                                        || (self.method == Method::CONNECT
+                                            && res.status().is_success());
+                                    res.map(|body| {
+                                        PoolTracker::track_response(body, &conn, upgraded)
+                                    })
+                                }
+                                None => res,
+                            }
+                        }
+                        Poll::Pending => {
+                            // The connect timeout of the request, if it is
+                            // still connecting.
+                            if self.as_mut().connect_timed_out(cx) {
+                                return Poll::Ready(Err(crate::error::connect(
+                                    crate::error::TimedOut,
+                                )
+                                .with_url(self.url.clone())));
+                            }
+                            return Poll::Pending;
                         }
-                        return Poll::Ready(Err(
-                            crate::error::request(e).with_url(self.url.clone())
-                        ));
                     }
-                    Poll::Ready(Ok(res)) => res.map(super::body::boxed),
-                    Poll::Pending => return Poll::Pending,
-                },
+                }
+                ResponseFuture::Selecting(selecting, req) => {
+                    let proxy = match selecting.as_mut().poll(cx) {
+                        Poll::Ready(proxy) => proxy,
+                        Poll::Pending => return Poll::Pending,
+                    };
+                    let req = req.take().expect("proxy selected more than once");
+                    let (in_flight, deadline) = self.client.send_hyper(
+                        *req,
+                        Some(&proxy),
+                        self.binding.as_ref(),
+                        self.connect_timeout,
+                    );
+                    *self.as_mut().project().connect_deadline = deadline;
+                    *self.as_mut().project().request_proxy = Some(proxy);
+                    *self.as_mut().in_flight().get_mut() = ResponseFuture::Default(in_flight);
+                    continue;
+                }
                 #[cfg(feature = "http3")]
                 ResponseFuture::H3(r) => match Pin::new(r).poll(cx) {
                     Poll::Ready(Err(e)) => {
@@ -2628,7 +5209,7 @@ impl Future for PendingRequest {
                     //
                     // If not, just log it and skip the redirect.
                     let loc = loc.and_then(|url| {
-                        if try_uri(&url).is_ok() {
+                        if request_uri(&url, None).is_some() {
                             Some(url)
                         } else {
                             None
@@ -2649,15 +5230,16 @@ impl Future for PendingRequest {
                     let url = self.url.clone();
                     self.as_mut().urls().push(url);
                     let action = self
-                        .client
                         .redirect_policy
+                        .as_deref()
+                        .unwrap_or(&self.client.redirect_policy)
                         .check(res.status(), &loc, &self.urls);
 
                     match action {
                         redirect::ActionKind::Follow => {
                             debug!("redirecting '{}' to '{}'", self.url, loc);
 
-                            if loc.scheme() != "http" && loc.scheme() != "https" {
+                            if !is_supported_scheme(&loc) {
                                 return Poll::Ready(Err(error::url_bad_scheme(loc)));
                             }
 
@@ -2668,16 +5250,71 @@ impl Future for PendingRequest {
                                 )));
                             }
 
+                            let was_unix =
+                                self.unix_socket.is_some() || self.url.scheme() == UNIX_SCHEME;
+                            // A socket given with `RequestBuilder::unix_socket`
+                            // only serves the origin it was set for.
+                            if self.unix_socket.is_some()
+                                && (loc.scheme() != "http"
+                                    || loc.host_str() != self.url.host_str()
+                                    || loc.port() != self.url.port())
+                            {
+                                self.unix_socket = None;
+                            }
+                            // Nor does a name given with `RequestBuilder::sni`,
+                            // or accepting invalid certificates.
+                            #[cfg(feature = "__tls")]
+                            if loc.host_str() != self.url.host_str() || loc.port() != self.url.port()
+                            {
+                                if let Some(ref mut binding) = self.binding {
+                                    binding.tls_server_name = None;
+                                    #[cfg(feature = "danger-request-invalid-certs")]
+                                    {
+                                        binding.accept_invalid_certs = false;
+                                    }
+                                    if *binding == RequestBinding::default() {
+                                        self.binding = None;
+                                    }
+                                }
+                            }
                             self.url = loc;
                             let mut headers =
                                 std::mem::replace(self.as_mut().headers(), HeaderMap::new());
+                            if self.unix_socket.is_some() || self.url.scheme() == UNIX_SCHEME {
+                                set_unix_socket_host(&mut headers, &self.url);
+                            } else if was_unix {
+                                headers.remove(HOST);
+                            }
 
                             remove_sensitive_headers(&mut headers, &self.url, &self.urls);
-                            let uri = try_uri(&self.url)?;
+                            // Only a `machine` entry of the new host applies on a
+                            // redirect, and never over plain http.
+                            if let Some(ref netrc) = self.client.netrc {
+                                if self.url.scheme() == "https"
+                                    && !headers.contains_key(AUTHORIZATION)
+                                {
+                                    if let Some(auth) = self
+                                        .url
+                                        .host_str()
+                                        .and_then(|host| netrc.machine_basic_auth(host))
+                                    {
+                                        headers.insert(AUTHORIZATION, auth);
+                                    }
+                                }
+                            }
+                            let uri = match request_uri(&self.url, self.unix_socket.as_deref()) {
+                                Some(uri) => uri,
+                                None => {
+                                    return Poll::Ready(Err(error::url_invalid_uri(
+                                        self.url.clone(),
+                                    )))
+                                }
+                            };
                             let body = match self.body {
                                 Some(Some(ref body)) => Body::reusable(body.clone()),
                                 _ => Body::empty(),
                             };
+                            let body = self.as_mut().wrap_request_body(body);
 
                             // Add cookies from the cookie store.
                             #[cfg(feature = "cookies")]
@@ -2687,6 +5324,15 @@ impl Future for PendingRequest {
                                 }
                             }
 
+                            let proxy_request = self.client.proxy_request(
+                                &self.method,
+                                &self.url,
+                                &headers,
+                                &body,
+                            );
+                            self.request_proxy =
+                                self.client.request_proxy(&uri, proxy_request.as_ref());
+
                             *self.as_mut().in_flight().get_mut() =
                                 match *self.as_mut().in_flight().as_ref() {
                                     #[cfg(feature = "http3")]
@@ -2711,7 +5357,15 @@ impl Future for PendingRequest {
                                             .expect("valid request parts");
                                         *req.headers_mut() = headers.clone();
                                         std::mem::swap(self.as_mut().headers(), &mut headers);
-                                        ResponseFuture::Default(self.client.hyper.request(req))
+                                        let (in_flight, deadline) = self.client.send_or_select(
+                                            req,
+                                            self.request_proxy.as_ref(),
+                                            proxy_request,
+                                            self.binding.as_ref(),
+                                            self.connect_timeout,
+                                        );
+                                        *self.as_mut().project().connect_deadline = deadline;
+                                        in_flight
                                     }
                                 };
 
@@ -2727,11 +5381,24 @@ impl Future for PendingRequest {
                 }
             }
 
+            // The response may arrive before the body was fully written, in
+            // which case the response read timeout starts now.
+            if self.response_read_timeout_fut.is_none() {
+                if let Some(timeout) = self.client.response_read_timeout {
+                    self.as_mut()
+                        .response_read_timeout()
+                        .set(Some(Box::pin(tokio::time::sleep(timeout))));
+                }
+            }
+            let total_timeout = earliest(
+                self.as_mut().project().total_timeout.take(),
+                self.as_mut().response_read_timeout().take(),
+            );
             let res = Response::new(
                 res,
                 self.url.clone(),
                 self.client.accepts,
-                self.total_timeout.take(),
+                total_timeout,
                 self.read_timeout,
             );
             return Poll::Ready(Ok(res));
@@ -2739,6 +5406,14 @@ impl Future for PendingRequest {
     }
 }
 
+fn earliest(a: Option<Pin<Box<Sleep>>>, b: Option<Pin<Box<Sleep>>>) -> Option<Pin<Box<Sleep>>> {
+    match (a, b) {
+        (Some(a), Some(b)) if b.deadline() < a.deadline() => Some(b),
+        (Some(a), _) => Some(a),
+        (None, b) => b,
+    }
+}
+
 impl fmt::Debug for Pending {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match self.inner {
@@ -2752,6 +5427,66 @@ impl fmt::Debug for Pending {
     }
 }
 
+fn is_supported_scheme(url: &Url) -> bool {
+    match url.scheme() {
+        "http" | "https" => true,
+        scheme => cfg!(unix) && scheme == UNIX_SCHEME,
+    }
+}
+
+/// The URI hyper is given for `url`.
+///
+/// Requests sent over a Unix socket, either with an `http+unix` URL or
+/// `RequestBuilder::unix_socket`, get a `http+unix` URI naming the socket
+/// for the connector to dial.
+fn request_uri(url: &Url, unix_socket: Option<&Path>) -> Option<Uri> {
+    #[cfg(unix)]
+    {
+        use std::os::unix::ffi::OsStrExt;
+
+        let socket = match unix_socket {
+            Some(path) => Some(path.to_path_buf()),
+            None if url.scheme() == UNIX_SCHEME => {
+                let host = url.host_str().filter(|host| !host.is_empty())?;
+                let path: Vec<u8> = percent_encoding::percent_decode_str(host).collect();
+                Some(PathBuf::from(std::ffi::OsStr::from_bytes(&path)))
+            }
+            None => None,
+        };
+        if let Some(path) = socket {
+            let uri = format!(
+                "{}://{}{}",
+                UNIX_SCHEME,
+                crate::connect::unix_socket_authority(&path),
+                &url[url::Position::BeforePath..url::Position::AfterQuery]
+            );
+            return uri.parse().ok();
+        }
+    }
+    #[cfg(not(unix))]
+    let _ = unix_socket;
+    try_uri(url).ok()
+}
+
+/// Sets the `Host` header of a request sent over a Unix socket, which
+/// would otherwise be derived from the socket path.
+fn set_unix_socket_host(headers: &mut HeaderMap, url: &Url) {
+    let host = if url.scheme() == UNIX_SCHEME {
+        HeaderValue::from_static("localhost")
+    } else {
+        let host = match (url.host_str(), url.port()) {
+            (Some(host), Some(port)) => format!("{host}:{port}"),
+            (Some(host), None) => host.to_owned(),
+            (None, _) => return,
+        };
+        match HeaderValue::try_from(host) {
+            Ok(host) => host,
+            Err(_) => return,
+        }
+    };
+    headers.insert(HOST, host);
+}
+
 fn make_referer(next: &Url, previous: &Url) -> Option<HeaderValue> {
     if next.scheme() == "http" && previous.scheme() == "https" {
         return None;
@@ -2771,6 +5506,28 @@ fn add_cookie_header(headers: &mut HeaderMap, cookie_store: &dyn cookie::CookieS
     }
 }
 
+/// `delay` plus up to `jitter_fraction` of it, as picked by `random`,
+/// between 0 and 1.
+///
+/// The fraction is taken between 0 and 1, and the jitter is computed in
+/// nanoseconds, as the largest durations can't go through an `f64`.
+#[cfg(feature = "http2")]
+fn jittered_delay(delay: Duration, jitter_fraction: f64, random: f64) -> Duration {
+    let fraction = jitter_fraction * random;
+    let fraction = if fraction.is_nan() {
+        0.0
+    } else {
+        fraction.clamp(0.0, 1.0)
+    };
+    let scale = (fraction * (1u64 << 32) as f64) as u128;
+    let nanos = (delay.as_nanos() * scale) >> 32;
+    let jitter = Duration::new(
+        (nanos / 1_000_000_000) as u64,
+        (nanos % 1_000_000_000) as u32,
+    );
+    delay.saturating_add(jitter)
+}
+
 #[cfg(test)]
 mod tests {
     #![cfg(not(feature = "rustls-tls-manual-roots-no-provider"))]
@@ -2799,4 +5556,113 @@ mod tests {
         assert!(err.is_builder());
         assert_eq!(url_str, err.url().unwrap().as_str());
     }
+
+    #[test]
+    fn address_and_interface_from_env() {
+        let build = |var: &str| {
+            let builder = crate::Client::builder().local_address_from_env(var);
+            #[cfg(any(
+                target_os = "android",
+                target_os = "fuchsia",
+                target_os = "ios",
+                target_os = "linux",
+                target_os = "macos",
+                target_os = "tvos",
+                target_os = "watchos",
+            ))]
+            let builder = builder.local_interface_from_env(var);
+            builder.build()
+        };
+
+        assert!(build("REQWEST_TEST_UNSET_ADDRESS_VAR").is_ok());
+
+        std::env::set_var("REQWEST_TEST_INVALID_ADDRESS_VAR", "eth0");
+        let err = build("REQWEST_TEST_INVALID_ADDRESS_VAR").unwrap_err();
+        assert!(err.is_builder());
+
+        std::env::set_var("REQWEST_TEST_ADDRESS_VAR", "127.0.0.1");
+        let client = crate::Client::builder()
+            .local_address_from_env("REQWEST_TEST_ADDRESS_VAR")
+            .build();
+        assert!(client.is_ok());
+
+        #[cfg(any(
+            target_os = "android",
+            target_os = "fuchsia",
+            target_os = "ios",
+            target_os = "linux",
+            target_os = "macos",
+            target_os = "tvos",
+            target_os = "watchos",
+        ))]
+        {
+            std::env::set_var("REQWEST_TEST_INTERFACE_VAR", "not a/name");
+            let err = crate::Client::builder()
+                .local_interface_from_env("REQWEST_TEST_INTERFACE_VAR")
+                .build()
+                .unwrap_err();
+            assert!(err.is_builder());
+        }
+    }
+
+    #[test]
+    fn proxies() {
+        let client = crate::Client::builder().no_proxy().build().unwrap();
+        assert!(!client.has_proxy());
+        assert!(client.proxies().is_empty());
+
+        let client = crate::Client::builder()
+            .proxy(crate::Proxy::http("http://proxy.corp:8080").unwrap())
+            .build()
+            .unwrap();
+        assert!(client.has_proxy());
+        assert_eq!(client.proxies().len(), 1);
+        assert_eq!(client.proxies()[0].scheme(), Some("http"));
+    }
+
+    #[cfg(feature = "http2")]
+    #[test]
+    fn jittered_delay() {
+        use super::jittered_delay;
+        use std::time::Duration;
+
+        let delay = Duration::from_millis(100);
+        assert_eq!(jittered_delay(delay, 0.5, 0.0), delay);
+        assert_eq!(jittered_delay(delay, 0.5, 1.0), Duration::from_millis(150));
+        // The fraction is kept between 0 and 1.
+        assert_eq!(jittered_delay(delay, -1.0, 1.0), delay);
+        assert_eq!(jittered_delay(delay, f64::NAN, 1.0), delay);
+        assert_eq!(jittered_delay(delay, f64::INFINITY, 1.0), delay * 2);
+        assert_eq!(jittered_delay(delay, 1e300, 1.0), delay * 2);
+        // The largest delays saturate instead of overflowing.
+        assert_eq!(jittered_delay(Duration::MAX, 1.0, 1.0), Duration::MAX);
+    }
+
+    #[test]
+    fn request_pools_evict_least_recently_used() {
+        use super::{RequestBinding, RequestConnect, MAX_REQUEST_POOLS};
+
+        // Requests bound to different local addresses take different pools.
+        let bound = |i: u32| RequestConnect {
+            binding: Some(RequestBinding {
+                local_address: Some(std::net::Ipv4Addr::from(i).into()),
+                ..Default::default()
+            }),
+            ..RequestConnect::direct()
+        };
+        let client = crate::Client::builder().no_proxy().build().unwrap();
+        let pools = &client.inner.request_pools;
+        let open = |i: u32| pools.client(bound(i).pool_key().unwrap(), bound(i));
+        for i in 0..MAX_REQUEST_POOLS as u32 {
+            open(i);
+        }
+        open(0);
+        open(MAX_REQUEST_POOLS as u32);
+
+        let clients = &pools.clients.lock().unwrap().clients;
+        assert_eq!(clients.len(), MAX_REQUEST_POOLS);
+        assert!(clients.contains_key(&bound(0).pool_key().unwrap()));
+        assert!(!clients.contains_key(&bound(1).pool_key().unwrap()));
+        assert!(clients.contains_key(&bound(MAX_REQUEST_POOLS as u32).pool_key().unwrap()));
+    }
 }
@@ -1,6 +1,7 @@
 pub use self::body::Body;
 pub use self::client::{Client, ClientBuilder};
-pub use self::request::{Request, RequestBuilder};
+pub use self::pool_stats::{HostPoolStats, PoolStats};
+pub use self::request::{Request, RequestBuilder, RequestSigner, Signing};
 pub use self::response::Response;
 pub use self::upgrade::Upgraded;
 
@@ -13,6 +14,7 @@ pub mod decoder;
 pub mod h3_client;
 #[cfg(feature = "multipart")]
 pub mod multipart;
+pub(crate) mod pool_stats;
 pub(crate) mod request;
 mod response;
 mod upgrade;
@@ -0,0 +1,376 @@
+//! Statistics about the connections of a `Client`.
+
+use std::collections::BTreeMap;
+use std::pin::Pin;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll, Waker};
+use std::time::{Duration, Instant};
+
+use bytes::Bytes;
+use futures_util::task::AtomicWaker;
+use http_body::{Body as HttpBody, Frame};
+
+use super::body::ResponseBody;
+
+/// A snapshot of the connections of a `Client`, returned by
+/// [`Client::pool_stats`](crate::Client::pool_stats).
+///
+/// A connection is active while it carries a response whose body has not
+/// been read to the end or dropped, and idle otherwise. HTTP/3 connections
+/// are not counted.
+#[derive(Clone, Debug)]
+pub struct PoolStats {
+    hosts: Vec<HostPoolStats>,
+    created: u64,
+    reused: u64,
+    closed: u64,
+}
+
+/// The connections of a `Client` to one host, as part of [`PoolStats`].
+#[derive(Clone, Debug)]
+pub struct HostPoolStats {
+    host: String,
+    idle: usize,
+    active: usize,
+    created: u64,
+    reused: u64,
+    closed: u64,
+    average_age: Option<Duration>,
+}
+
+impl PoolStats {
+    /// The connections to each host, ordered by host.
+    pub fn hosts(&self) -> &[HostPoolStats] {
+        &self.hosts
+    }
+
+    /// The connections to `host`, such as `"https://example.com:443"`.
+    pub fn host(&self, host: &str) -> Option<&HostPoolStats> {
+        self.hosts.iter().find(|stats| stats.host == host)
+    }
+
+    /// The number of open connections not carrying a response.
+    pub fn idle(&self) -> usize {
+        self.hosts.iter().map(HostPoolStats::idle).sum()
+    }
+
+    /// The number of open connections carrying a response.
+    pub fn active(&self) -> usize {
+        self.hosts.iter().map(HostPoolStats::active).sum()
+    }
+
+    /// The number of connections established so far.
+    pub fn created(&self) -> u64 {
+        self.created
+    }
+
+    /// The number of responses received over a connection that had already
+    /// carried one.
+    pub fn reused(&self) -> u64 {
+        self.reused
+    }
+
+    /// The number of connections closed so far.
+    pub fn closed(&self) -> u64 {
+        self.closed
+    }
+
+    /// The average age of the open connections, if there are any.
+    pub fn average_age(&self) -> Option<Duration> {
+        let (count, total) = self
+            .hosts
+            .iter()
+            .filter_map(|stats| Some((stats.open(), stats.average_age?)))
+            .fold((0u32, Duration::ZERO), |(count, total), (open, age)| {
+                let open = open as u32;
+                (count + open, total + age * open)
+            });
+        total.checked_div(count)
+    }
+}
+
+impl HostPoolStats {
+    /// The scheme, host and port of these connections' destination, such as
+    /// `"https://example.com:443"`.
+    pub fn host(&self) -> &str {
+        &self.host
+    }
+
+    /// The number of open connections not carrying a response.
+    pub fn idle(&self) -> usize {
+        self.idle
+    }
+
+    /// The number of open connections carrying a response.
+    pub fn active(&self) -> usize {
+        self.active
+    }
+
+    /// The number of connections established so far.
+    pub fn created(&self) -> u64 {
+        self.created
+    }
+
+    /// The number of responses received over a connection that had already
+    /// carried one.
+    pub fn reused(&self) -> u64 {
+        self.reused
+    }
+
+    /// The number of connections closed so far.
+    pub fn closed(&self) -> u64 {
+        self.closed
+    }
+
+    /// The average age of the open connections, if there are any.
+    pub fn average_age(&self) -> Option<Duration> {
+        self.average_age
+    }
+
+    fn open(&self) -> usize {
+        self.idle + self.active
+    }
+}
+
+/// Counts the connections of a client as they are made, used and closed.
+#[derive(Default)]
+pub(crate) struct PoolTracker {
+    hosts: Mutex<BTreeMap<String, HostCounters>>,
+}
+
+#[derive(Default)]
+struct HostCounters {
+    open: usize,
+    active: usize,
+    created: u64,
+    reused: u64,
+    closed: u64,
+    // The creation times of the open connections.
+    opened_at: Vec<(u64, Instant)>,
+    next_id: u64,
+}
+
+impl PoolTracker {
+    /// Records a new connection to `host`, which is closed once the returned
+    /// guard is dropped.
+    pub(crate) fn opened(self: &Arc<Self>, host: String) -> ConnGuard {
+        let mut hosts = self.hosts.lock().unwrap();
+        let counters = hosts.entry(host.clone()).or_default();
+        let id = counters.next_id;
+        counters.next_id += 1;
+        counters.open += 1;
+        counters.created += 1;
+        counters.opened_at.push((id, Instant::now()));
+        let conn = Arc::new(ConnState {
+            tracker: self.clone(),
+            host,
+            responses: Mutex::new((0, 0)),
+            awaiting_response: AtomicBool::new(false),
+            upgraded: AtomicBool::new(false),
+            idle_waker: AtomicWaker::new(),
+        });
+        ConnGuard { conn, id }
+    }
+
+    fn closed(&self, host: &str, id: u64) {
+        let mut hosts = self.hosts.lock().unwrap();
+        if let Some(counters) = hosts.get_mut(host) {
+            counters.open -= 1;
+            counters.closed += 1;
+            counters.opened_at.retain(|&(open_id, _)| open_id != id);
+        }
+    }
+
+    /// Records a response received over `conn`, which keeps it active
+    /// until `body` ends. Once `upgraded`, the connection is no longer
+    /// used for HTTP.
+    pub(crate) fn track_response(
+        body: ResponseBody,
+        conn: &ConnHandle,
+        upgraded: bool,
+    ) -> ResponseBody {
+        let conn = conn.0.clone();
+        let empty = body.is_end_stream();
+        if upgraded {
+            conn.upgraded.store(true, Ordering::Release);
+        }
+        {
+            let mut responses = conn.responses.lock().unwrap();
+            let (total, in_flight) = &mut *responses;
+            let mut hosts = conn.tracker.hosts.lock().unwrap();
+            if let Some(counters) = hosts.get_mut(&conn.host) {
+                if *total > 0 {
+                    counters.reused += 1;
+                }
+                if *in_flight == 0 && !empty {
+                    counters.active += 1;
+                }
+            }
+            *total += 1;
+            if !empty {
+                *in_flight += 1;
+            }
+            conn.awaiting_response.store(false, Ordering::Release);
+        }
+        if empty {
+            conn.idle_waker.wake();
+            return body;
+        }
+        super::body::boxed(TrackedBody {
+            inner: body,
+            conn: Some(conn),
+        })
+    }
+
+    pub(crate) fn stats(&self) -> PoolStats {
+        let now = Instant::now();
+        let hosts = self.hosts.lock().unwrap();
+        let mut stats = PoolStats {
+            hosts: Vec::with_capacity(hosts.len()),
+            created: 0,
+            reused: 0,
+            closed: 0,
+        };
+        for (host, counters) in hosts.iter() {
+            let active = counters.active.min(counters.open);
+            let total_age: Duration = counters
+                .opened_at
+                .iter()
+                .map(|&(_, opened)| now - opened)
+                .sum();
+            stats.created += counters.created;
+            stats.reused += counters.reused;
+            stats.closed += counters.closed;
+            stats.hosts.push(HostPoolStats {
+                host: host.clone(),
+                idle: counters.open - active,
+                active,
+                created: counters.created,
+                reused: counters.reused,
+                closed: counters.closed,
+                average_age: total_age.checked_div(counters.open as u32),
+            });
+        }
+        stats
+    }
+}
+
+struct ConnState {
+    tracker: Arc<PoolTracker>,
+    host: String,
+    // The responses received over the connection, in total and in flight.
+    responses: Mutex<(u64, usize)>,
+    // Whether an HTTP/1 request was written and its response not received
+    // yet.
+    awaiting_response: AtomicBool,
+    upgraded: AtomicBool,
+    // Woken once the connection becomes idle.
+    idle_waker: AtomicWaker,
+}
+
+impl ConnState {
+    fn response_done(&self) {
+        let mut responses = self.responses.lock().unwrap();
+        responses.1 -= 1;
+        if responses.1 == 0 {
+            let mut hosts = self.tracker.hosts.lock().unwrap();
+            if let Some(counters) = hosts.get_mut(&self.host) {
+                counters.active -= 1;
+            }
+            drop(hosts);
+            self.idle_waker.wake();
+        }
+    }
+}
+
+/// Held by a connection, to record it closed when dropped.
+pub(crate) struct ConnGuard {
+    conn: Arc<ConnState>,
+    id: u64,
+}
+
+impl ConnGuard {
+    /// A handle given to hyper as extra connection info, to find the
+    /// connection a response came over.
+    pub(crate) fn handle(&self) -> ConnHandle {
+        ConnHandle(self.conn.clone())
+    }
+
+    /// Records that a request is being written over the connection, which
+    /// must be an HTTP/1 one, carrying a single request at a time.
+    pub(crate) fn request_written(&self) {
+        self.conn.awaiting_response.store(true, Ordering::Release);
+    }
+
+    /// Whether the connection carries no request or response, waking
+    /// `waker` once it does not anymore if it does.
+    ///
+    /// A connection handed over by an upgrade is never idle.
+    pub(crate) fn poll_idle(&self, waker: &Waker) -> bool {
+        self.conn.idle_waker.register(waker);
+        !self.conn.upgraded.load(Ordering::Acquire)
+            && !self.conn.awaiting_response.load(Ordering::Acquire)
+            && self.conn.responses.lock().unwrap().1 == 0
+    }
+}
+
+impl Drop for ConnGuard {
+    fn drop(&mut self) {
+        self.conn.tracker.closed(&self.conn.host, self.id);
+    }
+}
+
+#[derive(Clone)]
+pub(crate) struct ConnHandle(Arc<ConnState>);
+
+impl ConnHandle {
+    /// Whether the connection already carried a response.
+    pub(crate) fn is_reused(&self) -> bool {
+        self.0.responses.lock().unwrap().0 > 0
+    }
+}
+
+/// A response body that marks its connection active until it ends.
+struct TrackedBody {
+    inner: ResponseBody,
+    conn: Option<Arc<ConnState>>,
+}
+
+impl TrackedBody {
+    fn done(&mut self) {
+        if let Some(conn) = self.conn.take() {
+            conn.response_done();
+        }
+    }
+}
+
+impl HttpBody for TrackedBody {
+    type Data = Bytes;
+    type Error = Box<dyn std::error::Error + Send + Sync>;
+
+    fn poll_frame(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<Frame<Self::Data>, Self::Error>>> {
+        let frame = Pin::new(&mut self.inner).poll_frame(cx);
+        if let Poll::Ready(None) | Poll::Ready(Some(Err(_))) = frame {
+            self.done();
+        }
+        frame
+    }
+
+    fn is_end_stream(&self) -> bool {
+        self.inner.is_end_stream()
+    }
+
+    fn size_hint(&self) -> http_body::SizeHint {
+        self.inner.size_hint()
+    }
+}
+
+impl Drop for TrackedBody {
+    fn drop(&mut self) {
+        self.done();
+    }
+}
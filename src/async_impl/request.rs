@@ -1,8 +1,14 @@
 use std::convert::TryFrom;
 use std::fmt;
 use std::future::Future;
+use std::net::IpAddr;
+use std::path::PathBuf;
+use std::pin::Pin;
+use std::sync::Arc;
 use std::time::Duration;
 
+use bytes::Bytes;
+
 use serde::Serialize;
 #[cfg(feature = "json")]
 use serde_json;
@@ -12,11 +18,13 @@ use super::client::{Client, Pending};
 #[cfg(feature = "multipart")]
 use super::multipart;
 use super::response::Response;
-#[cfg(feature = "multipart")]
-use crate::header::CONTENT_LENGTH;
-use crate::header::{HeaderMap, HeaderName, HeaderValue, CONTENT_TYPE};
-use crate::{Method, Url};
+#[cfg(feature = "graphql")]
+use crate::header::ACCEPT;
+use crate::header::{HeaderMap, HeaderName, HeaderValue, CONTENT_LENGTH, CONTENT_TYPE};
+use crate::connect::RequestBinding;
+use crate::{redirect, Method, Url};
 use http::{request::Parts, Request as HttpRequest, Version};
+use http_body::Body as HttpBody;
 
 /// A request which can be executed with `Client::execute()`.
 pub struct Request {
@@ -25,7 +33,11 @@ pub struct Request {
     headers: HeaderMap,
     body: Option<Body>,
     timeout: Option<Duration>,
+    connect_timeout: Option<Duration>,
     version: Version,
+    redirect_policy: Option<Arc<redirect::Policy>>,
+    unix_socket: Option<PathBuf>,
+    binding: Option<RequestBinding>,
 }
 
 /// A builder to construct the properties of a `Request`.
@@ -47,7 +59,11 @@ impl Request {
             headers: HeaderMap::new(),
             body: None,
             timeout: None,
+            connect_timeout: None,
             version: Version::default(),
+            redirect_policy: None,
+            unix_socket: None,
+            binding: None,
         }
     }
 
@@ -111,6 +127,18 @@ impl Request {
         &mut self.timeout
     }
 
+    /// Get the connect timeout.
+    #[inline]
+    pub fn connect_timeout(&self) -> Option<&Duration> {
+        self.connect_timeout.as_ref()
+    }
+
+    /// Get a mutable reference to the connect timeout.
+    #[inline]
+    pub fn connect_timeout_mut(&mut self) -> &mut Option<Duration> {
+        &mut self.connect_timeout
+    }
+
     /// Get the http version.
     #[inline]
     pub fn version(&self) -> Version {
@@ -123,6 +151,42 @@ impl Request {
         &mut self.version
     }
 
+    /// Get the body's bytes, if it has a body that isn't a stream.
+    pub fn body_bytes(&self) -> Option<&Bytes> {
+        self.body.as_ref().and_then(Body::reusable_bytes)
+    }
+
+    /// Returns true if a body is set on the request.
+    #[inline]
+    pub fn has_body(&self) -> bool {
+        self.body.is_some()
+    }
+
+    /// Estimate the number of bytes of the request headers and body.
+    ///
+    /// Each header counts as its name and value plus the `": "` and `"\r\n"`
+    /// separators. The body is counted from the `Content-Length` header when
+    /// it is set, and from the body's size hint otherwise, so streaming bodies
+    /// of unknown length may be undercounted.
+    pub fn estimated_size(&self) -> usize {
+        let headers = self
+            .headers
+            .iter()
+            .map(|(name, value)| name.as_str().len() + value.len() + 4)
+            .sum::<usize>();
+        let content_length = self
+            .headers
+            .get(CONTENT_LENGTH)
+            .and_then(|v| v.to_str().ok())
+            .and_then(|v| v.parse::<u64>().ok());
+        let body = match (content_length, &self.body) {
+            (Some(len), _) => len,
+            (None, Some(body)) => HttpBody::size_hint(body).lower(),
+            (None, None) => 0,
+        };
+        headers.saturating_add(usize::try_from(body).unwrap_or(usize::MAX))
+    }
+
     /// Attempt to clone the request.
     ///
     /// `None` is returned if the request can not be cloned, i.e. if the body is a stream.
@@ -133,12 +197,70 @@ impl Request {
         };
         let mut req = Request::new(self.method().clone(), self.url().clone());
         *req.timeout_mut() = self.timeout().copied();
+        *req.connect_timeout_mut() = self.connect_timeout().copied();
         *req.headers_mut() = self.headers().clone();
         *req.version_mut() = self.version();
+        req.redirect_policy = self.redirect_policy.clone();
+        req.unix_socket = self.unix_socket.clone();
+        req.binding = self.binding.clone();
         req.body = body;
         Some(req)
     }
 
+    pub(crate) fn set_redirect_policy(&mut self, policy: redirect::Policy) {
+        self.redirect_policy = Some(Arc::new(policy));
+    }
+
+    pub(super) fn take_redirect_policy(&mut self) -> Option<Arc<redirect::Policy>> {
+        self.redirect_policy.take()
+    }
+
+    #[cfg(unix)]
+    pub(crate) fn set_unix_socket(&mut self, path: PathBuf) {
+        self.unix_socket = Some(path);
+    }
+
+    pub(super) fn take_unix_socket(&mut self) -> Option<PathBuf> {
+        self.unix_socket.take()
+    }
+
+    pub(crate) fn set_local_address(&mut self, addr: IpAddr) {
+        self.binding
+            .get_or_insert_with(Default::default)
+            .local_address = Some(addr);
+    }
+
+    #[cfg(any(
+        target_os = "android",
+        target_os = "fuchsia",
+        target_os = "ios",
+        target_os = "linux",
+        target_os = "macos",
+        target_os = "tvos",
+        target_os = "watchos",
+    ))]
+    pub(crate) fn set_interface(&mut self, interface: &str) {
+        self.binding.get_or_insert_with(Default::default).interface = Some(interface.to_owned());
+    }
+
+    #[cfg(feature = "__tls")]
+    pub(crate) fn set_tls_server_name(&mut self, name: &str) {
+        self.binding
+            .get_or_insert_with(Default::default)
+            .tls_server_name = Some(name.to_owned());
+    }
+
+    #[cfg(all(feature = "__tls", feature = "danger-request-invalid-certs"))]
+    pub(crate) fn set_accept_invalid_certs(&mut self, accept: bool) {
+        self.binding
+            .get_or_insert_with(Default::default)
+            .accept_invalid_certs = accept;
+    }
+
+    pub(super) fn take_binding(&mut self) -> Option<RequestBinding> {
+        self.binding.take()
+    }
+
     pub(super) fn pieces(
         self,
     ) -> (
@@ -147,6 +269,7 @@ impl Request {
         HeaderMap,
         Option<Body>,
         Option<Duration>,
+        Option<Duration>,
         Version,
     ) {
         (
@@ -155,6 +278,7 @@ impl Request {
             self.headers,
             self.body,
             self.timeout,
+            self.connect_timeout,
             self.version,
         )
     }
@@ -290,6 +414,160 @@ impl RequestBuilder {
         self
     }
 
+    /// Set a timeout for only the connect phase of this request.
+    ///
+    /// This includes connecting through a proxy, and overrides the timeout
+    /// configured using `ClientBuilder::connect_timeout()`. It has no effect
+    /// if the request is sent over an already established, pooled connection.
+    ///
+    /// It runs until the request gets a connection, so it also covers any
+    /// retried attempts, see `ClientBuilder::connect_retry`.
+    pub fn connect_timeout(mut self, timeout: Duration) -> RequestBuilder {
+        if let Ok(ref mut req) = self.request {
+            *req.connect_timeout_mut() = Some(timeout);
+        }
+        self
+    }
+
+    /// Bind the connections of this request to a local IP address.
+    ///
+    /// This overrides the address configured using
+    /// `ClientBuilder::local_address()`. Connections bound this way are
+    /// pooled apart from the client's other connections, and only reused by
+    /// requests bound the same way.
+    pub fn local_address(mut self, addr: IpAddr) -> RequestBuilder {
+        if let Ok(ref mut req) = self.request {
+            req.set_local_address(addr);
+        }
+        self
+    }
+
+    /// Bind the connections of this request to a network interface, such
+    /// as `"eth1"`.
+    ///
+    /// This overrides the interface configured using
+    /// `ClientBuilder::interface()`. Connections bound this way are pooled
+    /// apart from the client's other connections, and only reused by
+    /// requests bound the same way.
+    #[cfg(any(
+        target_os = "android",
+        target_os = "fuchsia",
+        target_os = "ios",
+        target_os = "linux",
+        target_os = "macos",
+        target_os = "tvos",
+        target_os = "watchos",
+    ))]
+    pub fn interface(mut self, interface: &str) -> RequestBuilder {
+        if let Ok(ref mut req) = self.request {
+            req.set_interface(interface);
+        }
+        self
+    }
+
+    /// Use `name` in place of the host of the URL in the TLS handshake of
+    /// this request.
+    ///
+    /// The server is asked for the certificate of `name` with SNI, and the
+    /// certificate it presents is verified against `name`, while the
+    /// connection still goes to the host of the URL. This allows reaching a
+    /// server by IP address, or through another front, when its certificate
+    /// is for another name. With `ClientBuilder::tls_sni(false)`, no SNI is
+    /// sent, but the certificate is still verified against `name`.
+    ///
+    /// Redirects to another origin use their own host. Connections made
+    /// this way are pooled apart from the client's other connections, and
+    /// only reused by requests using the same name.
+    ///
+    /// # Optional
+    ///
+    /// This requires the optional `default-tls`, `native-tls`, or `rustls-tls(-...)`
+    /// feature to be enabled.
+    #[cfg(feature = "__tls")]
+    #[cfg_attr(
+        docsrs,
+        doc(cfg(any(
+            feature = "default-tls",
+            feature = "native-tls",
+            feature = "rustls-tls"
+        )))
+    )]
+    pub fn sni(mut self, name: &str) -> RequestBuilder {
+        if let Ok(ref mut req) = self.request {
+            req.set_tls_server_name(name);
+        }
+        self
+    }
+
+    /// Controls the use of certificate validation for this request only.
+    ///
+    /// Defaults to `false`, leaving validation to the client.
+    ///
+    /// # Warning
+    ///
+    /// You should think very carefully before using this method. If
+    /// invalid certificates are trusted, *any* certificate for *any* site
+    /// will be trusted for use. This includes expired certificates. This is
+    /// meant for tools diagnosing misconfigured servers, without turning off
+    /// validation for the whole client.
+    ///
+    /// Connections made this way are pooled apart from the client's other
+    /// connections, and their TLS sessions aren't resumed. Redirects to
+    /// another origin are validated as usual, as are HTTPS proxies. Clients
+    /// built with a preconfigured native-tls connector keep validating.
+    ///
+    /// # Optional
+    ///
+    /// This requires the `danger-request-invalid-certs` Cargo feature, and
+    /// the optional `default-tls`, `native-tls`, or `rustls-tls(-...)`
+    /// feature to be enabled.
+    #[cfg(all(feature = "__tls", feature = "danger-request-invalid-certs"))]
+    #[cfg_attr(docsrs, doc(cfg(feature = "danger-request-invalid-certs")))]
+    pub fn danger_accept_invalid_certs(mut self, accept_invalid_certs: bool) -> RequestBuilder {
+        if let Ok(ref mut req) = self.request {
+            req.set_accept_invalid_certs(accept_invalid_certs);
+        }
+        self
+    }
+
+    /// Send this request over the Unix domain socket at `path`.
+    ///
+    /// The URL must use the `http` scheme; its host and port are only used
+    /// for the `Host` header. The same can be expressed with an
+    /// `http+unix://` URL whose host is the percent-encoded socket path, such
+    /// as `http+unix://%2Fvar%2Frun%2Fdocker.sock/v1.41/containers/json`.
+    #[cfg(unix)]
+    pub fn unix_socket<P: AsRef<std::path::Path>>(mut self, path: P) -> RequestBuilder {
+        if let Ok(ref mut req) = self.request {
+            req.set_unix_socket(path.as_ref().to_path_buf());
+        }
+        self
+    }
+
+    /// Set a redirect `Policy` for only this request.
+    ///
+    /// This overrides the policy configured using `ClientBuilder::redirect()`.
+    pub fn redirect_policy(mut self, policy: redirect::Policy) -> RequestBuilder {
+        if let Ok(ref mut req) = self.request {
+            req.set_redirect_policy(policy);
+        }
+        self
+    }
+
+    /// Follow at most `max` redirects for this request.
+    ///
+    /// This is a shortcut for `redirect_policy(Policy::limited(max))`.
+    pub fn max_redirects(self, max: u32) -> RequestBuilder {
+        self.redirect_policy(redirect::Policy::limited(max as usize))
+    }
+
+    /// Don't follow redirects for this request.
+    ///
+    /// This is a shortcut for `redirect_policy(Policy::none())`.
+    pub fn no_redirect(self) -> RequestBuilder {
+        self.redirect_policy(redirect::Policy::none())
+    }
+
     /// Sends a multipart/form-data body.
     ///
     /// ```
@@ -331,6 +609,39 @@ impl RequestBuilder {
         builder
     }
 
+    /// Sends a multipart/form-data body made of the given text fields.
+    ///
+    /// This is a shorthand for building a [`multipart::Form`] with one
+    /// `text` part per field and passing it to [`RequestBuilder::multipart`].
+    ///
+    /// ```
+    /// # use reqwest::Error;
+    ///
+    /// # async fn run() -> Result<(), Error> {
+    /// let client = reqwest::Client::new();
+    /// let response = client.post("your url")
+    ///     .multipart_fields([("key3", "value3"), ("key4", "value4")])
+    ///     .send()
+    ///     .await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[cfg(feature = "multipart")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "multipart")))]
+    pub fn multipart_fields<I, K, V>(self, fields: I) -> RequestBuilder
+    where
+        I: IntoIterator<Item = (K, V)>,
+        K: AsRef<str>,
+        V: AsRef<str>,
+    {
+        let form = fields
+            .into_iter()
+            .fold(multipart::Form::new(), |form, (name, value)| {
+                form.text(name.as_ref().to_owned(), value.as_ref().to_owned())
+            });
+        self.multipart(form)
+    }
+
     /// Modify the query string of the URL.
     ///
     /// Modifies the URL of this request, adding the parameters provided.
@@ -426,6 +737,44 @@ impl RequestBuilder {
         self
     }
 
+    /// Send a form body made of the given fields.
+    ///
+    /// Like [`RequestBuilder::form`], this sets the body to the url encoded
+    /// fields and the `Content-Type: application/x-www-form-urlencoded`
+    /// header, but takes the fields from an iterator of pairs instead of a
+    /// `Serialize` value.
+    ///
+    /// ```rust
+    /// # use reqwest::Error;
+    /// #
+    /// # async fn run() -> Result<(), Error> {
+    /// let client = reqwest::Client::new();
+    /// let res = client.post("http://httpbin.org")
+    ///     .urlencoded_fields([("lang", "rust"), ("edition", "2021")])
+    ///     .send()
+    ///     .await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn urlencoded_fields<I, K, V>(mut self, fields: I) -> RequestBuilder
+    where
+        I: IntoIterator<Item = (K, V)>,
+        K: AsRef<str>,
+        V: AsRef<str>,
+    {
+        if let Ok(ref mut req) = self.request {
+            let body = url::form_urlencoded::Serializer::new(String::new())
+                .extend_pairs(fields)
+                .finish();
+            req.headers_mut().insert(
+                CONTENT_TYPE,
+                HeaderValue::from_static("application/x-www-form-urlencoded"),
+            );
+            *req.body_mut() = Some(body.into());
+        }
+        self
+    }
+
     /// Send a JSON body.
     ///
     /// # Optional
@@ -458,6 +807,67 @@ impl RequestBuilder {
         self
     }
 
+    /// Send a GraphQL query with variables.
+    ///
+    /// The body is a JSON object of the form
+    /// `{"query": "...", "variables": {...}}`, and the `Content-Type` and
+    /// `Accept` headers are set to `application/json`.
+    ///
+    /// # Optional
+    ///
+    /// This requires the optional `graphql` feature enabled.
+    ///
+    /// # Errors
+    ///
+    /// Serialization can fail if `T`'s implementation of `Serialize` decides to
+    /// fail, or if `T` contains a map with non-string keys.
+    #[cfg(feature = "graphql")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "graphql")))]
+    pub fn graphql<T: Serialize + ?Sized>(mut self, query: &str, variables: &T) -> RequestBuilder {
+        match serde_json::to_value(variables) {
+            Ok(variables) => self.graphql_body(query, Some(variables)),
+            Err(err) => {
+                if self.request.is_ok() {
+                    self.request = Err(crate::error::builder(err));
+                }
+                self
+            }
+        }
+    }
+
+    /// Send a GraphQL query without variables.
+    ///
+    /// Same as [`RequestBuilder::graphql`], but the `variables` key is omitted
+    /// from the body.
+    ///
+    /// # Optional
+    ///
+    /// This requires the optional `graphql` feature enabled.
+    #[cfg(feature = "graphql")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "graphql")))]
+    pub fn graphql_no_vars(self, query: &str) -> RequestBuilder {
+        self.graphql_body(query, None)
+    }
+
+    #[cfg(feature = "graphql")]
+    fn graphql_body(mut self, query: &str, variables: Option<serde_json::Value>) -> RequestBuilder {
+        if let Ok(ref mut req) = self.request {
+            let mut body = serde_json::Map::new();
+            body.insert("query".to_owned(), query.into());
+            if let Some(variables) = variables {
+                body.insert("variables".to_owned(), variables);
+            }
+            // Serializing a `serde_json::Value` cannot fail.
+            let body = serde_json::to_vec(&body).expect("serialize graphql body");
+            req.headers_mut()
+                .insert(CONTENT_TYPE, HeaderValue::from_static("application/json"));
+            req.headers_mut()
+                .insert(ACCEPT, HeaderValue::from_static("application/json"));
+            *req.body_mut() = Some(body.into());
+        }
+        self
+    }
+
     /// Disable CORS on fetching the request.
     ///
     /// # WASM
@@ -486,6 +896,41 @@ impl RequestBuilder {
         (self.client, self.request)
     }
 
+    /// Build the `Request` and let `signer` modify it, typically to add
+    /// authentication headers computed from its contents.
+    ///
+    /// # Errors
+    ///
+    /// This method fails if the request could not be built, or if `signer`
+    /// returns an error.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # async fn run(signer: impl reqwest::RequestSigner) -> Result<(), reqwest::Error> {
+    /// let response = reqwest::Client::new()
+    ///     .post("https://hyper.rs")
+    ///     .body("hello")
+    ///     .sign(&signer)
+    ///     .await?
+    ///     .send()
+    ///     .await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn sign<S>(self, signer: &S) -> crate::Result<RequestBuilder>
+    where
+        S: RequestSigner + ?Sized,
+    {
+        let (client, req) = self.build_split();
+        let mut req = req?;
+        signer
+            .sign(&mut req)
+            .await
+            .map_err(|e| crate::error::builder(e).with_url(req.url().clone()))?;
+        Ok(RequestBuilder::from_parts(client, req))
+    }
+
     /// Constructs the Request and sends it to the target URL, returning a
     /// future Response.
     ///
@@ -545,6 +990,20 @@ impl RequestBuilder {
     }
 }
 
+/// Alias for the `Future` type returned by a `RequestSigner`.
+pub type Signing<'a> =
+    Pin<Box<dyn Future<Output = Result<(), Box<dyn std::error::Error + Send + Sync>>> + Send + 'a>>;
+
+/// Trait for signing requests, used by [`RequestBuilder::sign`].
+///
+/// A signer typically computes a signature over the method, URL, headers
+/// and body of the request, and adds it as a header. The body is available
+/// through [`Request::body_bytes`] unless it is a stream.
+pub trait RequestSigner: Send + Sync {
+    /// Sign the request, modifying it in place.
+    fn sign<'a>(&'a self, req: &'a mut Request) -> Signing<'a>;
+}
+
 impl fmt::Debug for Request {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         fmt_request_fields(&mut f.debug_struct("Request"), self).finish()
@@ -620,7 +1079,11 @@ where
             headers,
             body: Some(body.into()),
             timeout: None,
+            connect_timeout: None,
             version,
+            redirect_policy: None,
+            unix_socket: None,
+            binding: None,
         })
     }
 }
@@ -809,6 +1272,51 @@ mod tests {
         assert!(clone.is_none());
     }
 
+    #[test]
+    fn redirect_policy_override() {
+        let client = Client::new();
+        let mut req = client
+            .get("https://localhost/")
+            .max_redirects(3)
+            .build()
+            .expect("request build");
+        let clone = req.try_clone().expect("clone");
+        assert!(req.take_redirect_policy().is_some());
+        assert!(req.take_redirect_policy().is_none());
+        assert!(clone.redirect_policy.is_some());
+
+        let req = client.get("https://localhost/").build().expect("request build");
+        assert!(req.redirect_policy.is_none());
+    }
+
+    #[test]
+    fn estimated_size() {
+        let client = Client::new();
+        let req = client
+            .get("https://localhost/")
+            .header("x-foo", "bar")
+            .build()
+            .expect("request build");
+        assert!(!req.has_body());
+        assert_eq!(req.estimated_size(), "x-foo: bar\r\n".len());
+
+        let req = client
+            .post("https://localhost/")
+            .body("hello")
+            .build()
+            .expect("request build");
+        assert!(req.has_body());
+        assert_eq!(req.estimated_size(), 5);
+
+        let req = client
+            .post("https://localhost/")
+            .header("content-length", "100")
+            .body("hello")
+            .build()
+            .expect("request build");
+        assert_eq!(req.estimated_size(), "content-length: 100\r\n".len() + 100);
+    }
+
     #[test]
     fn convert_url_authority_into_basic_auth() {
         let client = Client::new();
@@ -17,6 +17,8 @@ use url::Url;
 use super::body::Body;
 use super::decoder::{Accepts, Decoder};
 use crate::async_impl::body::ResponseBody;
+use crate::connect::ConnectionInfo;
+use crate::proxy::ProxyInfo;
 #[cfg(feature = "cookies")]
 use crate::cookie;
 
@@ -113,10 +115,26 @@ impl Response {
 
     /// Get the remote address used to get this `Response`.
     pub fn remote_addr(&self) -> Option<SocketAddr> {
-        self.res
-            .extensions()
+        let extensions = self.res.extensions();
+        extensions
             .get::<HttpInfo>()
             .map(|info| info.remote_addr())
+            .or_else(|| {
+                extensions
+                    .get::<crate::proxy::CustomRemoteAddr>()
+                    .map(|addr| addr.0)
+            })
+    }
+
+    /// Get the proxy of the connection this `Response` came over, or `None`
+    /// if it went direct.
+    pub fn proxy_info(&self) -> Option<&ProxyInfo> {
+        self.res.extensions().get::<ProxyInfo>()
+    }
+
+    /// Get the details of the connection this `Response` came over.
+    pub fn connection_info(&self) -> Option<&ConnectionInfo> {
+        self.res.extensions().get::<ConnectionInfo>()
     }
 
     /// Returns a reference to the associated extensions.
@@ -293,6 +311,66 @@ impl Response {
             .map(|buf| buf.to_bytes())
     }
 
+    /// Read the rest of the response body and discard it.
+    ///
+    /// Unlike [`Response::bytes`], this doesn't buffer the body. Reading the
+    /// body to its end allows the connection to be returned to the pool, so
+    /// this is useful when only the status or headers are needed.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # async fn run() -> Result<(), Box<dyn std::error::Error>> {
+    /// let res = reqwest::get("http://httpbin.org/ip").await?;
+    /// let status = res.status();
+    /// res.consume().await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn consume(self) -> crate::Result<()> {
+        use http_body_util::BodyExt;
+
+        let mut body = self.res.into_body();
+        while let Some(frame) = body.frame().await {
+            frame?;
+        }
+        Ok(())
+    }
+
+    /// Decompress the response body according to its `Content-Encoding`
+    /// header, when automatic decompression was disabled for it.
+    ///
+    /// The `Content-Encoding` and `Content-Length` headers are removed if
+    /// the body is going to be decompressed. The response is returned as is
+    /// if it isn't compressed, if it was decompressed already, or if support
+    /// for its encoding isn't enabled with the `gzip`, `brotli`, `zstd` or
+    /// `deflate` features.
+    ///
+    /// The body is decompressed as it is read, so a corrupt or truncated
+    /// body makes reading it fail with an error for which `is_decode()`
+    /// returns true.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # async fn run() -> Result<(), Box<dyn std::error::Error>> {
+    /// let client = reqwest::Client::builder().no_gzip().build()?;
+    /// let res = client.get("http://httpbin.org/gzip").send().await?;
+    /// // inspect the compressed response here...
+    /// let body = res.decompress().text().await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn decompress(self) -> Response {
+        let (mut parts, body) = self.res.into_parts();
+        let body: ResponseBody = http_body_util::combinators::BoxBody::new(body.map_err(Into::into));
+        let decoder = Decoder::detect(&mut parts.headers, body, Accepts::default());
+        Response {
+            res: hyper::Response::from_parts(parts, decoder),
+            url: self.url,
+        }
+    }
+
     /// Stream a chunk of the response body.
     ///
     /// When the response body has been exhausted, this will return `None`.
@@ -416,6 +494,42 @@ impl Response {
         }
     }
 
+    /// Check that the response has the expected `Content-Type`.
+    ///
+    /// Only the media type is compared, so parameters such as
+    /// `charset=utf-8` are ignored. The comparison is case-insensitive.
+    ///
+    /// The error's source is a [`ContentTypeMismatch`](crate::ContentTypeMismatch)
+    /// describing the expected and actual values.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # async fn run() -> Result<(), Box<dyn std::error::Error>> {
+    /// let res = reqwest::get("http://httpbin.org/json").await?;
+    /// res.assert_content_type("application/json")?;
+    /// let body = res.text().await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn assert_content_type(&self, expected: &str) -> crate::Result<&Self> {
+        let actual = self
+            .headers()
+            .get(crate::header::CONTENT_TYPE)
+            .map(|value| String::from_utf8_lossy(value.as_bytes()).into_owned());
+
+        match actual {
+            Some(ref actual) if media_type(actual).eq_ignore_ascii_case(media_type(expected)) => {
+                Ok(self)
+            }
+            _ => Err(crate::error::content_type_mismatch(
+                *self.url.clone(),
+                expected,
+                actual,
+            )),
+        }
+    }
+
     // private
 
     // The Response's body is an implementation detail.
@@ -429,6 +543,14 @@ impl Response {
     }
 }
 
+fn media_type(content_type: &str) -> &str {
+    content_type
+        .split(';')
+        .next()
+        .unwrap_or(content_type)
+        .trim()
+}
+
 impl fmt::Debug for Response {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         f.debug_struct("Response")
@@ -502,4 +624,39 @@ mod tests {
         assert_eq!(response.status(), 200);
         assert_eq!(*response.url(), url);
     }
+
+    #[test]
+    fn test_assert_content_type() {
+        let response = Builder::new()
+            .header("content-type", "Application/JSON; charset=utf-8")
+            .body("{}")
+            .unwrap();
+        let response = Response::from(response);
+
+        assert!(response.assert_content_type("application/json").is_ok());
+
+        let err = response.assert_content_type("text/html").unwrap_err();
+        assert!(err.is_decode());
+        let mismatch = std::error::Error::source(&err)
+            .and_then(|e| e.downcast_ref::<crate::ContentTypeMismatch>())
+            .unwrap();
+        assert_eq!(mismatch.expected(), "text/html");
+        assert_eq!(
+            mismatch.actual(),
+            Some("Application/JSON; charset=utf-8")
+        );
+    }
+
+    #[test]
+    fn test_assert_content_type_missing() {
+        let response = Response::from(Builder::new().body("").unwrap());
+
+        let err = response
+            .assert_content_type("application/json")
+            .unwrap_err();
+        let mismatch = std::error::Error::source(&err)
+            .and_then(|e| e.downcast_ref::<crate::ContentTypeMismatch>())
+            .unwrap();
+        assert_eq!(mismatch.actual(), None);
+    }
 }
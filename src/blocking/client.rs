@@ -5,6 +5,7 @@ use std::fmt;
 use std::future::Future;
 use std::net::IpAddr;
 use std::net::SocketAddr;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
 use std::thread;
 use std::time::Duration;
@@ -16,14 +17,17 @@ use tokio::sync::{mpsc, oneshot};
 use super::request::{Request, RequestBuilder};
 use super::response::Response;
 use super::wait;
-use crate::dns::Resolve;
+use crate::dns::{DnsLookup, Resolve};
+use crate::error::BoxError;
 #[cfg(feature = "__tls")]
 use crate::tls;
 #[cfg(feature = "__tls")]
 use crate::Certificate;
 #[cfg(any(feature = "native-tls", feature = "__rustls"))]
 use crate::Identity;
-use crate::{async_impl, header, redirect, IntoUrl, Method, Proxy};
+use crate::{
+    async_impl, header, redirect, CustomProxyStream, IntoUrl, Method, Proxy, ProxySelection,
+};
 
 /// A `Client` to make Requests with.
 ///
@@ -321,6 +325,14 @@ impl ClientBuilder {
         self.with_inner(|inner| inner.no_deflate())
     }
 
+    /// Set whether an `Accept-Encoding` header listing the enabled
+    /// decompression algorithms is added to requests.
+    ///
+    /// Default is `true`.
+    pub fn auto_accept_encoding(self, enable: bool) -> ClientBuilder {
+        self.with_inner(|inner| inner.auto_accept_encoding(enable))
+    }
+
     // Redirect options
 
     /// Set a `redirect::Policy` for this client.
@@ -359,6 +371,67 @@ impl ClientBuilder {
         self.with_inner(move |inner| inner.no_proxy())
     }
 
+    /// Bypass every proxy for link-local and private network addresses.
+    ///
+    /// Default is `false`.
+    pub fn no_proxy_private_networks(self, enabled: bool) -> ClientBuilder {
+        self.with_inner(|inner| inner.no_proxy_private_networks(enabled))
+    }
+
+    /// Re-read the system proxy settings once they are older than `interval`.
+    ///
+    /// Default is `None`.
+    pub fn system_proxy_refresh<D>(self, interval: D) -> ClientBuilder
+    where
+        D: Into<Option<Duration>>,
+    {
+        self.with_inner(move |inner| inner.system_proxy_refresh(interval))
+    }
+
+    /// Read credentials from a netrc file, like curl's `--netrc`.
+    ///
+    /// Proxies without credentials in their URL use the login of their
+    /// host in the file, and so do requests without an `Authorization`
+    /// header. With `None`, the file is `$NETRC` or `~/.netrc`.
+    ///
+    /// Default is not reading a netrc file.
+    pub fn netrc<P>(self, path: P) -> ClientBuilder
+    where
+        P: Into<Option<PathBuf>>,
+    {
+        self.with_inner(move |inner| inner.netrc(path))
+    }
+
+    /// Call `callback` each time a new connection is opened, with the proxy
+    /// it goes through, or `None` if it goes direct.
+    ///
+    /// Pooled connections are reused without calling it again. The proxy of
+    /// the connection a response came over is available from
+    /// `Response::proxy_info`.
+    pub fn on_proxy_selected<F>(self, callback: F) -> ClientBuilder
+    where
+        F: Fn(&ProxySelection) + Send + Sync + 'static,
+    {
+        self.with_inner(move |inner| inner.on_proxy_selected(callback))
+    }
+
+    /// Set whether destination hostnames may be resolved locally when
+    /// connecting through a SOCKS5 proxy.
+    ///
+    /// When this is `false`, the hostname is always sent to the proxy, as
+    /// with `socks5h://`.
+    ///
+    /// Default is `true`.
+    ///
+    /// # Optional
+    ///
+    /// This requires the optional `socks` feature to be enabled.
+    #[cfg(feature = "socks")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "socks")))]
+    pub fn proxy_resolve_via_system_dns(self, enabled: bool) -> ClientBuilder {
+        self.with_inner(|inner| inner.proxy_resolve_via_system_dns(enabled))
+    }
+
     // Timeout options
 
     /// Set a timeout for connect, read and write operations of a `Client`.
@@ -374,6 +447,34 @@ impl ClientBuilder {
         self
     }
 
+    /// Set a timeout for receiving the response.
+    ///
+    /// The timeout starts once the last byte of the request body has been
+    /// written, and requires the complete response body to be received
+    /// before the deadline is reached. It applies to HTTP/1 and HTTP/2.
+    ///
+    /// Unlike `timeout`, it leaves out connecting and sending the request,
+    /// so slow uploads don't count against it.
+    ///
+    /// Default is `None`.
+    pub fn response_read_timeout(self, timeout: Duration) -> ClientBuilder {
+        self.with_inner(|inner| inner.response_read_timeout(timeout))
+    }
+
+    /// Set a timeout for sending the request body.
+    ///
+    /// The timeout starts when the request is sent, once connected, and
+    /// requires the whole body to be written before the deadline is reached.
+    /// It applies to HTTP/1 and HTTP/2.
+    ///
+    /// Unlike `timeout`, it leaves out connecting and receiving the
+    /// response, so slow downloads don't count against it.
+    ///
+    /// Default is `None`.
+    pub fn request_write_timeout(self, timeout: Duration) -> ClientBuilder {
+        self.with_inner(|inner| inner.request_write_timeout(timeout))
+    }
+
     /// Set a timeout for only the connect phase of a `Client`.
     ///
     /// Default is `None`.
@@ -389,6 +490,29 @@ impl ClientBuilder {
         }
     }
 
+    /// Set a timeout for only the connect phase to hosts matching
+    /// `host_pattern`, such as `"*.example.com"`.
+    ///
+    /// See [`async ClientBuilder::connect_timeout_for`](crate::ClientBuilder::connect_timeout_for).
+    pub fn connect_timeout_for(self, host_pattern: &str, timeout: Duration) -> ClientBuilder {
+        self.with_inner(move |inner| inner.connect_timeout_for(host_pattern, timeout))
+    }
+
+    /// Try the addresses of a host one at a time, giving each at most
+    /// `timeout` to connect, starting from a different one each time.
+    ///
+    /// See [`async ClientBuilder::connect_timeout_per_address`](crate::ClientBuilder::connect_timeout_per_address).
+    pub fn connect_timeout_per_address(self, timeout: Duration) -> ClientBuilder {
+        self.with_inner(move |inner| inner.connect_timeout_per_address(timeout))
+    }
+
+    /// Set a policy for retrying connections that fail to be established.
+    ///
+    /// Default is to not retry.
+    pub fn connect_retry(self, retry: crate::ConnectRetry) -> ClientBuilder {
+        self.with_inner(move |inner| inner.connect_retry(retry))
+    }
+
     /// Set whether connections should emit verbose logs.
     ///
     /// Enabling this option will emit [log][] messages at the `TRACE` level
@@ -399,6 +523,15 @@ impl ClientBuilder {
         self.with_inner(move |inner| inner.connection_verbose(verbose))
     }
 
+    /// Calls `hook` with the bytes read and written on every connection,
+    /// with their direction and an id telling apart the connections.
+    pub fn wire_log<F>(self, hook: F) -> ClientBuilder
+    where
+        F: Fn(crate::WireDirection, u32, &[u8]) + Send + Sync + 'static,
+    {
+        self.with_inner(move |inner| inner.wire_log(hook))
+    }
+
     // HTTP options
 
     /// Set an optional timeout for idle sockets being kept-alive.
@@ -418,6 +551,36 @@ impl ClientBuilder {
         self.with_inner(move |inner| inner.pool_max_idle_per_host(max))
     }
 
+    /// Sets the longest a connection is kept in the pool.
+    ///
+    /// A connection older than this is closed once it becomes idle, instead
+    /// of being reused. Default is no limit. Does not apply to HTTP/2
+    /// connections.
+    pub fn pool_max_connection_lifetime(self, lifetime: Duration) -> ClientBuilder {
+        self.with_inner(move |inner| inner.pool_max_connection_lifetime(lifetime))
+    }
+
+    /// Sets the maximum number of connections open to each host at once.
+    ///
+    /// Beyond it, requests needing a new connection wait for one to become
+    /// idle or close, up to the connect timeout, or 30 seconds without one.
+    /// Default is no limit.
+    ///
+    /// See [`async ClientBuilder::max_connections_per_host`](crate::ClientBuilder::max_connections_per_host).
+    pub fn max_connections_per_host(self, max: usize) -> ClientBuilder {
+        self.with_inner(move |inner| inner.max_connections_per_host(max))
+    }
+
+    /// Sets the maximum number of connections open at once, to all hosts.
+    ///
+    /// Idle connections count against the limit, but are closed for
+    /// requests to other hosts waiting for a slot. Default is no limit.
+    ///
+    /// See [`async ClientBuilder::max_connections`](crate::ClientBuilder::max_connections).
+    pub fn max_connections(self, max: usize) -> ClientBuilder {
+        self.with_inner(move |inner| inner.max_connections(max))
+    }
+
     /// Send headers as title case instead of lowercase.
     pub fn http1_title_case_headers(self) -> ClientBuilder {
         self.with_inner(|inner| inner.http1_title_case_headers())
@@ -432,6 +595,18 @@ impl ClientBuilder {
         self.with_inner(|inner| inner.http1_allow_obsolete_multiline_headers_in_responses(value))
     }
 
+    /// Set whether HTTP/1 connections will accept obsolete line folding for
+    /// header values.
+    ///
+    /// Obsolete line folding was deprecated by RFC 7230, so this is `false`
+    /// by default and must be opted into explicitly.
+    ///
+    /// This is a shorthand for
+    /// [`ClientBuilder::http1_allow_obsolete_multiline_headers_in_responses`].
+    pub fn http1_allow_obsolete_multiline_headers(self, value: bool) -> ClientBuilder {
+        self.with_inner(|inner| inner.http1_allow_obsolete_multiline_headers(value))
+    }
+
     /// Sets whether invalid header lines should be silently ignored in HTTP/1 responses.
     pub fn http1_ignore_invalid_headers_in_responses(self, value: bool) -> ClientBuilder {
         self.with_inner(|inner| inner.http1_ignore_invalid_headers_in_responses(value))
@@ -456,6 +631,17 @@ impl ClientBuilder {
         self.with_inner(|inner| inner.http09_responses())
     }
 
+    /// Set whether HTTP/0.9 responses are accepted.
+    ///
+    /// When enabled, a response that doesn't start with a status line is
+    /// treated as the body of a `200 OK` response, and
+    /// `Response::version()` returns `HTTP/0.9`.
+    ///
+    /// Default is `false`.
+    pub fn accept_http_0_9(self, enabled: bool) -> ClientBuilder {
+        self.with_inner(|inner| inner.accept_http_0_9(enabled))
+    }
+
     /// Only use HTTP/2.
     #[cfg(feature = "http2")]
     #[cfg_attr(docsrs, doc(cfg(feature = "http2")))]
@@ -494,12 +680,26 @@ impl ClientBuilder {
     /// Sets the maximum frame size to use for HTTP2.
     ///
     /// Default is currently 16,384 but may change internally to optimize for common uses.
+    ///
+    /// The size must be between 16,384 and 16,777,215 (RFC 7540, Section 6.5.2),
+    /// otherwise `ClientBuilder::build` will return an error.
     #[cfg(feature = "http2")]
     #[cfg_attr(docsrs, doc(cfg(feature = "http2")))]
     pub fn http2_max_frame_size(self, sz: impl Into<Option<u32>>) -> ClientBuilder {
         self.with_inner(|inner| inner.http2_max_frame_size(sz))
     }
 
+    /// Sets a delay before retrying a request whose HTTP2 stream was reset
+    /// by the server with `REFUSED_STREAM`, plus a random extra delay of up
+    /// to `jitter_fraction` times `delay`.
+    ///
+    /// Default is no delay.
+    #[cfg(feature = "http2")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "http2")))]
+    pub fn http2_rst_error_delay(self, delay: Duration, jitter_fraction: f64) -> ClientBuilder {
+        self.with_inner(|inner| inner.http2_rst_error_delay(delay, jitter_fraction))
+    }
+
     /// This requires the optional `http3` feature to be
     /// enabled.
     #[cfg(feature = "http3")]
@@ -535,8 +735,19 @@ impl ClientBuilder {
         self.with_inner(move |inner| inner.local_address(addr))
     }
 
+    /// Bind to the local IP address given by an environment variable, read
+    /// when the client is built.
+    ///
+    /// This does nothing if the variable is unset or empty.
+    pub fn local_address_from_env(self, var: &str) -> ClientBuilder {
+        self.with_inner(move |inner| inner.local_address_from_env(var))
+    }
+
     /// Bind to an interface by `SO_BINDTODEVICE`.
     ///
+    /// On macOS and iOS, `IP_BOUND_IF` and `IPV6_BOUND_IF` are used
+    /// instead, with the index of the named interface.
+    ///
     /// # Example
     ///
     /// ```
@@ -545,11 +756,75 @@ impl ClientBuilder {
     ///     .interface(interface)
     ///     .build().unwrap();
     /// ```
-    #[cfg(any(target_os = "android", target_os = "fuchsia", target_os = "linux"))]
+    #[cfg(any(
+        target_os = "android",
+        target_os = "fuchsia",
+        target_os = "ios",
+        target_os = "linux",
+        target_os = "macos",
+        target_os = "tvos",
+        target_os = "watchos",
+    ))]
     pub fn interface(self, interface: &str) -> ClientBuilder {
         self.with_inner(move |inner| inner.interface(interface))
     }
 
+    /// Bind to the interface named by an environment variable, read when the
+    /// client is built.
+    ///
+    /// This does nothing if the variable is unset or empty.
+    #[cfg(any(
+        target_os = "android",
+        target_os = "fuchsia",
+        target_os = "ios",
+        target_os = "linux",
+        target_os = "macos",
+        target_os = "tvos",
+        target_os = "watchos",
+    ))]
+    pub fn local_interface_from_env(self, var: &str) -> ClientBuilder {
+        self.with_inner(move |inner| inner.local_interface_from_env(var))
+    }
+
+    /// Pick the interface to bind to from the interfaces that are currently up,
+    /// reading the list again in the background at most once per
+    /// `refresh_interval`.
+    ///
+    /// Building the client fails if no
+    /// [`preferred_interface_pattern`](ClientBuilder::preferred_interface_pattern)
+    /// is set.
+    #[cfg(any(target_os = "android", target_os = "linux"))]
+    pub fn interface_auto_detect(self, refresh_interval: Duration) -> ClientBuilder {
+        self.with_inner(move |inner| inner.interface_auto_detect(refresh_interval))
+    }
+
+    /// Set the pattern of interface names to pick from when
+    /// [`interface_auto_detect`](ClientBuilder::interface_auto_detect) is enabled.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use std::time::Duration;
+    ///
+    /// let client = reqwest::blocking::Client::builder()
+    ///     .interface_auto_detect(Duration::from_secs(30))
+    ///     .preferred_interface_pattern("wg*")
+    ///     .build().unwrap();
+    /// ```
+    #[cfg(any(target_os = "android", target_os = "linux"))]
+    pub fn preferred_interface_pattern(self, pattern: &str) -> ClientBuilder {
+        self.with_inner(move |inner| inner.preferred_interface_pattern(pattern))
+    }
+
+    /// Set the `SO_MARK` option on outgoing TCP sockets, for policy routing
+    /// and netfilter rules to match on.
+    ///
+    /// See [`async ClientBuilder::socket_mark`](crate::ClientBuilder::socket_mark).
+    #[cfg(any(target_os = "android", target_os = "fuchsia", target_os = "linux"))]
+    pub fn socket_mark(self, mark: u32) -> ClientBuilder {
+        self.with_inner(move |inner| inner.socket_mark(mark))
+    }
+
     /// Set that all sockets have `SO_KEEPALIVE` set with the supplied duration.
     ///
     /// If `None`, the option will not be set.
@@ -560,6 +835,32 @@ impl ClientBuilder {
         self.with_inner(move |inner| inner.tcp_keepalive(val))
     }
 
+    /// Set the `SO_SNDBUF` option on outgoing TCP sockets.
+    ///
+    /// See [`async ClientBuilder::tcp_send_buffer_size`](crate::ClientBuilder::tcp_send_buffer_size).
+    pub fn tcp_send_buffer_size(self, size: usize) -> ClientBuilder {
+        self.with_inner(move |inner| inner.tcp_send_buffer_size(size))
+    }
+
+    /// Set the `SO_RCVBUF` option on outgoing TCP sockets.
+    ///
+    /// See [`async ClientBuilder::tcp_recv_buffer_size`](crate::ClientBuilder::tcp_recv_buffer_size).
+    pub fn tcp_recv_buffer_size(self, size: usize) -> ClientBuilder {
+        self.with_inner(move |inner| inner.tcp_recv_buffer_size(size))
+    }
+
+    /// Fall back to IPv4 when connecting over IPv6 fails or stalls.
+    ///
+    /// When a host resolves to IPv6 addresses, an IPv4 connection attempt
+    /// is started if the IPv6 one hasn't completed within 200ms, or
+    /// immediately if it fails. Only the IPv4 addresses returned by the
+    /// resolver of the client are tried.
+    ///
+    /// Default is `false`.
+    pub fn fallback_to_ipv4_on_ipv6_failure(self, enabled: bool) -> ClientBuilder {
+        self.with_inner(|inner| inner.fallback_to_ipv4_on_ipv6_failure(enabled))
+    }
+
     // TLS options
 
     /// Add a custom root certificate.
@@ -606,6 +907,35 @@ impl ClientBuilder {
         self.with_inner(move |inner| inner.add_root_certificate(cert))
     }
 
+    /// Add a certificate revocation list, to fail connections to servers
+    /// whose certificate, or that of an issuer, it revokes.
+    ///
+    /// Certificates that no list was given for are accepted. Revocation
+    /// lists can't be used along with `danger_accept_invalid_hostnames` or
+    /// `dangerous_custom_verifier`.
+    ///
+    /// # Optional
+    ///
+    /// This requires the `rustls-tls(-...)` Cargo feature enabled, and the
+    /// `rustls` backend to be used.
+    #[cfg(feature = "__rustls")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "rustls-tls")))]
+    pub fn add_crl(self, crl: tls::CertificateRevocationList) -> ClientBuilder {
+        self.with_inner(move |inner| inner.add_crl(crl))
+    }
+
+    /// Add several certificate revocation lists.
+    ///
+    /// See `add_crl` for details.
+    #[cfg(feature = "__rustls")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "rustls-tls")))]
+    pub fn add_crls(
+        self,
+        crls: impl IntoIterator<Item = tls::CertificateRevocationList>,
+    ) -> ClientBuilder {
+        self.with_inner(move |inner| inner.add_crls(crls))
+    }
+
     /// Controls the use of built-in system certificates during certificate validation.
     ///
     /// Defaults to `true` -- built-in system certs will be used.
@@ -657,6 +987,23 @@ impl ClientBuilder {
         self.with_inner(move |inner| inner.identity(identity))
     }
 
+    /// Sets the identity to be used for client certificate authentication
+    /// with servers whose host matches `host_pattern`, in place of the one
+    /// set with [`identity`](ClientBuilder::identity).
+    ///
+    /// The pattern may contain `*` wildcards, like `"*.internal.example"`.
+    /// When several patterns match, the one added first wins.
+    ///
+    /// # Optional
+    ///
+    /// This requires the optional `native-tls` or `rustls-tls(-...)` feature to be
+    /// enabled.
+    #[cfg(any(feature = "native-tls", feature = "__rustls"))]
+    #[cfg_attr(docsrs, doc(cfg(any(feature = "native-tls", feature = "rustls-tls"))))]
+    pub fn identity_for(self, host_pattern: &str, identity: Identity) -> ClientBuilder {
+        self.with_inner(move |inner| inner.identity_for(host_pattern, identity))
+    }
+
     /// Controls the use of hostname verification.
     ///
     /// Defaults to `false`.
@@ -709,6 +1056,31 @@ impl ClientBuilder {
         self.with_inner(|inner| inner.danger_accept_invalid_certs(accept_invalid_certs))
     }
 
+    /// Leaves the decision to trust a server certificate to `verify`, which
+    /// is called with the DER encoded certificate chain, leaf first, and the
+    /// host connected to.
+    ///
+    /// # Warning
+    ///
+    /// `verify` replaces the usual checks of certificates and host names.
+    /// See `ClientBuilder::dangerous_custom_verifier` of the async client
+    /// for details.
+    #[cfg(feature = "__tls")]
+    #[cfg_attr(
+        docsrs,
+        doc(cfg(any(
+            feature = "default-tls",
+            feature = "native-tls",
+            feature = "rustls-tls"
+        )))
+    )]
+    pub fn dangerous_custom_verifier<F>(self, verify: F) -> ClientBuilder
+    where
+        F: Fn(&[&[u8]], &str) -> Result<(), BoxError> + Send + Sync + 'static,
+    {
+        self.with_inner(move |inner| inner.dangerous_custom_verifier(verify))
+    }
+
     /// Controls the use of TLS server name indication.
     ///
     /// Defaults to `true`.
@@ -828,6 +1200,148 @@ impl ClientBuilder {
         self.with_inner(|inner| inner.tls_info(tls_info))
     }
 
+    /// Logs the secrets of TLS sessions to the file named by the
+    /// `SSLKEYLOGFILE` environment variable, if it is set.
+    ///
+    /// # Optional
+    ///
+    /// This requires the `rustls-tls(-...)` Cargo feature enabled, and the
+    /// `rustls` backend to be used.
+    #[cfg(feature = "__rustls")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "rustls-tls")))]
+    pub fn tls_key_log_file(self) -> ClientBuilder {
+        self.with_inner(|inner| inner.tls_key_log_file())
+    }
+
+    /// Calls `log` with the secrets of TLS sessions, as the NSS key log
+    /// label, the client random of the session, and the secret.
+    ///
+    /// # Optional
+    ///
+    /// This requires the `rustls-tls(-...)` Cargo feature enabled, and the
+    /// `rustls` backend to be used.
+    #[cfg(feature = "__rustls")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "rustls-tls")))]
+    pub fn tls_key_log<F>(self, log: F) -> ClientBuilder
+    where
+        F: Fn(&str, &[u8], &[u8]) + Send + Sync + 'static,
+    {
+        self.with_inner(move |inner| inner.tls_key_log(log))
+    }
+
+    /// Controls whether TLS 1.2 sessions may be resumed with session
+    /// tickets, rather than only with session ids.
+    ///
+    /// Defaults to `true`.
+    ///
+    /// # Optional
+    ///
+    /// This requires the `rustls-tls(-...)` Cargo feature enabled, and the
+    /// `rustls` backend to be used.
+    #[cfg(feature = "__rustls")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "rustls-tls")))]
+    pub fn tls_session_tickets(self, enabled: bool) -> ClientBuilder {
+        self.with_inner(|inner| inner.tls_session_tickets(enabled))
+    }
+
+    /// Sets how many TLS sessions the client keeps to resume, or disables
+    /// resumption if `size` is 0.
+    ///
+    /// Defaults to 256.
+    ///
+    /// # Optional
+    ///
+    /// This requires the `rustls-tls(-...)` Cargo feature enabled, and the
+    /// `rustls` backend to be used.
+    #[cfg(feature = "__rustls")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "rustls-tls")))]
+    pub fn tls_session_cache_size(self, size: usize) -> ClientBuilder {
+        self.with_inner(|inner| inner.tls_session_cache_size(size))
+    }
+
+    /// Keeps the TLS sessions to resume in `cache`, which may be shared with
+    /// other clients.
+    ///
+    /// # Optional
+    ///
+    /// This requires the `rustls-tls(-...)` Cargo feature enabled, and the
+    /// `rustls` backend to be used.
+    #[cfg(feature = "__rustls")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "rustls-tls")))]
+    pub fn tls_session_cache(self, cache: tls::SessionCache) -> ClientBuilder {
+        self.with_inner(|inner| inner.tls_session_cache(cache))
+    }
+
+    /// Requires servers to staple an OCSP response to their certificate,
+    /// and fails connections to those whose response says it was revoked.
+    ///
+    /// Defaults to `false`.
+    ///
+    /// # Optional
+    ///
+    /// This requires the `rustls-tls(-...)` Cargo feature enabled, and the
+    /// `rustls` backend to be used.
+    #[cfg(feature = "__rustls")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "rustls-tls")))]
+    pub fn tls_ocsp_stapling_required(self, required: bool) -> ClientBuilder {
+        self.with_inner(|inner| inner.tls_ocsp_stapling_required(required))
+    }
+
+    /// Requires the certificates of servers that chain to a built-in root
+    /// certificate to carry signed certificate timestamps (SCTs) from these
+    /// Certificate Transparency logs.
+    ///
+    /// # Optional
+    ///
+    /// This requires the `rustls-tls(-...)` Cargo feature enabled, and the
+    /// `rustls` backend to be used.
+    #[cfg(feature = "__rustls")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "rustls-tls")))]
+    pub fn tls_ct_logs(self, logs: impl IntoIterator<Item = tls::CtLog>) -> ClientBuilder {
+        self.with_inner(move |inner| inner.tls_ct_logs(logs))
+    }
+
+    /// Sets how many of the logs given to `tls_ct_logs` must have signed an
+    /// SCT of a server certificate.
+    ///
+    /// Defaults to 2.
+    ///
+    /// # Optional
+    ///
+    /// This requires the `rustls-tls(-...)` Cargo feature enabled.
+    #[cfg(feature = "__rustls")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "rustls-tls")))]
+    pub fn tls_ct_min_scts(self, min: usize) -> ClientBuilder {
+        self.with_inner(|inner| inner.tls_ct_min_scts(min))
+    }
+
+    /// Log a warning when a server certificate expires within `days` days.
+    ///
+    /// # Optional
+    ///
+    /// This requires the optional `cert-expiry-check` feature, and one of the
+    /// `default-tls`, `native-tls`, or `rustls-tls(-...)` features, to be
+    /// enabled.
+    #[cfg(all(feature = "__tls", feature = "cert-expiry-check"))]
+    #[cfg_attr(docsrs, doc(cfg(feature = "cert-expiry-check")))]
+    pub fn ssl_expiry_warning_days(self, days: u64) -> ClientBuilder {
+        self.with_inner(|inner| inner.ssl_expiry_warning_days(days))
+    }
+
+    /// Fail connecting to servers whose certificate expires within `days`
+    /// days.
+    ///
+    /// # Optional
+    ///
+    /// This requires the optional `cert-expiry-check` feature, and one of the
+    /// `default-tls`, `native-tls`, or `rustls-tls(-...)` features, to be
+    /// enabled.
+    #[cfg(all(feature = "__tls", feature = "cert-expiry-check"))]
+    #[cfg_attr(docsrs, doc(cfg(feature = "cert-expiry-check")))]
+    pub fn ssl_expiry_fail_days(self, days: u64) -> ClientBuilder {
+        self.with_inner(|inner| inner.ssl_expiry_fail_days(days))
+    }
+
     /// Use a preconfigured TLS backend.
     ///
     /// If the passed `Any` argument is not a TLS backend that reqwest
@@ -907,6 +1421,11 @@ impl ClientBuilder {
 
     /// Override DNS resolution for specific domains to a particular IP address.
     ///
+    /// `domain` may be a wildcard such as `*.service.consul`, which matches
+    /// every subdomain of `service.consul`, but not `service.consul` itself.
+    /// Exact domains take precedence over wildcards, and longer wildcards
+    /// over shorter ones.
+    ///
     /// Warning
     ///
     /// Since the DNS protocol has no notion of ports, if you wish to send
@@ -919,6 +1438,10 @@ impl ClientBuilder {
 
     /// Override DNS resolution for specific domains to particular IP addresses.
     ///
+    /// The addresses are tried in turn, so later ones serve as fallbacks
+    /// when connecting to earlier ones fails. `domain` may be a wildcard, as
+    /// with `resolve`.
+    ///
     /// Warning
     ///
     /// Since the DNS protocol has no notion of ports, if you wish to send
@@ -929,6 +1452,63 @@ impl ClientBuilder {
         self.with_inner(|inner| inner.resolve_to_addrs(domain, addrs))
     }
 
+    /// Look up names in the hosts file at `path` before using DNS, like the
+    /// system does with `/etc/hosts`.
+    ///
+    /// Overrides passed to `resolve` and `resolve_to_addrs` take precedence
+    /// over the file, which is read once by `ClientBuilder::build`.
+    pub fn hosts_file<P: AsRef<Path>>(self, path: P) -> ClientBuilder {
+        self.with_inner(|inner| inner.hosts_file(path))
+    }
+
+    /// Open connections with `connector` instead of over TCP.
+    ///
+    /// `connector` is called with the URI of each new connection's
+    /// destination, and returns the stream to send requests over. Proxies
+    /// are not used by a client with its own transport.
+    pub fn connector<S>(self, connector: S) -> ClientBuilder
+    where
+        S: tower_service::Service<http::Uri> + Clone + Send + Sync + 'static,
+        S::Response: CustomProxyStream,
+        S::Error: Into<BoxError>,
+        S::Future: Send + 'static,
+    {
+        self.with_inner(move |inner| inner.connector(connector))
+    }
+
+    /// Wraps the connector of the client in a [`tower::Layer`].
+    ///
+    /// The layer sees every connection being established, and must hand the
+    /// connection of the inner connector back unchanged. Each call wraps the
+    /// layers added before.
+    pub fn connector_layer<L>(self, layer: L) -> ClientBuilder
+    where
+        L: tower::Layer<crate::connect::BoxedConnectorService> + Send + Sync + 'static,
+        L::Service: tower_service::Service<http::Uri, Response = crate::connect::Conn>
+            + Clone
+            + Send
+            + Sync
+            + 'static,
+        <L::Service as tower_service::Service<http::Uri>>::Error: Into<BoxError>,
+        <L::Service as tower_service::Service<http::Uri>>::Future: Send + 'static,
+    {
+        self.with_inner(move |inner| inner.connector_layer(layer))
+    }
+
+    /// Connect to `host` through the Unix domain socket at `path`, instead
+    /// of over TCP.
+    ///
+    /// Requests to other hosts are not affected, and the port in the URL is
+    /// ignored.
+    #[cfg(unix)]
+    pub fn connect_host_via_unix_socket<P: AsRef<std::path::Path>>(
+        self,
+        host: &str,
+        path: P,
+    ) -> ClientBuilder {
+        self.with_inner(|inner| inner.connect_host_via_unix_socket(host, path))
+    }
+
     /// Override the DNS resolver implementation.
     ///
     /// Pass an `Arc` wrapping a trait object implementing `Resolve`.
@@ -938,6 +1518,75 @@ impl ClientBuilder {
         self.with_inner(|inner| inner.dns_resolver(resolver))
     }
 
+    /// Resolve names with a DNS-over-TLS server instead of the system or
+    /// hickory-dns resolver.
+    ///
+    /// # Optional
+    ///
+    /// This requires the optional `dns-over-tls` feature to be enabled.
+    #[cfg(feature = "dns-over-tls")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "dns-over-tls")))]
+    pub fn dns_over_tls(self, upstream: SocketAddr, server_name: &str) -> ClientBuilder {
+        self.with_inner(|inner| inner.dns_over_tls(upstream, server_name))
+    }
+
+    /// Cache the answers of the DNS resolver in the client, shared with its
+    /// clones.
+    ///
+    /// Default is `false`.
+    pub fn dns_cache(self, enable: bool) -> ClientBuilder {
+        self.with_inner(|inner| inner.dns_cache(enable))
+    }
+
+    /// Set the shortest time answers are kept in the DNS cache.
+    ///
+    /// Default is zero.
+    pub fn dns_cache_min_ttl(self, ttl: Duration) -> ClientBuilder {
+        self.with_inner(|inner| inner.dns_cache_min_ttl(ttl))
+    }
+
+    /// Set the longest time answers are kept in the DNS cache, and how long
+    /// answers without a TTL are kept.
+    ///
+    /// Default is 60 seconds.
+    pub fn dns_cache_max_ttl(self, ttl: Duration) -> ClientBuilder {
+        self.with_inner(|inner| inner.dns_cache_max_ttl(ttl))
+    }
+
+    /// Set how long answers that a name doesn't exist or has no addresses
+    /// are kept in the DNS cache.
+    ///
+    /// Default is 5 seconds.
+    pub fn dns_cache_negative_ttl(self, ttl: Duration) -> ClientBuilder {
+        self.with_inner(|inner| inner.dns_cache_negative_ttl(ttl))
+    }
+
+    /// Call `callback` after each DNS lookup made by the client.
+    ///
+    /// Names overridden with `resolve` and `resolve_to_addrs` aren't
+    /// reported.
+    pub fn on_dns_lookup<F>(self, callback: F) -> ClientBuilder
+    where
+        F: Fn(&DnsLookup<'_>) + Send + Sync + 'static,
+    {
+        self.with_inner(move |inner| inner.on_dns_lookup(callback))
+    }
+
+    /// Log every DNS lookup made by the client.
+    ///
+    /// Default is `false`.
+    pub fn dns_resolver_debug_log(self, enable: bool) -> ClientBuilder {
+        self.with_inner(|inner| inner.dns_resolver_debug_log(enable))
+    }
+
+    /// Log a hash of hostnames instead of the names themselves, when DNS
+    /// lookups are logged with `dns_resolver_debug_log`.
+    ///
+    /// Default is `false`.
+    pub fn dns_log_hash_hostnames(self, enable: bool) -> ClientBuilder {
+        self.with_inner(|inner| inner.dns_log_hash_hostnames(enable))
+    }
+
     // private
 
     fn with_inner<F>(mut self, func: F) -> ClientBuilder
@@ -1,5 +1,6 @@
 use std::convert::TryFrom;
 use std::fmt;
+use std::net::IpAddr;
 use std::time::Duration;
 
 use http::{request::Parts, Request as HttpRequest, Version};
@@ -12,8 +13,10 @@ use super::body::{self, Body};
 #[cfg(feature = "multipart")]
 use super::multipart;
 use super::Client;
+#[cfg(feature = "graphql")]
+use crate::header::ACCEPT;
 use crate::header::{HeaderMap, HeaderName, HeaderValue, CONTENT_TYPE};
-use crate::{async_impl, Method, Url};
+use crate::{async_impl, redirect, Method, Url};
 
 /// A request which can be executed with `Client::execute()`.
 pub struct Request {
@@ -113,6 +116,18 @@ impl Request {
         self.inner.timeout_mut()
     }
 
+    /// Get the connect timeout.
+    #[inline]
+    pub fn connect_timeout(&self) -> Option<&Duration> {
+        self.inner.connect_timeout()
+    }
+
+    /// Get a mutable reference to the connect timeout.
+    #[inline]
+    pub fn connect_timeout_mut(&mut self) -> &mut Option<Duration> {
+        self.inner.connect_timeout_mut()
+    }
+
     /// Attempts to clone the `Request`.
     ///
     /// None is returned if a body is which can not be cloned. This can be because the body is a
@@ -363,6 +378,144 @@ impl RequestBuilder {
         self
     }
 
+    /// Set a timeout for only the connect phase of this request.
+    ///
+    /// This includes connecting through a proxy, and overrides the timeout
+    /// configured using `ClientBuilder::connect_timeout()`. It has no effect
+    /// if the request is sent over an already established, pooled connection.
+    ///
+    /// It runs until the request gets a connection, so it also covers any
+    /// retried attempts, see `ClientBuilder::connect_retry`.
+    pub fn connect_timeout(mut self, timeout: Duration) -> RequestBuilder {
+        if let Ok(ref mut req) = self.request {
+            *req.connect_timeout_mut() = Some(timeout);
+        }
+        self
+    }
+
+    /// Bind the connections of this request to a local IP address.
+    ///
+    /// This overrides the address configured using
+    /// `ClientBuilder::local_address()`. Connections bound this way are
+    /// pooled apart from the client's other connections, and only reused by
+    /// requests bound the same way.
+    pub fn local_address(mut self, addr: IpAddr) -> RequestBuilder {
+        if let Ok(ref mut req) = self.request {
+            req.inner.set_local_address(addr);
+        }
+        self
+    }
+
+    /// Bind the connections of this request to a network interface, such
+    /// as `"eth1"`.
+    ///
+    /// This overrides the interface configured using
+    /// `ClientBuilder::interface()`. Connections bound this way are pooled
+    /// apart from the client's other connections, and only reused by
+    /// requests bound the same way.
+    #[cfg(any(
+        target_os = "android",
+        target_os = "fuchsia",
+        target_os = "ios",
+        target_os = "linux",
+        target_os = "macos",
+        target_os = "tvos",
+        target_os = "watchos",
+    ))]
+    pub fn interface(mut self, interface: &str) -> RequestBuilder {
+        if let Ok(ref mut req) = self.request {
+            req.inner.set_interface(interface);
+        }
+        self
+    }
+
+    /// Use `name` in place of the host of the URL in the TLS handshake of
+    /// this request.
+    ///
+    /// The server is asked for the certificate of `name` with SNI, and the
+    /// certificate it presents is verified against `name`, while the
+    /// connection still goes to the host of the URL. Connections made this
+    /// way are pooled apart from the client's other connections, and only
+    /// reused by requests using the same name.
+    ///
+    /// # Optional
+    ///
+    /// This requires the optional `default-tls`, `native-tls`, or `rustls-tls(-...)`
+    /// feature to be enabled.
+    #[cfg(feature = "__tls")]
+    #[cfg_attr(
+        docsrs,
+        doc(cfg(any(
+            feature = "default-tls",
+            feature = "native-tls",
+            feature = "rustls-tls"
+        )))
+    )]
+    pub fn sni(mut self, name: &str) -> RequestBuilder {
+        if let Ok(ref mut req) = self.request {
+            req.inner.set_tls_server_name(name);
+        }
+        self
+    }
+
+    /// Controls the use of certificate validation for this request only.
+    ///
+    /// Defaults to `false`, leaving validation to the client.
+    ///
+    /// # Warning
+    ///
+    /// If invalid certificates are trusted, *any* certificate for *any* site
+    /// will be trusted for use. See the async
+    /// `RequestBuilder::danger_accept_invalid_certs` for details.
+    ///
+    /// # Optional
+    ///
+    /// This requires the `danger-request-invalid-certs` Cargo feature, and
+    /// the optional `default-tls`, `native-tls`, or `rustls-tls(-...)`
+    /// feature to be enabled.
+    #[cfg(all(feature = "__tls", feature = "danger-request-invalid-certs"))]
+    #[cfg_attr(docsrs, doc(cfg(feature = "danger-request-invalid-certs")))]
+    pub fn danger_accept_invalid_certs(mut self, accept_invalid_certs: bool) -> RequestBuilder {
+        if let Ok(ref mut req) = self.request {
+            req.inner.set_accept_invalid_certs(accept_invalid_certs);
+        }
+        self
+    }
+
+    /// Send this request over the Unix domain socket at `path`.
+    ///
+    /// The URL must use the `http` scheme; its host and port are only used
+    /// for the `Host` header. The same can be expressed with an
+    /// `http+unix://` URL whose host is the percent-encoded socket path, such
+    /// as `http+unix://%2Fvar%2Frun%2Fdocker.sock/v1.41/containers/json`.
+    #[cfg(unix)]
+    pub fn unix_socket<P: AsRef<std::path::Path>>(mut self, path: P) -> RequestBuilder {
+        if let Ok(ref mut req) = self.request {
+            req.inner.set_unix_socket(path.as_ref().to_path_buf());
+        }
+        self
+    }
+
+    /// Set a redirect `Policy` for only this request.
+    ///
+    /// This overrides the policy configured using `ClientBuilder::redirect()`.
+    pub fn redirect_policy(mut self, policy: redirect::Policy) -> RequestBuilder {
+        if let Ok(ref mut req) = self.request {
+            req.inner.set_redirect_policy(policy);
+        }
+        self
+    }
+
+    /// Follow at most `max` redirects for this request.
+    pub fn max_redirects(self, max: u32) -> RequestBuilder {
+        self.redirect_policy(redirect::Policy::limited(max as usize))
+    }
+
+    /// Don't follow redirects for this request.
+    pub fn no_redirect(self) -> RequestBuilder {
+        self.redirect_policy(redirect::Policy::none())
+    }
+
     /// Modify the query string of the URL.
     ///
     /// Modifies the URL of this request, adding the parameters provided.
@@ -469,6 +622,43 @@ impl RequestBuilder {
         self
     }
 
+    /// Send a form body made of the given fields.
+    ///
+    /// Like [`RequestBuilder::form`], this sets the body to the url encoded
+    /// fields and the `Content-Type: application/x-www-form-urlencoded`
+    /// header, but takes the fields from an iterator of pairs instead of a
+    /// `Serialize` value.
+    ///
+    /// ```rust
+    /// # use reqwest::Error;
+    /// #
+    /// # fn run() -> Result<(), Error> {
+    /// let client = reqwest::blocking::Client::new();
+    /// let res = client.post("http://httpbin.org")
+    ///     .urlencoded_fields([("lang", "rust"), ("edition", "2021")])
+    ///     .send()?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn urlencoded_fields<I, K, V>(mut self, fields: I) -> RequestBuilder
+    where
+        I: IntoIterator<Item = (K, V)>,
+        K: AsRef<str>,
+        V: AsRef<str>,
+    {
+        if let Ok(ref mut req) = self.request {
+            let body = url::form_urlencoded::Serializer::new(String::new())
+                .extend_pairs(fields)
+                .finish();
+            req.headers_mut().insert(
+                CONTENT_TYPE,
+                HeaderValue::from_static("application/x-www-form-urlencoded"),
+            );
+            *req.body_mut() = Some(body.into());
+        }
+        self
+    }
+
     /// Send a JSON body.
     ///
     /// Sets the body to the JSON serialization of the passed value, and
@@ -522,6 +712,83 @@ impl RequestBuilder {
         self
     }
 
+    /// Send a GraphQL query with variables.
+    ///
+    /// The body is a JSON object of the form
+    /// `{"query": "...", "variables": {...}}`, and the `Content-Type` and
+    /// `Accept` headers are set to `application/json`.
+    ///
+    /// ```rust
+    /// # use reqwest::Error;
+    /// # use std::collections::HashMap;
+    /// #
+    /// # fn run() -> Result<(), Error> {
+    /// let mut variables = HashMap::new();
+    /// variables.insert("login", "octocat");
+    ///
+    /// let client = reqwest::blocking::Client::new();
+    /// let res = client.post("https://api.github.com/graphql")
+    ///     .graphql("query($login: String!) { user(login: $login) { name } }", &variables)
+    ///     .send()?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// # Optional
+    ///
+    /// This requires the optional `graphql` feature enabled.
+    ///
+    /// # Errors
+    ///
+    /// Serialization can fail if `T`'s implementation of `Serialize` decides to
+    /// fail, or if `T` contains a map with non-string keys.
+    #[cfg(feature = "graphql")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "graphql")))]
+    pub fn graphql<T: Serialize + ?Sized>(mut self, query: &str, variables: &T) -> RequestBuilder {
+        match serde_json::to_value(variables) {
+            Ok(variables) => self.graphql_body(query, Some(variables)),
+            Err(err) => {
+                if self.request.is_ok() {
+                    self.request = Err(crate::error::builder(err));
+                }
+                self
+            }
+        }
+    }
+
+    /// Send a GraphQL query without variables.
+    ///
+    /// Same as [`RequestBuilder::graphql`], but the `variables` key is omitted
+    /// from the body.
+    ///
+    /// # Optional
+    ///
+    /// This requires the optional `graphql` feature enabled.
+    #[cfg(feature = "graphql")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "graphql")))]
+    pub fn graphql_no_vars(self, query: &str) -> RequestBuilder {
+        self.graphql_body(query, None)
+    }
+
+    #[cfg(feature = "graphql")]
+    fn graphql_body(mut self, query: &str, variables: Option<serde_json::Value>) -> RequestBuilder {
+        if let Ok(ref mut req) = self.request {
+            let mut body = serde_json::Map::new();
+            body.insert("query".to_owned(), query.into());
+            if let Some(variables) = variables {
+                body.insert("variables".to_owned(), variables);
+            }
+            // Serializing a `serde_json::Value` cannot fail.
+            let body = serde_json::to_vec(&body).expect("serialize graphql body");
+            req.headers_mut()
+                .insert(CONTENT_TYPE, HeaderValue::from_static("application/json"));
+            req.headers_mut()
+                .insert(ACCEPT, HeaderValue::from_static("application/json"));
+            *req.body_mut() = Some(body.into());
+        }
+        self
+    }
+
     /// Sends a multipart/form-data body.
     ///
     /// ```
@@ -557,6 +824,36 @@ impl RequestBuilder {
         builder
     }
 
+    /// Sends a multipart/form-data body made of the given text fields.
+    ///
+    /// This is a shorthand for building a [`multipart::Form`] with one
+    /// `text` part per field and passing it to [`RequestBuilder::multipart`].
+    ///
+    /// ```
+    /// # fn run() -> Result<(), Box<dyn std::error::Error>> {
+    /// let client = reqwest::blocking::Client::new();
+    /// let response = client.post("your url")
+    ///     .multipart_fields([("key3", "value3"), ("key4", "value4")])
+    ///     .send()?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[cfg(feature = "multipart")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "multipart")))]
+    pub fn multipart_fields<I, K, V>(self, fields: I) -> RequestBuilder
+    where
+        I: IntoIterator<Item = (K, V)>,
+        K: AsRef<str>,
+        V: AsRef<str>,
+    {
+        let form = fields
+            .into_iter()
+            .fold(multipart::Form::new(), |form, (name, value)| {
+                form.text(name.as_ref().to_owned(), value.as_ref().to_owned())
+            });
+        self.multipart(form)
+    }
+
     /// Build a `Request`, which can be inspected, modified and executed with
     /// `Client::execute()`.
     pub fn build(self) -> crate::Result<Request> {
@@ -909,6 +1206,26 @@ mod tests {
         assert_eq!(buf, body_should_be);
     }
 
+    #[test]
+    fn add_urlencoded_fields() {
+        let client = Client::new();
+        let some_url = "https://google.com/";
+        let r = client.post(some_url);
+
+        let mut r = r
+            .urlencoded_fields([("foo", "bar"), ("baz", "a b&c")])
+            .build()
+            .unwrap();
+
+        assert_eq!(
+            r.headers().get(CONTENT_TYPE).unwrap(),
+            &"application/x-www-form-urlencoded"
+        );
+
+        let buf = body::read_to_string(r.body_mut().take().unwrap()).unwrap();
+        assert_eq!(buf, "foo=bar&baz=a+b%26c");
+    }
+
     #[test]
     #[cfg(feature = "json")]
     fn add_json() {
@@ -955,6 +1272,48 @@ mod tests {
         assert!(err.source().unwrap().is::<serde_json::Error>());
     }
 
+    #[test]
+    #[cfg(feature = "graphql")]
+    fn add_graphql() {
+        let client = Client::new();
+        let some_url = "https://google.com/";
+        let r = client.post(some_url);
+
+        let mut variables = HashMap::new();
+        variables.insert("id", 1);
+
+        let mut r = r
+            .graphql("query($id: Int) { user(id: $id) { name } }", &variables)
+            .build()
+            .unwrap();
+
+        assert_eq!(r.headers().get(CONTENT_TYPE).unwrap(), &"application/json");
+        assert_eq!(r.headers().get(ACCEPT).unwrap(), &"application/json");
+
+        let buf = body::read_to_string(r.body_mut().take().unwrap()).unwrap();
+        let body: serde_json::Value = serde_json::from_str(&buf).unwrap();
+        assert_eq!(
+            body,
+            serde_json::json!({
+                "query": "query($id: Int) { user(id: $id) { name } }",
+                "variables": { "id": 1 },
+            })
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "graphql")]
+    fn add_graphql_no_vars() {
+        let client = Client::new();
+        let some_url = "https://google.com/";
+        let r = client.post(some_url);
+
+        let mut r = r.graphql_no_vars("{ viewer { login } }").build().unwrap();
+
+        let buf = body::read_to_string(r.body_mut().take().unwrap()).unwrap();
+        assert_eq!(buf, r#"{"query":"{ viewer { login } }"}"#);
+    }
+
     #[test]
     fn test_replace_headers() {
         use http::HeaderMap;
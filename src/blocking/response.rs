@@ -178,6 +178,17 @@ impl Response {
         self.inner.remote_addr()
     }
 
+    /// Get the proxy of the connection this `Response` came over, or `None`
+    /// if it went direct.
+    pub fn proxy_info(&self) -> Option<&crate::ProxyInfo> {
+        self.inner.proxy_info()
+    }
+
+    /// Get the details of the connection this `Response` came over.
+    pub fn connection_info(&self) -> Option<&crate::ConnectionInfo> {
+        self.inner.connection_info()
+    }
+
     /// Returns a reference to the associated extensions.
     pub fn extensions(&self) -> &http::Extensions {
         self.inner.extensions()
@@ -353,6 +364,27 @@ impl Response {
         io::copy(self, w).map_err(crate::error::decode_io)
     }
 
+    /// Read the rest of the response body and discard it.
+    ///
+    /// Unlike [`Response::bytes`], this doesn't buffer the body. Reading the
+    /// body to its end allows the connection to be returned to the pool.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # fn run() -> Result<(), Box<dyn std::error::Error>> {
+    /// let res = reqwest::blocking::get("http://httpbin.org/ip")?;
+    /// let status = res.status();
+    /// res.consume()?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn consume(mut self) -> crate::Result<()> {
+        io::copy(&mut self, &mut io::sink())
+            .map(|_| ())
+            .map_err(crate::error::decode_io)
+    }
+
     /// Turn a response into an error if the server returned an error.
     ///
     /// # Example
@@ -404,6 +436,24 @@ impl Response {
         self.inner.error_for_status_ref().and_then(|_| Ok(self))
     }
 
+    /// Check that the response has the expected `Content-Type`.
+    ///
+    /// Only the media type is compared, so parameters such as
+    /// `charset=utf-8` are ignored.
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// # fn run() -> Result<(), Box<dyn std::error::Error>> {
+    /// let res = reqwest::blocking::get("http://httpbin.org/json")?;
+    /// res.assert_content_type("application/json")?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn assert_content_type(&self, expected: &str) -> crate::Result<&Self> {
+        self.inner.assert_content_type(expected).and(Ok(self))
+    }
+
     // private
 
     fn body_mut(&mut self) -> Pin<&mut dyn futures_util::io::AsyncRead> {
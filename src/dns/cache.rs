@@ -0,0 +1,301 @@
+//! Caching of DNS lookups
+
+use std::collections::HashMap;
+use std::error::Error as StdError;
+use std::fmt;
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use super::{Addrs, Name, Resolve, ResolveWithTtl, Resolving};
+use crate::error::BoxError;
+
+/// Past this many names, expired entries are dropped before adding more.
+const PURGE_THRESHOLD: usize = 1024;
+
+/// How long answers are kept at most, however long their TTL, so that a TTL
+/// of `Duration::MAX` caches them for good without overflowing `Instant`.
+const FOREVER: Duration = Duration::from_secs(60 * 60 * 24 * 365 * 30);
+
+/// Keeps the answers of a resolver, successful or not, for a while.
+///
+/// Answers are kept for their TTL, clamped between `min_ttl` and `max_ttl`.
+/// Answers without a TTL, such as those of `getaddrinfo`, are kept for
+/// `max_ttl`, and names found to have no addresses for `negative_ttl`. Other
+/// failures aren't kept, see `is_no_such_name`.
+#[derive(Clone)]
+pub(crate) struct DnsCache {
+    source: Source,
+    min_ttl: Duration,
+    max_ttl: Duration,
+    negative_ttl: Duration,
+    entries: Arc<Mutex<HashMap<String, CacheEntry>>>,
+}
+
+#[derive(Clone)]
+enum Source {
+    Plain(Arc<dyn Resolve>),
+    WithTtl(Arc<dyn ResolveWithTtl>),
+}
+
+struct CacheEntry {
+    expires: Instant,
+    result: Result<Vec<SocketAddr>, Arc<BoxError>>,
+}
+
+/// A failed lookup, answered again from the cache.
+#[derive(Debug)]
+struct CachedError(Arc<BoxError>);
+
+impl DnsCache {
+    pub(crate) fn new(
+        resolver: Arc<dyn Resolve>,
+        ttl_resolver: Option<Arc<dyn ResolveWithTtl>>,
+        min_ttl: Duration,
+        max_ttl: Duration,
+        negative_ttl: Duration,
+    ) -> Self {
+        let source = match ttl_resolver {
+            Some(resolver) => Source::WithTtl(resolver),
+            None => Source::Plain(resolver),
+        };
+        DnsCache {
+            source,
+            min_ttl,
+            max_ttl: max_ttl.max(min_ttl),
+            negative_ttl,
+            entries: Arc::new(Mutex::new(HashMap::new())),
+        }
+    }
+
+    /// Forgets every answer.
+    pub(crate) fn flush(&self) {
+        self.entries.lock().unwrap().clear();
+    }
+
+    fn get(&self, key: &str) -> Option<Result<Addrs, BoxError>> {
+        let entries = self.entries.lock().unwrap();
+        let entry = entries.get(key).filter(|e| e.expires > Instant::now())?;
+        Some(match entry.result {
+            Ok(ref addrs) => Ok(Box::new(addrs.clone().into_iter()) as Addrs),
+            Err(ref err) => Err(Box::new(CachedError(err.clone())) as BoxError),
+        })
+    }
+
+    fn insert(&self, key: String, ttl: Duration, result: Result<Vec<SocketAddr>, Arc<BoxError>>) {
+        if ttl.is_zero() {
+            return;
+        }
+        let now = Instant::now();
+        let mut entries = self.entries.lock().unwrap();
+        if entries.len() >= PURGE_THRESHOLD {
+            entries.retain(|_, entry| entry.expires > now);
+        }
+        entries.insert(
+            key,
+            CacheEntry {
+                expires: now + ttl.min(FOREVER),
+                result,
+            },
+        );
+    }
+}
+
+impl DnsCache {
+    /// Resolves `name`, and tells whether the answer came from the cache.
+    pub(crate) fn resolve_cached(&self, name: Name) -> (Resolving, bool) {
+        let key = name.as_str().to_ascii_lowercase();
+        if let Some(result) = self.get(&key) {
+            return (Box::pin(futures_util::future::ready(result)), true);
+        }
+
+        let cache = self.clone();
+        let resolving = Box::pin(async move {
+            let result = match cache.source {
+                Source::Plain(ref resolver) => resolver
+                    .resolve(name)
+                    .await
+                    .map(|addrs| (addrs.collect::<Vec<_>>(), None)),
+                Source::WithTtl(ref resolver) => resolver.resolve_with_ttl(name).await,
+            };
+            match result {
+                Ok((addrs, ttl)) => {
+                    let ttl = ttl
+                        .unwrap_or(cache.max_ttl)
+                        .clamp(cache.min_ttl, cache.max_ttl);
+                    cache.insert(key, ttl, Ok(addrs.clone()));
+                    Ok(Box::new(addrs.into_iter()) as Addrs)
+                }
+                Err(err) if is_no_such_name(&*err) => {
+                    let err = Arc::new(err);
+                    cache.insert(key, cache.negative_ttl, Err(err.clone()));
+                    Err(Box::new(CachedError(err)) as BoxError)
+                }
+                Err(err) => Err(err),
+            }
+        });
+        (resolving, false)
+    }
+}
+
+/// Whether `err` is an answer that the name doesn't exist or has no
+/// addresses, rather than a lookup that failed, such as a timeout or an
+/// unreachable server, and may work when tried again.
+fn is_no_such_name(err: &(dyn StdError + 'static)) -> bool {
+    let mut source = Some(err);
+    while let Some(err) = source {
+        if let Some(io) = err.downcast_ref::<std::io::Error>() {
+            return io.kind() == std::io::ErrorKind::NotFound;
+        }
+        #[cfg(feature = "hickory-dns")]
+        if let Some(err) = err.downcast_ref::<hickory_resolver::error::ResolveError>() {
+            return matches!(
+                err.kind(),
+                hickory_resolver::error::ResolveErrorKind::NoRecordsFound { .. }
+            );
+        }
+        source = err.source();
+    }
+    false
+}
+
+impl Resolve for DnsCache {
+    fn resolve(&self, name: Name) -> Resolving {
+        self.resolve_cached(name).0
+    }
+}
+
+impl fmt::Display for CachedError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(&self.0, f)
+    }
+}
+
+impl StdError for CachedError {
+    fn source(&self) -> Option<&(dyn StdError + 'static)> {
+        self.0.source()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    struct Counting {
+        lookups: AtomicUsize,
+        ttl: Option<Duration>,
+    }
+
+    impl Resolve for Counting {
+        fn resolve(&self, name: Name) -> Resolving {
+            self.lookups.fetch_add(1, Ordering::SeqCst);
+            let result = match name.as_str() {
+                "missing.test" => {
+                    Err(std::io::Error::new(std::io::ErrorKind::NotFound, "no such host").into())
+                }
+                "unreachable.test" => Err("no DNS server reachable".into()),
+                _ => Ok(Box::new(vec!["127.0.0.1:0".parse().unwrap()].into_iter()) as Addrs),
+            };
+            Box::pin(futures_util::future::ready(result))
+        }
+    }
+
+    impl ResolveWithTtl for Counting {
+        fn resolve_with_ttl(&self, name: Name) -> crate::dns::resolve::ResolvingWithTtl {
+            let ttl = self.ttl;
+            let resolving = self.resolve(name);
+            Box::pin(async move { Ok((resolving.await?.collect(), ttl)) })
+        }
+    }
+
+    fn counting(ttl: Option<Duration>) -> Arc<Counting> {
+        Arc::new(Counting {
+            lookups: AtomicUsize::new(0),
+            ttl,
+        })
+    }
+
+    async fn lookup(cache: &DnsCache, name: &str) -> Result<Vec<SocketAddr>, BoxError> {
+        Ok(cache.resolve(name.parse().unwrap()).await?.collect())
+    }
+
+    #[tokio::test]
+    async fn caches_answers_and_failures() {
+        let resolver = counting(None);
+        let cache = DnsCache::new(
+            resolver.clone(),
+            None,
+            Duration::ZERO,
+            Duration::from_secs(60),
+            Duration::from_secs(60),
+        );
+
+        for _ in 0..2 {
+            let addrs = lookup(&cache, "Example.test").await.unwrap();
+            assert_eq!(addrs, ["127.0.0.1:0".parse().unwrap()]);
+            let addrs = lookup(&cache, "example.test").await.unwrap();
+            assert_eq!(addrs, ["127.0.0.1:0".parse().unwrap()]);
+            let err = lookup(&cache, "missing.test").await.unwrap_err();
+            assert_eq!(err.to_string(), "no such host");
+        }
+        assert_eq!(resolver.lookups.load(Ordering::SeqCst), 2);
+
+        // Lookups that failed for other reasons are tried again.
+        lookup(&cache, "unreachable.test").await.unwrap_err();
+        lookup(&cache, "unreachable.test").await.unwrap_err();
+        assert_eq!(resolver.lookups.load(Ordering::SeqCst), 4);
+
+        cache.flush();
+        lookup(&cache, "example.test").await.unwrap();
+        assert_eq!(resolver.lookups.load(Ordering::SeqCst), 5);
+    }
+
+    #[tokio::test]
+    async fn clamps_ttls() {
+        // A zero TTL raised by the minimum.
+        let resolver = counting(Some(Duration::ZERO));
+        let cache = DnsCache::new(
+            resolver.clone(),
+            Some(resolver.clone()),
+            Duration::from_secs(30),
+            Duration::from_secs(60),
+            Duration::ZERO,
+        );
+        lookup(&cache, "example.test").await.unwrap();
+        lookup(&cache, "example.test").await.unwrap();
+        assert_eq!(resolver.lookups.load(Ordering::SeqCst), 1);
+
+        // A long TTL cut to a zero maximum, and failures not kept.
+        let resolver = counting(Some(Duration::from_secs(3600)));
+        let cache = DnsCache::new(
+            resolver.clone(),
+            Some(resolver.clone()),
+            Duration::ZERO,
+            Duration::ZERO,
+            Duration::ZERO,
+        );
+        lookup(&cache, "example.test").await.unwrap();
+        lookup(&cache, "example.test").await.unwrap();
+        lookup(&cache, "missing.test").await.unwrap_err();
+        lookup(&cache, "missing.test").await.unwrap_err();
+        assert_eq!(resolver.lookups.load(Ordering::SeqCst), 4);
+    }
+
+    #[tokio::test]
+    async fn caches_forever_with_max_ttls() {
+        let resolver = counting(Some(Duration::MAX));
+        let cache = DnsCache::new(
+            resolver.clone(),
+            Some(resolver.clone()),
+            Duration::MAX,
+            Duration::MAX,
+            Duration::MAX,
+        );
+        for _ in 0..2 {
+            lookup(&cache, "example.test").await.unwrap();
+            lookup(&cache, "missing.test").await.unwrap_err();
+        }
+        assert_eq!(resolver.lookups.load(Ordering::SeqCst), 2);
+    }
+}
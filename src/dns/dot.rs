@@ -0,0 +1,355 @@
+//! DNS resolution over TLS ([RFC 7858](https://www.rfc-editor.org/rfc/rfc7858))
+
+use rustls_pki_types::ServerName;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpStream;
+use tokio_rustls::TlsConnector;
+
+use std::cmp;
+use std::io;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr};
+use std::sync::atomic::{AtomicU16, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use super::resolve::ResolvingWithTtl;
+use super::{Addrs, Name, Resolve, ResolveWithTtl, Resolving};
+
+const TYPE_A: u16 = 1;
+const TYPE_AAAA: u16 = 28;
+const CLASS_IN: u16 = 1;
+const RCODE_NXDOMAIN: u16 = 3;
+
+/// How long a lookup may take when the client has no connect timeout.
+const DEFAULT_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Looks up names with an upstream DNS server reached over TLS.
+///
+/// Every lookup opens its own connection, on which the `A` and `AAAA`
+/// queries are sent together. Connecting, the TLS handshake and reading the
+/// answers must all be done within `timeout`.
+#[derive(Clone)]
+pub(crate) struct DotResolver {
+    upstream: SocketAddr,
+    server_name: ServerName<'static>,
+    tls: TlsConnector,
+    next_id: Arc<AtomicU16>,
+    timeout: Duration,
+}
+
+impl DotResolver {
+    /// Lookups time out after `timeout`, or 5 seconds without one.
+    pub(crate) fn new(
+        upstream: SocketAddr,
+        server_name: &str,
+        mut tls: rustls::ClientConfig,
+        timeout: Option<Duration>,
+    ) -> crate::Result<Self> {
+        let server_name = ServerName::try_from(server_name.to_owned())
+            .map_err(|_| crate::error::builder("invalid DNS-over-TLS server name"))?;
+        // Servers aren't required to support the "dot" protocol name.
+        tls.alpn_protocols.clear();
+        Ok(DotResolver {
+            upstream,
+            server_name,
+            tls: TlsConnector::from(Arc::new(tls)),
+            next_id: Arc::new(AtomicU16::new(rand_id())),
+            timeout: timeout.unwrap_or(DEFAULT_TIMEOUT),
+        })
+    }
+
+    /// Returns the addresses of `name`, and the lowest TTL of their records.
+    async fn lookup(self, name: Name) -> io::Result<(Vec<IpAddr>, Option<Duration>)> {
+        let timeout = self.timeout;
+        match tokio::time::timeout(timeout, self.query(name)).await {
+            Ok(res) => res,
+            Err(_) => Err(io::Error::new(
+                io::ErrorKind::TimedOut,
+                "DNS-over-TLS lookup timed out",
+            )),
+        }
+    }
+
+    async fn query(self, name: Name) -> io::Result<(Vec<IpAddr>, Option<Duration>)> {
+        let ids = [
+            self.next_id.fetch_add(1, Ordering::Relaxed),
+            self.next_id.fetch_add(1, Ordering::Relaxed),
+        ];
+        let mut out = Vec::new();
+        encode_query(&mut out, ids[0], name.as_str(), TYPE_A)?;
+        encode_query(&mut out, ids[1], name.as_str(), TYPE_AAAA)?;
+
+        let tcp = TcpStream::connect(self.upstream).await?;
+        tcp.set_nodelay(true)?;
+        let mut stream = self.tls.connect(self.server_name, tcp).await?;
+        stream.write_all(&out).await?;
+        stream.flush().await?;
+
+        // Responses may come back in any order.
+        let mut answers = [None, None];
+        while answers.iter().any(Option::is_none) {
+            let len = stream.read_u16().await?;
+            let mut message = vec![0; usize::from(len)];
+            stream.read_exact(&mut message).await?;
+            let (id, addrs, ttl) = decode_response(&message)?;
+            match ids.iter().position(|&i| i == id) {
+                Some(i) => answers[i] = Some((addrs, ttl)),
+                None => return Err(invalid_data("unexpected DNS response id")),
+            }
+        }
+
+        let mut addrs = Vec::new();
+        let mut ttl = None;
+        for (answer, answer_ttl) in answers.into_iter().flatten() {
+            addrs.extend(answer);
+            ttl = match (ttl, answer_ttl) {
+                (Some(ttl), Some(answer_ttl)) => Some(cmp::min(ttl, answer_ttl)),
+                (ttl, answer_ttl) => ttl.or(answer_ttl),
+            };
+        }
+        if addrs.is_empty() {
+            return Err(io::Error::new(
+                io::ErrorKind::NotFound,
+                format!("no addresses found for {}", name.as_str()),
+            ));
+        }
+        Ok((addrs, ttl.map(|secs| Duration::from_secs(secs.into()))))
+    }
+}
+
+impl Resolve for DotResolver {
+    fn resolve(&self, name: Name) -> Resolving {
+        let resolver = self.clone();
+        Box::pin(async move {
+            let (addrs, _) = resolver.lookup(name).await?;
+            let addrs: Addrs = Box::new(addrs.into_iter().map(|ip| SocketAddr::new(ip, 0)));
+            Ok(addrs)
+        })
+    }
+}
+
+impl ResolveWithTtl for DotResolver {
+    fn resolve_with_ttl(&self, name: Name) -> ResolvingWithTtl {
+        let resolver = self.clone();
+        Box::pin(async move {
+            let (addrs, ttl) = resolver.lookup(name).await?;
+            let addrs = addrs.into_iter().map(|ip| SocketAddr::new(ip, 0)).collect();
+            Ok((addrs, ttl))
+        })
+    }
+}
+
+fn rand_id() -> u16 {
+    use std::collections::hash_map::RandomState;
+    use std::hash::{BuildHasher, Hasher};
+
+    RandomState::new().build_hasher().finish() as u16
+}
+
+fn invalid_data(msg: &'static str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, msg)
+}
+
+/// Appends a recursive query for `name`, prefixed with its length.
+fn encode_query(out: &mut Vec<u8>, id: u16, name: &str, qtype: u16) -> io::Result<()> {
+    let start = out.len();
+    out.extend_from_slice(&[0, 0]);
+    out.extend_from_slice(&id.to_be_bytes());
+    // Recursion desired, one question.
+    out.extend_from_slice(&[0x01, 0x00, 0, 1, 0, 0, 0, 0, 0, 0]);
+
+    let name = name.strip_suffix('.').unwrap_or(name);
+    let encoded = out.len();
+    for label in name.split('.') {
+        if label.is_empty() || label.len() > 63 {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "invalid DNS name",
+            ));
+        }
+        out.push(label.len() as u8);
+        out.extend_from_slice(label.as_bytes());
+    }
+    out.push(0);
+    if out.len() - encoded > 255 {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "DNS name too long",
+        ));
+    }
+    out.extend_from_slice(&qtype.to_be_bytes());
+    out.extend_from_slice(&CLASS_IN.to_be_bytes());
+
+    let len = (out.len() - start - 2) as u16;
+    out[start..start + 2].copy_from_slice(&len.to_be_bytes());
+    Ok(())
+}
+
+/// Returns the id of a response, the addresses it answers with, and the
+/// lowest TTL of their records.
+fn decode_response(message: &[u8]) -> io::Result<(u16, Vec<IpAddr>, Option<u32>)> {
+    let mut reader = Reader { buf: message };
+    let id = reader.u16()?;
+    let flags = reader.u16()?;
+    let questions = reader.u16()?;
+    let answers = reader.u16()?;
+    reader.skip(4)?;
+
+    if flags & 0x8000 == 0 {
+        return Err(invalid_data("DNS message is not a response"));
+    }
+    match flags & 0x000f {
+        0 => {}
+        RCODE_NXDOMAIN => return Ok((id, Vec::new(), None)),
+        _ => {
+            return Err(io::Error::new(
+                io::ErrorKind::Other,
+                format!("DNS server failed with response code {}", flags & 0x000f),
+            ))
+        }
+    }
+
+    for _ in 0..questions {
+        reader.skip_name()?;
+        reader.skip(4)?;
+    }
+
+    let mut addrs = Vec::new();
+    let mut min_ttl = None;
+    for _ in 0..answers {
+        reader.skip_name()?;
+        let rtype = reader.u16()?;
+        let class = reader.u16()?;
+        let ttl = reader.u32()?;
+        let len = usize::from(reader.u16()?);
+        let data = reader.take(len)?;
+        match (rtype, class, data.len()) {
+            (TYPE_A, CLASS_IN, 4) => {
+                let octets: [u8; 4] = data.try_into().expect("length checked");
+                addrs.push(IpAddr::V4(Ipv4Addr::from(octets)));
+                min_ttl = Some(min_ttl.map_or(ttl, |min| cmp::min(min, ttl)));
+            }
+            (TYPE_AAAA, CLASS_IN, 16) => {
+                let octets: [u8; 16] = data.try_into().expect("length checked");
+                addrs.push(IpAddr::V6(Ipv6Addr::from(octets)));
+                min_ttl = Some(min_ttl.map_or(ttl, |min| cmp::min(min, ttl)));
+            }
+            // CNAME records and anything else the server adds.
+            _ => {}
+        }
+    }
+    Ok((id, addrs, min_ttl))
+}
+
+struct Reader<'a> {
+    buf: &'a [u8],
+}
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, len: usize) -> io::Result<&'a [u8]> {
+        if self.buf.len() < len {
+            return Err(invalid_data("truncated DNS response"));
+        }
+        let (head, rest) = self.buf.split_at(len);
+        self.buf = rest;
+        Ok(head)
+    }
+
+    fn skip(&mut self, len: usize) -> io::Result<()> {
+        self.take(len).map(|_| ())
+    }
+
+    fn u16(&mut self) -> io::Result<u16> {
+        let bytes = self.take(2)?;
+        Ok(u16::from_be_bytes([bytes[0], bytes[1]]))
+    }
+
+    fn u32(&mut self) -> io::Result<u32> {
+        let bytes = self.take(4)?;
+        Ok(u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+    }
+
+    /// Skips a name, which may end in a compression pointer.
+    fn skip_name(&mut self) -> io::Result<()> {
+        loop {
+            let len = self.take(1)?[0];
+            match len {
+                0 => return Ok(()),
+                len if len & 0xc0 == 0xc0 => return self.skip(1),
+                len if len & 0xc0 == 0 => self.skip(usize::from(len))?,
+                _ => return Err(invalid_data("invalid DNS name label")),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn query_encoding() {
+        let mut out = Vec::new();
+        encode_query(&mut out, 0x1234, "example.com.", TYPE_AAAA).unwrap();
+        assert_eq!(
+            out,
+            b"\x00\x1d\x12\x34\x01\x00\x00\x01\x00\x00\x00\x00\x00\x00\
+              \x07example\x03com\x00\x00\x1c\x00\x01"
+        );
+
+        assert!(encode_query(&mut out, 0, "a..b", TYPE_A).is_err());
+        assert!(encode_query(&mut out, 0, &"a".repeat(64), TYPE_A).is_err());
+    }
+
+    #[test]
+    fn response_decoding() {
+        let mut message = Vec::new();
+        message.extend_from_slice(b"\x12\x34\x81\x80\x00\x01\x00\x02\x00\x00\x00\x00");
+        message.extend_from_slice(b"\x03www\x07example\x03com\x00\x00\x01\x00\x01");
+        // www.example.com CNAME example.com
+        message.extend_from_slice(b"\xc0\x0c\x00\x05\x00\x01\x00\x00\x00\x3c\x00\x02\xc0\x10");
+        // example.com A 93.184.216.34
+        message.extend_from_slice(b"\xc0\x10\x00\x01\x00\x01\x00\x00\x00\x3c\x00\x04");
+        message.extend_from_slice(&[93, 184, 216, 34]);
+
+        let (id, addrs, ttl) = decode_response(&message).unwrap();
+        assert_eq!(id, 0x1234);
+        assert_eq!(addrs, [IpAddr::from([93, 184, 216, 34])]);
+        assert_eq!(ttl, Some(60));
+
+        // NXDOMAIN
+        message[3] = 0x83;
+        assert!(decode_response(&message).unwrap().1.is_empty());
+        // SERVFAIL
+        message[3] = 0x82;
+        assert!(decode_response(&message).is_err());
+
+        assert!(decode_response(&message[..20]).is_err());
+    }
+
+    #[tokio::test]
+    async fn lookup_timeout() {
+        // Accepts connections, but never completes the TLS handshake.
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let upstream = listener.local_addr().unwrap();
+        std::thread::spawn(move || {
+            let mut conns = Vec::new();
+            for tcp in listener.incoming() {
+                conns.push(tcp);
+            }
+        });
+
+        let tls = rustls::ClientConfig::builder_with_provider(Arc::new(
+            rustls::crypto::ring::default_provider(),
+        ))
+        .with_safe_default_protocol_versions()
+        .unwrap()
+        .with_root_certificates(rustls::RootCertStore::empty())
+        .with_no_client_auth();
+        let resolver =
+            DotResolver::new(upstream, "localhost", tls, Some(Duration::from_millis(100))).unwrap();
+        let name = "example.com".parse().unwrap();
+        let err = resolver.lookup(name).await.unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::TimedOut);
+    }
+}
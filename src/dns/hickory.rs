@@ -9,8 +9,10 @@ use once_cell::sync::OnceCell;
 use std::fmt;
 use std::net::SocketAddr;
 use std::sync::Arc;
+use std::time::Instant;
 
-use super::{Addrs, Name, Resolve, Resolving};
+use super::resolve::ResolvingWithTtl;
+use super::{Addrs, Name, Resolve, ResolveWithTtl, Resolving};
 
 /// Wrapper around an `AsyncResolver`, which implements the `Resolve` trait.
 #[derive(Debug, Default, Clone)]
@@ -43,6 +45,25 @@ impl Resolve for HickoryDnsResolver {
     }
 }
 
+impl ResolveWithTtl for HickoryDnsResolver {
+    fn resolve_with_ttl(&self, name: Name) -> ResolvingWithTtl {
+        let resolver = self.clone();
+        Box::pin(async move {
+            let resolver = resolver.state.get_or_try_init(new_resolver)?;
+
+            let lookup = resolver.lookup_ip(name.as_str()).await?;
+            let ttl = lookup
+                .valid_until()
+                .saturating_duration_since(Instant::now());
+            let addrs = lookup
+                .iter()
+                .map(|ip_addr| SocketAddr::new(ip_addr, 0))
+                .collect();
+            Ok((addrs, Some(ttl)))
+        })
+    }
+}
+
 impl Iterator for SocketAddrs {
     type Item = SocketAddr;
 
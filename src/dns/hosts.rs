@@ -0,0 +1,77 @@
+//! Addresses from a hosts file, in the format of `/etc/hosts`.
+
+use std::collections::HashMap;
+use std::fs;
+use std::net::{IpAddr, SocketAddr};
+use std::path::Path;
+
+/// Reads the hosts file at `path`, as addresses for each lowercased name.
+pub(crate) fn load(path: &Path) -> crate::Result<HashMap<String, Vec<SocketAddr>>> {
+    match fs::read_to_string(path) {
+        Ok(contents) => Ok(parse(&contents)),
+        Err(e) => Err(crate::error::builder(format!(
+            "failed to read hosts file {}: {e}",
+            path.display()
+        ))),
+    }
+}
+
+/// Each line holds an address and the names it's for, then maybe a `#`
+/// comment. Lines with an invalid address are skipped, and a name listed on
+/// several lines gets all their addresses, in order.
+fn parse(contents: &str) -> HashMap<String, Vec<SocketAddr>> {
+    let mut hosts: HashMap<String, Vec<SocketAddr>> = HashMap::new();
+    for line in contents.lines() {
+        let line = line.split('#').next().unwrap_or_default();
+        let mut fields = line.split_whitespace();
+        let ip = match fields.next().and_then(|ip| ip.parse::<IpAddr>().ok()) {
+            Some(ip) => ip,
+            None => continue,
+        };
+        for name in fields {
+            // Not a pattern, as it would be with `ClientBuilder::resolve`.
+            if name.starts_with('*') {
+                continue;
+            }
+            let addrs = hosts.entry(name.to_ascii_lowercase()).or_default();
+            let addr = SocketAddr::new(ip, 0);
+            if !addrs.contains(&addr) {
+                addrs.push(addr);
+            }
+        }
+    }
+    hosts
+}
+
+#[cfg(test)]
+mod tests {
+    use super::parse;
+    use std::net::SocketAddr;
+
+    #[test]
+    fn test_hosts_parse() {
+        let hosts = parse(
+            "# local\n\
+             127.0.0.1\tlocalhost Box.Local   # this machine\n\
+             ::1 localhost ip6-localhost\n\
+             not-an-ip nowhere.test\n\
+             10.0.0.1 *.wild.test db.test\n\
+             \n\
+             10.0.0.2 db.test\n\
+             10.0.0.1 db.test\n",
+        );
+
+        let addrs = |ips: &[&str]| {
+            ips.iter()
+                .map(|ip| SocketAddr::new(ip.parse().unwrap(), 0))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(hosts["localhost"], addrs(&["127.0.0.1", "::1"]));
+        assert_eq!(hosts["box.local"], addrs(&["127.0.0.1"]));
+        assert_eq!(hosts["ip6-localhost"], addrs(&["::1"]));
+        assert_eq!(hosts["db.test"], addrs(&["10.0.0.1", "10.0.0.2"]));
+        assert!(!hosts.contains_key("nowhere.test"));
+        assert!(!hosts.contains_key("*.wild.test"));
+        assert_eq!(hosts.len(), 4);
+    }
+}
@@ -1,9 +1,18 @@
 //! DNS resolution
 
-pub use resolve::{Addrs, Name, Resolve, Resolving};
-pub(crate) use resolve::{DnsResolverWithOverrides, DynResolver};
+pub use resolve::{Addrs, DnsLookup, Name, Resolve, Resolving};
+pub(crate) use cache::DnsCache;
+pub(crate) use resolve::{
+    DnsLookupFn, DnsResolverWithHook, DnsResolverWithLogging, DnsResolverWithOverrides,
+    DynResolver, ResolveWithTtl,
+};
 
+pub(crate) mod cache;
+
+#[cfg(feature = "dns-over-tls")]
+pub(crate) mod dot;
 pub(crate) mod gai;
 #[cfg(feature = "hickory-dns")]
 pub(crate) mod hickory;
+pub(crate) mod hosts;
 pub(crate) mod resolve;
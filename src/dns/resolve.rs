@@ -2,13 +2,16 @@ use hyper_util::client::legacy::connect::dns::Name as HyperName;
 use tower_service::Service;
 
 use std::collections::HashMap;
+use std::error::Error as StdError;
 use std::future::Future;
 use std::net::SocketAddr;
 use std::pin::Pin;
 use std::str::FromStr;
 use std::sync::Arc;
 use std::task::{Context, Poll};
+use std::time::{Duration, Instant};
 
+use super::DnsCache;
 use crate::error::BoxError;
 
 /// Alias for an `Iterator` trait object over `SocketAddr`.
@@ -30,6 +33,17 @@ pub trait Resolve: Send + Sync {
     fn resolve(&self, name: Name) -> Resolving;
 }
 
+/// Alias for the `Future` type returned by a resolver that knows how long
+/// its answers stay valid.
+pub(crate) type ResolvingWithTtl =
+    Pin<Box<dyn Future<Output = Result<(Vec<SocketAddr>, Option<Duration>), BoxError>> + Send>>;
+
+/// A resolver that can tell the DNS cache for how long to keep its answers.
+pub(crate) trait ResolveWithTtl: Send + Sync {
+    /// Resolves `name`, along with the TTL of the answer if it has one.
+    fn resolve_with_ttl(&self, name: Name) -> ResolvingWithTtl;
+}
+
 /// A name that must be resolved to addresses.
 #[derive(Debug)]
 pub struct Name(pub(super) HyperName);
@@ -79,23 +93,45 @@ impl Service<HyperName> for DynResolver {
 pub(crate) struct DnsResolverWithOverrides {
     dns_resolver: Arc<dyn Resolve>,
     overrides: Arc<HashMap<String, Vec<SocketAddr>>>,
+    /// Patterns like `*.example.com`, as `.example.com`, longest first.
+    wildcards: Arc<Vec<(String, Vec<SocketAddr>)>>,
 }
 
 impl DnsResolverWithOverrides {
     pub(crate) fn new(
         dns_resolver: Arc<dyn Resolve>,
-        overrides: HashMap<String, Vec<SocketAddr>>,
+        mut overrides: HashMap<String, Vec<SocketAddr>>,
     ) -> Self {
+        let mut wildcards = Vec::new();
+        overrides.retain(|domain, addrs| match domain.strip_prefix('*') {
+            Some(suffix) if suffix.starts_with('.') => {
+                wildcards.push((suffix.to_owned(), std::mem::take(addrs)));
+                false
+            }
+            _ => true,
+        });
+        wildcards.sort_by_key(|(suffix, _)| std::cmp::Reverse(suffix.len()));
+
         DnsResolverWithOverrides {
             dns_resolver,
             overrides: Arc::new(overrides),
+            wildcards: Arc::new(wildcards),
         }
     }
+
+    fn lookup(&self, name: &str) -> Option<&Vec<SocketAddr>> {
+        self.overrides.get(name).or_else(|| {
+            self.wildcards
+                .iter()
+                .find(|(suffix, _)| name.len() > suffix.len() && name.ends_with(suffix.as_str()))
+                .map(|(_, addrs)| addrs)
+        })
+    }
 }
 
 impl Resolve for DnsResolverWithOverrides {
     fn resolve(&self, name: Name) -> Resolving {
-        match self.overrides.get(name.as_str()) {
+        match self.lookup(name.as_str()) {
             Some(dest) => {
                 let addrs: Addrs = Box::new(dest.clone().into_iter());
                 Box::pin(futures_util::future::ready(Ok(addrs)))
@@ -105,6 +141,147 @@ impl Resolve for DnsResolverWithOverrides {
     }
 }
 
+/// A DNS lookup made by a client, given to `ClientBuilder::on_dns_lookup`.
+#[derive(Debug)]
+pub struct DnsLookup<'a> {
+    name: &'a str,
+    duration: Duration,
+    cache_hit: bool,
+    result: Result<&'a [SocketAddr], &'a (dyn StdError + Send + Sync + 'static)>,
+}
+
+pub(crate) type DnsLookupFn = dyn Fn(&DnsLookup<'_>) + Send + Sync + 'static;
+
+impl<'a> DnsLookup<'a> {
+    /// The name that was looked up.
+    pub fn name(&self) -> &'a str {
+        self.name
+    }
+
+    /// How long the lookup took.
+    pub fn duration(&self) -> Duration {
+        self.duration
+    }
+
+    /// Whether the answer came from the client's DNS cache, see
+    /// `ClientBuilder::dns_cache`.
+    pub fn cache_hit(&self) -> bool {
+        self.cache_hit
+    }
+
+    /// The addresses found, empty if the lookup failed.
+    pub fn addrs(&self) -> &'a [SocketAddr] {
+        self.result.unwrap_or(&[])
+    }
+
+    /// The error the lookup failed with, if it did.
+    pub fn error(&self) -> Option<&'a (dyn StdError + Send + Sync + 'static)> {
+        self.result.err()
+    }
+}
+
+enum Observed {
+    Resolver(Arc<dyn Resolve>),
+    Cache(DnsCache),
+}
+
+/// Reports every lookup made by the wrapped resolver or cache to a callback.
+pub(crate) struct DnsResolverWithHook {
+    observed: Observed,
+    hook: Arc<DnsLookupFn>,
+}
+
+impl DnsResolverWithHook {
+    pub(crate) fn new(
+        dns_resolver: Arc<dyn Resolve>,
+        cache: Option<DnsCache>,
+        hook: Arc<DnsLookupFn>,
+    ) -> Self {
+        let observed = match cache {
+            Some(cache) => Observed::Cache(cache),
+            None => Observed::Resolver(dns_resolver),
+        };
+        DnsResolverWithHook { observed, hook }
+    }
+}
+
+impl Resolve for DnsResolverWithHook {
+    fn resolve(&self, name: Name) -> Resolving {
+        let start = Instant::now();
+        let host = name.as_str().to_owned();
+        let (resolving, cache_hit) = match self.observed {
+            Observed::Resolver(ref resolver) => (resolver.resolve(name), false),
+            Observed::Cache(ref cache) => cache.resolve_cached(name),
+        };
+        let hook = self.hook.clone();
+        Box::pin(async move {
+            let result = resolving.await.map(|addrs| addrs.collect::<Vec<_>>());
+            hook(&DnsLookup {
+                name: &host,
+                duration: start.elapsed(),
+                cache_hit,
+                result: match result {
+                    Ok(ref addrs) => Ok(addrs),
+                    Err(ref err) => Err(&**err),
+                },
+            });
+            result.map(|addrs| Box::new(addrs.into_iter()) as Addrs)
+        })
+    }
+}
+
+/// Logs every lookup made by the wrapped resolver.
+pub(crate) struct DnsResolverWithLogging {
+    dns_resolver: Arc<dyn Resolve>,
+    hash_hostnames: bool,
+}
+
+impl DnsResolverWithLogging {
+    pub(crate) fn new(dns_resolver: Arc<dyn Resolve>, hash_hostnames: bool) -> Self {
+        DnsResolverWithLogging {
+            dns_resolver,
+            hash_hostnames,
+        }
+    }
+}
+
+impl Resolve for DnsResolverWithLogging {
+    fn resolve(&self, name: Name) -> Resolving {
+        let host = if self.hash_hostnames {
+            hash_hostname(name.as_str())
+        } else {
+            name.as_str().to_owned()
+        };
+        log::debug!("DNS lookup: {host}");
+
+        let resolving = self.dns_resolver.resolve(name);
+        Box::pin(async move {
+            match resolving.await {
+                Ok(addrs) => {
+                    let addrs = addrs.collect::<Vec<_>>();
+                    log::debug!("DNS result for {host}: {addrs:?}");
+                    Ok(Box::new(addrs.into_iter()) as Addrs)
+                }
+                Err(err) => {
+                    log::warn!("DNS failed for {host}: {err}");
+                    Err(err)
+                }
+            }
+        })
+    }
+}
+
+/// Replaces a hostname with a hash of it, the same for every lookup of that
+/// name, so logs can be correlated without revealing it.
+fn hash_hostname(host: &str) -> String {
+    use std::collections::hash_map::DefaultHasher;
+    use std::hash::{Hash, Hasher};
+
+    let mut hasher = DefaultHasher::new();
+    host.hash(&mut hasher);
+    format!("<host {:016x}>", hasher.finish())
+}
+
 mod sealed {
     use std::fmt;
 
@@ -121,3 +298,110 @@ mod sealed {
 
     impl std::error::Error for InvalidNameError {}
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures_util::TryFutureExt;
+
+    #[test]
+    fn hash_hostname_hides_name() {
+        let hashed = hash_hostname("secret.example");
+        assert!(!hashed.contains("secret"));
+        assert_eq!(hashed, hash_hostname("secret.example"));
+        assert_ne!(hashed, hash_hostname("other.example"));
+    }
+
+    #[tokio::test]
+    async fn logging_resolver_passes_through() {
+        let mut overrides = HashMap::new();
+        overrides.insert(
+            "example.test".to_owned(),
+            vec!["127.0.0.1:0".parse().unwrap()],
+        );
+        let inner = DnsResolverWithOverrides::new(Arc::new(NoResolve), overrides);
+        let resolver = DnsResolverWithLogging::new(Arc::new(inner), true);
+
+        let addrs = resolver
+            .resolve("example.test".parse().unwrap())
+            .await
+            .unwrap()
+            .collect::<Vec<_>>();
+        assert_eq!(addrs, ["127.0.0.1:0".parse().unwrap()]);
+
+        resolver
+            .resolve("missing.test".parse().unwrap())
+            .await
+            .err()
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn wildcard_overrides() {
+        let addr = |last: u8| SocketAddr::from(([127, 0, 0, last], 0));
+        let mut overrides = HashMap::new();
+        overrides.insert("*.example.test".to_owned(), vec![addr(1)]);
+        overrides.insert("*.deep.example.test".to_owned(), vec![addr(2), addr(3)]);
+        overrides.insert("exact.deep.example.test".to_owned(), vec![addr(4)]);
+        let resolver = DnsResolverWithOverrides::new(Arc::new(NoResolve), overrides);
+
+        let lookup = |name: &str| {
+            resolver
+                .resolve(name.parse().unwrap())
+                .map_ok(|addrs| addrs.collect::<Vec<_>>())
+        };
+        assert_eq!(lookup("a.example.test").await.unwrap(), [addr(1)]);
+        assert_eq!(lookup("a.b.example.test").await.unwrap(), [addr(1)]);
+        assert_eq!(lookup("a.deep.example.test").await.unwrap(), [addr(2), addr(3)]);
+        assert_eq!(lookup("exact.deep.example.test").await.unwrap(), [addr(4)]);
+        // Only subdomains match.
+        lookup("example.test").await.err().unwrap();
+        lookup("notexample.test").await.err().unwrap();
+    }
+
+    #[tokio::test]
+    async fn hook_reports_lookups() {
+        let lookups = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let recorded = lookups.clone();
+        let hook: Arc<DnsLookupFn> = Arc::new(move |lookup: &DnsLookup<'_>| {
+            recorded.lock().unwrap().push((
+                lookup.name().to_owned(),
+                lookup.cache_hit(),
+                lookup.addrs().to_vec(),
+                lookup.error().map(|err| err.to_string()),
+            ));
+        });
+
+        let mut overrides = HashMap::new();
+        overrides.insert("cached.test".to_owned(), vec![SocketAddr::from(([127, 0, 0, 1], 0))]);
+        let inner = Arc::new(DnsResolverWithOverrides::new(Arc::new(NoResolve), overrides));
+        let cache = DnsCache::new(
+            inner,
+            None,
+            Duration::ZERO,
+            Duration::from_secs(60),
+            Duration::ZERO,
+        );
+        let resolver = DnsResolverWithHook::new(Arc::new(NoResolve), Some(cache), hook);
+
+        for name in ["cached.test", "cached.test", "missing.test"] {
+            let _ = resolver.resolve(name.parse().unwrap()).await;
+        }
+        assert_eq!(
+            *lookups.lock().unwrap(),
+            [
+                ("cached.test".to_owned(), false, vec!["127.0.0.1:0".parse().unwrap()], None),
+                ("cached.test".to_owned(), true, vec!["127.0.0.1:0".parse().unwrap()], None),
+                ("missing.test".to_owned(), false, vec![], Some("no such host".to_owned())),
+            ]
+        );
+    }
+
+    struct NoResolve;
+
+    impl Resolve for NoResolve {
+        fn resolve(&self, _name: Name) -> Resolving {
+            Box::pin(futures_util::future::ready(Err("no such host".into())))
+        }
+    }
+}
@@ -124,6 +124,10 @@ impl Error {
     #[cfg(not(target_arch = "wasm32"))]
     /// Returns true if the error is related to connect
     pub fn is_connect(&self) -> bool {
+        if matches!(self.inner.kind, Kind::Connect) {
+            return true;
+        }
+
         let mut source = self.source();
 
         while let Some(err) = source {
@@ -191,6 +195,7 @@ impl fmt::Display for Error {
             Kind::Decode => f.write_str("error decoding response body")?,
             Kind::Redirect => f.write_str("error following redirect")?,
             Kind::Upgrade => f.write_str("error upgrading connection")?,
+            Kind::Connect => f.write_str("error trying to connect")?,
             Kind::Status(ref code) => {
                 let prefix = if code.is_client_error() {
                     "HTTP status client error"
@@ -239,6 +244,8 @@ pub(crate) enum Kind {
     Body,
     Decode,
     Upgrade,
+    #[cfg_attr(target_arch = "wasm32", allow(dead_code))]
+    Connect,
 }
 
 // constructors
@@ -275,6 +282,17 @@ pub(crate) fn url_invalid_uri(url: Url) -> Error {
     Error::new(Kind::Builder, Some("Parsed Url is not a valid Uri")).with_url(url)
 }
 
+pub(crate) fn content_type_mismatch(url: Url, expected: &str, actual: Option<String>) -> Error {
+    Error::new(
+        Kind::Decode,
+        Some(ContentTypeMismatch {
+            expected: expected.to_owned(),
+            actual,
+        }),
+    )
+    .with_url(url)
+}
+
 if_wasm! {
     pub(crate) fn wasm(js_val: wasm_bindgen::JsValue) -> BoxError {
         format!("{js_val:?}").into()
@@ -285,6 +303,11 @@ pub(crate) fn upgrade<E: Into<BoxError>>(e: E) -> Error {
     Error::new(Kind::Upgrade, Some(e))
 }
 
+#[cfg(not(target_arch = "wasm32"))]
+pub(crate) fn connect<E: Into<BoxError>>(e: E) -> Error {
+    Error::new(Kind::Connect, Some(e))
+}
+
 // io::Error helpers
 
 #[cfg(any(
@@ -334,6 +357,43 @@ impl fmt::Display for BadScheme {
 
 impl StdError for BadScheme {}
 
+/// The `Content-Type` of a response didn't match what was expected.
+///
+/// This is the source of the error returned by
+/// [`Response::assert_content_type`](crate::Response::assert_content_type).
+#[derive(Debug)]
+pub struct ContentTypeMismatch {
+    expected: String,
+    actual: Option<String>,
+}
+
+impl ContentTypeMismatch {
+    /// The media type that was expected.
+    pub fn expected(&self) -> &str {
+        &self.expected
+    }
+
+    /// The `Content-Type` the server sent, if any.
+    pub fn actual(&self) -> Option<&str> {
+        self.actual.as_deref()
+    }
+}
+
+impl fmt::Display for ContentTypeMismatch {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.actual {
+            Some(ref actual) => write!(
+                f,
+                "expected content type `{}`, found `{}`",
+                self.expected, actual
+            ),
+            None => write!(f, "expected content type `{}`, found none", self.expected),
+        }
+    }
+}
+
+impl StdError for ContentTypeMismatch {}
+
 #[cfg(test)]
 mod tests {
     use super::*;
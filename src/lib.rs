@@ -145,6 +145,13 @@
 //! If both the all proxy and HTTP or HTTPS proxy variables are set the more specific
 //! HTTP or HTTPS proxies take precedence.
 //!
+//! When none of these are set, the proxy settings of the Windows registry or
+//! of the macOS system configuration are used, and on Linux, those of GNOME or
+//! KDE if the `linux-desktop-proxy` feature is enabled. With the
+//! `proxy-auto-config` feature, a proxy found by automatic discovery (WPAD) or
+//! given as a setup script (PAC) is used on Windows and macOS when no proxy is
+//! set manually.
+//!
 //! These can be overwritten by adding a [`Proxy`] to `ClientBuilder`
 //! i.e. `let proxy = reqwest::Proxy::http("https://secure.example")?;`
 //! or disabled by calling `ClientBuilder::no_proxy()`.
@@ -203,11 +210,29 @@
 //! - **zstd**: Provides response body zstd decompression.
 //! - **deflate**: Provides response body deflate decompression.
 //! - **json**: Provides serialization and deserialization for JSON bodies.
+//! - **graphql**: Provides helpers for sending GraphQL requests as JSON bodies.
 //! - **multipart**: Provides functionality for multipart forms.
 //! - **stream**: Adds support for `futures::Stream`.
 //! - **socks**: Provides SOCKS5 proxy support.
+//! - **websocket**: Provides WebSocket client support.
+//! - **cert-expiry-check**: Provides checking how soon server certificates expire.
+//! - **tls-cert-parsing**: Provides the subject, alternative names and expiry
+//!   of server certificates in `TlsInfo`.
+//! - **danger-request-invalid-certs**: Allows turning off certificate
+//!   validation for single requests.
+//! - **proxy-digest-auth**: Answers Digest challenges from proxies.
+//! - **proxy-ntlm-auth**: Provides NTLM, and on Windows Negotiate, proxy
+//!   authentication.
+//! - **linux-desktop-proxy**: Uses the GNOME or KDE proxy settings on Linux,
+//!   when no proxy environment variables are set.
+//! - **proxy-auto-config**: Uses automatic proxy discovery (WPAD) and setup
+//!   scripts (PAC) of the Windows and macOS proxy settings. The script is run
+//!   for the scheme, host and port of each destination, on a thread of its
+//!   own.
 //! - **hickory-dns**: Enables a hickory-dns async resolver instead of default
 //!   threadpool using `getaddrinfo`.
+//! - **dns-over-tls**: Allows resolving names with a DNS-over-TLS server, see
+//!   `ClientBuilder::dns_over_tls`.
 //!
 //! ## Unstable Features
 //!
@@ -275,7 +300,7 @@ mod error;
 mod into_url;
 mod response;
 
-pub use self::error::{Error, Result};
+pub use self::error::{ContentTypeMismatch, Error, Result};
 pub use self::into_url::IntoUrl;
 pub use self::response::ResponseBuilderExt;
 
@@ -343,9 +368,15 @@ if_hyper! {
     doctest!("../README.md");
 
     pub use self::async_impl::{
-        Body, Client, ClientBuilder, Request, RequestBuilder, Response, Upgraded,
+        Body, Client, ClientBuilder, HostPoolStats, PoolStats, Request, RequestBuilder,
+        RequestSigner, Response, Signing, Upgraded,
+    };
+    pub use self::connect::{ConnectRetry, ConnectionInfo, WireDirection};
+    pub use self::proxy::{
+        ConnectedMeta, CustomProxyConnector, CustomProxyContext, CustomProxyStream, NoProxy, Proxy,
+        ProxyInfo, ProxyRequest, ProxyScheme, ProxySelection,
     };
-    pub use self::proxy::{Proxy,NoProxy, CustomProxyConnector, CustomProxyStream};
+    pub use self::proxy_auth::ProxyAuthChallenge;
     #[cfg(feature = "__tls")]
     // Re-exports, to be removed in a future release
     pub use tls::{Certificate, Identity};
@@ -360,11 +391,16 @@ if_hyper! {
     #[cfg(feature = "cookies")]
     pub mod cookie;
     pub mod dns;
+    mod netrc;
     mod proxy;
+    mod proxy_auth;
     pub mod redirect;
     #[cfg(feature = "__tls")]
     pub mod tls;
     mod util;
+    #[cfg(feature = "websocket")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "websocket")))]
+    pub mod websocket;
 }
 
 if_wasm! {
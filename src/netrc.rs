@@ -0,0 +1,223 @@
+//! Credentials from a `.netrc` file, as read by curl's `--netrc`.
+
+use std::collections::HashMap;
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use http::header::HeaderValue;
+
+/// The `machine` entries of a netrc file, and its `default` entry.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct Netrc {
+    machines: HashMap<String, Login>,
+    default: Option<Login>,
+}
+
+#[derive(Clone, Default)]
+struct Login {
+    login: String,
+    password: String,
+}
+
+impl std::fmt::Debug for Login {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Login")
+            .field("login", &self.login)
+            .finish_non_exhaustive()
+    }
+}
+
+impl Netrc {
+    /// Reads the netrc file at `path`, or the default one when `None`.
+    ///
+    /// The default is `$NETRC`, or `.netrc` in the home directory (`_netrc`
+    /// on Windows). Unlike an explicit path, it's fine for it not to exist.
+    pub(crate) fn load(path: Option<&Path>) -> crate::Result<Netrc> {
+        let (path, required) = match path {
+            Some(path) => (path.to_owned(), true),
+            None => match default_path() {
+                Some(path) => (path, false),
+                None => return Ok(Netrc::default()),
+            },
+        };
+        match fs::read_to_string(&path) {
+            Ok(contents) => Ok(Netrc::parse(&contents)),
+            Err(e) if !required && e.kind() == std::io::ErrorKind::NotFound => {
+                Ok(Netrc::default())
+            }
+            Err(e) => Err(crate::error::builder(format!(
+                "failed to read netrc file {}: {e}",
+                path.display()
+            ))),
+        }
+    }
+
+    fn parse(contents: &str) -> Netrc {
+        let mut netrc = Netrc::default();
+        // The entry being read: `Some(name)` for a machine, `None` for the
+        // default.
+        let mut current: Option<(Option<String>, Login)> = None;
+        let mut lines = contents.lines();
+
+        while let Some(line) = lines.next() {
+            let line = line.trim();
+            if line.starts_with('#') {
+                continue;
+            }
+            let mut tokens = line.split_whitespace();
+            while let Some(token) = tokens.next() {
+                match token {
+                    "machine" | "default" => {
+                        if let Some((name, login)) = current.take() {
+                            netrc.insert(name, login);
+                        }
+                        let name = if token == "machine" {
+                            tokens.next().map(|name| name.to_ascii_lowercase())
+                        } else {
+                            None
+                        };
+                        current = Some((name, Login::default()));
+                    }
+                    "login" => {
+                        if let (Some((_, login)), Some(value)) = (current.as_mut(), tokens.next()) {
+                            login.login = value.to_owned();
+                        }
+                    }
+                    "password" => {
+                        if let (Some((_, login)), Some(value)) = (current.as_mut(), tokens.next()) {
+                            login.password = value.to_owned();
+                        }
+                    }
+                    "account" => {
+                        tokens.next();
+                    }
+                    "macdef" => {
+                        // A macro runs until the next empty line.
+                        for line in lines.by_ref() {
+                            if line.trim().is_empty() {
+                                break;
+                            }
+                        }
+                        break;
+                    }
+                    _ => {}
+                }
+            }
+        }
+        if let Some((name, login)) = current {
+            netrc.insert(name, login);
+        }
+        netrc
+    }
+
+    fn insert(&mut self, name: Option<String>, login: Login) {
+        match name {
+            // The first entry for a machine wins, like curl.
+            Some(name) => {
+                self.machines.entry(name).or_insert(login);
+            }
+            None => {
+                self.default.get_or_insert(login);
+            }
+        }
+    }
+
+    /// The login and password for `host`, if the file has some.
+    pub(crate) fn login(&self, host: &str) -> Option<(String, String)> {
+        self.find(host, true)
+    }
+
+    /// The `Basic` authorization for `host`, if the file has a login for it.
+    pub(crate) fn basic_auth(&self, host: &str) -> Option<HeaderValue> {
+        self.login(host).map(to_basic_auth)
+    }
+
+    /// The `Basic` authorization of the `machine` entry for `host`.
+    ///
+    /// Unlike `basic_auth`, the `default` entry is never used, for hosts
+    /// that weren't chosen by the user, such as the target of a redirect.
+    pub(crate) fn machine_basic_auth(&self, host: &str) -> Option<HeaderValue> {
+        self.find(host, false).map(to_basic_auth)
+    }
+
+    fn find(&self, host: &str, or_default: bool) -> Option<(String, String)> {
+        let host = host.trim_start_matches('[').trim_end_matches(']');
+        let login = match self.machines.get(&host.to_ascii_lowercase()) {
+            Some(login) => login,
+            None if or_default => self.default.as_ref()?,
+            None => return None,
+        };
+        if login.login.is_empty() {
+            return None;
+        }
+        Some((login.login.clone(), login.password.clone()))
+    }
+}
+
+fn to_basic_auth((login, password): (String, String)) -> HeaderValue {
+    let mut header = crate::util::basic_auth(login, Some(password));
+    header.set_sensitive(true);
+    header
+}
+
+fn default_path() -> Option<PathBuf> {
+    if let Some(path) = env::var_os("NETRC") {
+        return Some(PathBuf::from(path));
+    }
+    let home = if cfg!(windows) {
+        env::var_os("USERPROFILE").or_else(|| env::var_os("HOME"))
+    } else {
+        env::var_os("HOME")
+    }?;
+    let name = if cfg!(windows) { "_netrc" } else { ".netrc" };
+    Some(Path::new(&home).join(name))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Netrc;
+
+    #[test]
+    fn test_netrc_parse() {
+        let netrc = Netrc::parse(
+            "# proxies\n\
+             machine proxy.corp login alice password s3cret\n\
+             machine Other.Example\n\
+             \tlogin bob\n\
+             \tpassword hunter2 account x\n\
+             macdef init\n\
+             machine evil.example login mallory password x\n\
+             \n\
+             machine proxy.corp login ignored password ignored\n\
+             default login anonymous password guest\n",
+        );
+
+        assert_eq!(
+            netrc.basic_auth("proxy.corp").unwrap(),
+            crate::util::basic_auth("alice", Some("s3cret"))
+        );
+        assert_eq!(
+            netrc.basic_auth("other.example").unwrap(),
+            crate::util::basic_auth("bob", Some("hunter2"))
+        );
+        assert_eq!(
+            netrc.basic_auth("evil.example").unwrap(),
+            crate::util::basic_auth("anonymous", Some("guest"))
+        );
+        assert!(netrc.basic_auth("proxy.corp").unwrap().is_sensitive());
+
+        assert_eq!(
+            netrc.machine_basic_auth("Proxy.Corp").unwrap(),
+            crate::util::basic_auth("alice", Some("s3cret"))
+        );
+        assert!(netrc.machine_basic_auth("evil.example").is_none());
+    }
+
+    #[test]
+    fn test_netrc_without_default() {
+        let netrc = Netrc::parse("machine proxy.corp login alice password s3cret");
+        assert!(netrc.basic_auth("example.com").is_none());
+        assert!(Netrc::parse("").basic_auth("proxy.corp").is_none());
+    }
+}
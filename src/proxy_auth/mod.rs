@@ -0,0 +1,489 @@
+//! Answering `407 Proxy Authentication Required` responses to a `CONNECT`.
+#![cfg_attr(not(feature = "__tls"), allow(unused))]
+
+use std::error::Error as StdError;
+use std::fmt;
+
+use http::header::HeaderValue;
+
+#[cfg(feature = "proxy-ntlm-auth")]
+use crate::error::BoxError;
+
+#[cfg(feature = "proxy-ntlm-auth")]
+mod ntlm;
+#[cfg(all(windows, feature = "proxy-ntlm-auth"))]
+mod sspi;
+
+/// An authentication challenge sent by a proxy in a `Proxy-Authenticate`
+/// header.
+///
+/// This is passed to the callback given to
+/// [`Proxy::with_auth_callback`](crate::Proxy::with_auth_callback), which decides
+/// which credentials, if any, to answer it with.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ProxyAuthChallenge {
+    scheme: String,
+    token: Option<String>,
+    params: Vec<(String, String)>,
+}
+
+/// The callback that supplies credentials for a proxy's challenge.
+pub(crate) type AuthCallback =
+    dyn Fn(&ProxyAuthChallenge) -> Option<(String, String)> + Send + Sync;
+
+impl ProxyAuthChallenge {
+    /// Returns the authentication scheme, such as `Basic` or `Digest`.
+    pub fn scheme(&self) -> &str {
+        &self.scheme
+    }
+
+    /// Returns the realm of the challenge, if the proxy sent one.
+    pub fn realm(&self) -> Option<&str> {
+        self.param("realm")
+    }
+
+    /// Returns a parameter of the challenge by name.
+    ///
+    /// Names are matched case-insensitively, and quoted values are returned
+    /// unquoted.
+    pub fn param(&self, name: &str) -> Option<&str> {
+        self.params
+            .iter()
+            .find(|(key, _)| key.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.as_str())
+    }
+
+    /// Returns the opaque token sent instead of parameters, by schemes like
+    /// NTLM and Negotiate.
+    #[cfg(feature = "proxy-ntlm-auth")]
+    pub(crate) fn token(&self) -> Option<&str> {
+        self.token.as_deref()
+    }
+
+    pub(crate) fn is(&self, scheme: &str) -> bool {
+        self.scheme.eq_ignore_ascii_case(scheme)
+    }
+}
+
+/// Credentials for a proxy that authenticates connections, rather than
+/// requests, with a handshake of several round trips.
+#[cfg(feature = "proxy-ntlm-auth")]
+#[derive(Clone)]
+pub(crate) enum ConnectionAuth {
+    Ntlm(ntlm::Credentials),
+    /// The credentials of the logged-on user, through SSPI.
+    #[cfg(windows)]
+    Sso,
+}
+
+#[cfg(feature = "proxy-ntlm-auth")]
+impl ConnectionAuth {
+    pub(crate) fn ntlm(domain: &str, username: &str, password: &str) -> ConnectionAuth {
+        ConnectionAuth::Ntlm(ntlm::Credentials {
+            domain: domain.to_owned(),
+            username: username.to_owned(),
+            password: password.to_owned(),
+        })
+    }
+
+    /// Starts a handshake with the proxy at `proxy_host`.
+    #[cfg_attr(not(windows), allow(unused_variables))]
+    pub(crate) fn start(&self, proxy_host: &str) -> Result<Box<dyn Handshake>, BoxError> {
+        match self {
+            ConnectionAuth::Ntlm(credentials) => {
+                Ok(Box::new(ntlm::Handshake::new(credentials.clone())))
+            }
+            #[cfg(windows)]
+            ConnectionAuth::Sso => Ok(Box::new(sspi::Handshake::new(
+                "Negotiate",
+                &format!("HTTP/{proxy_host}"),
+            )?)),
+        }
+    }
+}
+
+/// Our side of a connection-oriented handshake, such as NTLM.
+#[cfg(feature = "proxy-ntlm-auth")]
+pub(crate) trait Handshake: Send {
+    /// The scheme of the `Proxy-Authorization` and `Proxy-Authenticate`
+    /// headers of the handshake.
+    fn scheme(&self) -> &'static str;
+
+    /// Returns the next token to send, given the token of the proxy's last
+    /// challenge, if there was one.
+    fn step(&mut self, challenge: Option<&[u8]>) -> Result<Vec<u8>, BoxError>;
+}
+
+/// The error of a tunnel the proxy refused with a `407` status.
+#[derive(Debug)]
+pub(crate) struct ProxyAuthRequired {
+    pub(crate) challenges: Vec<ProxyAuthChallenge>,
+}
+
+impl ProxyAuthRequired {
+    /// Collects the challenges of a `407` response from its head.
+    pub(crate) fn from_head(head: &[u8]) -> ProxyAuthRequired {
+        let mut challenges = Vec::new();
+        for line in String::from_utf8_lossy(head).split("\r\n").skip(1) {
+            if let Some((name, value)) = line.split_once(':') {
+                if name.trim().eq_ignore_ascii_case("proxy-authenticate") {
+                    parse_challenges(value.trim(), &mut challenges);
+                }
+            }
+        }
+        ProxyAuthRequired { challenges }
+    }
+}
+
+impl fmt::Display for ProxyAuthRequired {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("proxy authentication required")
+    }
+}
+
+impl StdError for ProxyAuthRequired {}
+
+/// Answers the strongest challenge we support with the credentials from
+/// `callback`, for a `CONNECT` to `authority`.
+///
+/// Digest is preferred over Basic, when the `proxy-digest-auth` feature is
+/// enabled.
+pub(crate) fn respond(
+    challenges: &[ProxyAuthChallenge],
+    callback: &AuthCallback,
+    authority: &str,
+) -> Option<HeaderValue> {
+    #[cfg(feature = "proxy-digest-auth")]
+    if let Some(challenge) = challenges
+        .iter()
+        .find(|c| c.is("Digest") && digest::supports(c))
+    {
+        let (username, password) = callback(challenge)?;
+        return digest::respond(challenge, &username, &password, "CONNECT", authority);
+    }
+
+    #[cfg(not(feature = "proxy-digest-auth"))]
+    let _ = authority;
+
+    let challenge = challenges.iter().find(|c| c.is("Basic"))?;
+    let (username, password) = callback(challenge)?;
+    Some(crate::proxy::encode_basic_auth(&username, &password))
+}
+
+/// Parses the comma-separated challenges of one `Proxy-Authenticate` value.
+fn parse_challenges(value: &str, challenges: &mut Vec<ProxyAuthChallenge>) {
+    let first = challenges.len();
+    let mut rest = value;
+    loop {
+        rest = rest.trim_start_matches(|c: char| c == ',' || c.is_ascii_whitespace());
+        let (name, after) = split_token(rest);
+        if name.is_empty() {
+            return;
+        }
+        let after_ws = after.trim_start();
+        match after_ws.strip_prefix('=') {
+            Some(value) if challenges.len() > first => {
+                let (value, after) = split_value(value.trim_start());
+                if let Some(challenge) = challenges.last_mut() {
+                    challenge.params.push((name.to_ascii_lowercase(), value));
+                }
+                rest = after;
+            }
+            _ => {
+                let (token, after) = match split_token68(after.trim_start()) {
+                    Some((token, after)) => (Some(token.to_owned()), after),
+                    None => (None, after),
+                };
+                challenges.push(ProxyAuthChallenge {
+                    scheme: name.to_owned(),
+                    token,
+                    params: Vec::new(),
+                });
+                rest = after;
+            }
+        }
+    }
+}
+
+fn split_token(s: &str) -> (&str, &str) {
+    let end = s
+        .find(|c: char| !(c.is_ascii_alphanumeric() || "!#$%&'*+-.^_`|~".contains(c)))
+        .unwrap_or(s.len());
+    s.split_at(end)
+}
+
+/// Splits off a `token68`, if `s` starts with one rather than parameters.
+fn split_token68(s: &str) -> Option<(&str, &str)> {
+    let end = s
+        .find(|c: char| !(c.is_ascii_alphanumeric() || "-._~+/".contains(c)))
+        .unwrap_or(s.len());
+    if end == 0 {
+        return None;
+    }
+    let end = s.len() - s[end..].trim_start_matches('=').len();
+    let (token, rest) = s.split_at(end);
+    let next = rest.trim_start();
+    if next.is_empty() || next.starts_with(',') {
+        Some((token, rest))
+    } else {
+        None
+    }
+}
+
+fn split_value(s: &str) -> (String, &str) {
+    let quoted = match s.strip_prefix('"') {
+        Some(quoted) => quoted,
+        None => {
+            let (token, rest) = split_token(s);
+            return (token.to_owned(), rest);
+        }
+    };
+
+    let mut value = String::new();
+    let mut chars = quoted.char_indices();
+    while let Some((i, c)) = chars.next() {
+        match c {
+            '"' => return (value, &quoted[i + 1..]),
+            '\\' => {
+                if let Some((_, escaped)) = chars.next() {
+                    value.push(escaped);
+                }
+            }
+            c => value.push(c),
+        }
+    }
+    (value, "")
+}
+
+#[cfg(feature = "proxy-digest-auth")]
+mod digest {
+    use http::header::HeaderValue;
+    use md5::Md5;
+    use sha2::{Digest, Sha256};
+
+    use super::ProxyAuthChallenge;
+
+    const NONCE_COUNT: &str = "00000001";
+
+    type HashFn = fn(&str) -> String;
+
+    pub(super) fn supports(challenge: &ProxyAuthChallenge) -> bool {
+        hasher(challenge).is_some() && qop_auth(challenge).is_some()
+    }
+
+    pub(super) fn respond(
+        challenge: &ProxyAuthChallenge,
+        username: &str,
+        password: &str,
+        method: &str,
+        uri: &str,
+    ) -> Option<HeaderValue> {
+        let mut random = [0; 8];
+        getrandom::getrandom(&mut random).ok()?;
+        let cnonce = format!("{:016x}", u64::from_le_bytes(random));
+        respond_with_cnonce(challenge, username, password, method, uri, &cnonce)
+    }
+
+    pub(super) fn respond_with_cnonce(
+        challenge: &ProxyAuthChallenge,
+        username: &str,
+        password: &str,
+        method: &str,
+        uri: &str,
+        cnonce: &str,
+    ) -> Option<HeaderValue> {
+        let (hash, sess) = hasher(challenge)?;
+        let qop = qop_auth(challenge)?;
+        let realm = challenge.realm().unwrap_or("");
+        let nonce = challenge.param("nonce")?;
+
+        let mut ha1 = hash(&format!("{username}:{realm}:{password}"));
+        if sess {
+            ha1 = hash(&format!("{ha1}:{nonce}:{cnonce}"));
+        }
+        let ha2 = hash(&format!("{method}:{uri}"));
+        let response = if qop {
+            hash(&format!("{ha1}:{nonce}:{NONCE_COUNT}:{cnonce}:auth:{ha2}"))
+        } else {
+            hash(&format!("{ha1}:{nonce}:{ha2}"))
+        };
+
+        let mut header = format!(
+            "Digest username=\"{}\", realm=\"{}\", nonce=\"{}\", uri=\"{}\", response=\"{}\"",
+            quote(username),
+            quote(realm),
+            quote(nonce),
+            uri,
+            response,
+        );
+        if let Some(algorithm) = challenge.param("algorithm") {
+            header.push_str(&format!(", algorithm={algorithm}"));
+        }
+        if let Some(opaque) = challenge.param("opaque") {
+            header.push_str(&format!(", opaque=\"{}\"", quote(opaque)));
+        }
+        if qop {
+            header.push_str(&format!(", qop=auth, nc={NONCE_COUNT}, cnonce=\"{cnonce}\""));
+        }
+
+        let mut header = HeaderValue::from_str(&header).ok()?;
+        header.set_sensitive(true);
+        Some(header)
+    }
+
+    /// The hash function of the challenge's algorithm, and whether it is a
+    /// `-sess` variant.
+    fn hasher(challenge: &ProxyAuthChallenge) -> Option<(HashFn, bool)> {
+        let algorithm = challenge.param("algorithm").unwrap_or("MD5");
+        if algorithm.eq_ignore_ascii_case("MD5") {
+            Some((md5_hex, false))
+        } else if algorithm.eq_ignore_ascii_case("MD5-sess") {
+            Some((md5_hex, true))
+        } else if algorithm.eq_ignore_ascii_case("SHA-256") {
+            Some((sha256_hex, false))
+        } else if algorithm.eq_ignore_ascii_case("SHA-256-sess") {
+            Some((sha256_hex, true))
+        } else {
+            None
+        }
+    }
+
+    /// Whether to answer with `qop=auth`, or `None` if the proxy only offers
+    /// quality of protection we don't support, like `auth-int`.
+    fn qop_auth(challenge: &ProxyAuthChallenge) -> Option<bool> {
+        match challenge.param("qop") {
+            None => Some(false),
+            Some(qop) => qop
+                .split(',')
+                .any(|qop| qop.trim().eq_ignore_ascii_case("auth"))
+                .then_some(true),
+        }
+    }
+
+    fn md5_hex(data: &str) -> String {
+        hex(&Md5::digest(data.as_bytes()))
+    }
+
+    fn sha256_hex(data: &str) -> String {
+        hex(&Sha256::digest(data.as_bytes()))
+    }
+
+    fn hex(bytes: &[u8]) -> String {
+        bytes.iter().map(|b| format!("{b:02x}")).collect()
+    }
+
+    fn quote(s: &str) -> String {
+        s.replace('\\', "\\\\").replace('"', "\\\"")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn challenges(head: &str) -> Vec<ProxyAuthChallenge> {
+        ProxyAuthRequired::from_head(head.as_bytes()).challenges
+    }
+
+    #[test]
+    fn parses_challenges() {
+        let challenges = challenges(
+            "HTTP/1.1 407 Proxy Authentication Required\r\n\
+             Proxy-Authenticate: Digest realm=\"a \\\"quoted\\\" realm\", qop=\"auth,auth-int\", nonce=abc, Basic realm=\"basic\"\r\n\
+             proxy-authenticate: Negotiate\r\n\
+             Content-Length: 0\r\n\
+             \r\n",
+        );
+
+        assert_eq!(challenges.len(), 3);
+        assert_eq!(challenges[0].scheme(), "Digest");
+        assert_eq!(challenges[0].realm(), Some("a \"quoted\" realm"));
+        assert_eq!(challenges[0].param("QOP"), Some("auth,auth-int"));
+        assert_eq!(challenges[0].param("nonce"), Some("abc"));
+        assert_eq!(challenges[1].scheme(), "Basic");
+        assert_eq!(challenges[1].realm(), Some("basic"));
+        assert_eq!(challenges[2].scheme(), "Negotiate");
+        assert_eq!(challenges[2].realm(), None);
+    }
+
+    #[test]
+    fn parses_token68_challenges() {
+        let challenges = challenges(
+            "HTTP/1.1 407 Proxy Authentication Required\r\n\
+             Proxy-Authenticate: NTLM TlRMTVNTUAACAAAA==, Negotiate\r\n\
+             Proxy-Authenticate: Basic realm=\"proxy\"\r\n\
+             \r\n",
+        );
+
+        assert_eq!(challenges.len(), 3);
+        assert_eq!(challenges[0].scheme(), "NTLM");
+        assert_eq!(challenges[0].token.as_deref(), Some("TlRMTVNTUAACAAAA=="));
+        assert_eq!(challenges[1].scheme(), "Negotiate");
+        assert_eq!(challenges[1].token, None);
+        assert_eq!(challenges[2].token, None);
+        assert_eq!(challenges[2].realm(), Some("proxy"));
+    }
+
+    #[test]
+    fn responds_with_basic() {
+        let challenges = challenges(
+            "HTTP/1.1 407 Proxy Authentication Required\r\n\
+             Proxy-Authenticate: Negotiate\r\n\
+             Proxy-Authenticate: Basic realm=\"proxy\"\r\n\
+             \r\n",
+        );
+
+        let header = respond(
+            &challenges,
+            &|challenge| {
+                assert_eq!(challenge.realm(), Some("proxy"));
+                Some(("Aladdin".into(), "open sesame".into()))
+            },
+            "example.com:443",
+        );
+        assert_eq!(header.unwrap(), "Basic QWxhZGRpbjpvcGVuIHNlc2FtZQ==");
+
+        assert!(respond(&challenges, &|_| None, "example.com:443").is_none());
+    }
+
+    #[cfg(feature = "proxy-digest-auth")]
+    #[test]
+    fn responds_with_digest() {
+        // The example of RFC 2617, section 3.5.
+        let challenges = challenges(
+            "HTTP/1.1 407 Proxy Authentication Required\r\n\
+             Proxy-Authenticate: Basic realm=\"testrealm@host.com\"\r\n\
+             Proxy-Authenticate: Digest realm=\"testrealm@host.com\", qop=\"auth,auth-int\", \
+             nonce=\"dcd98b7102dd2f0e8b11d0f600bfb0c093\", opaque=\"5ccc069c403ebaf9f0171e9517f40e41\"\r\n\
+             \r\n",
+        );
+        let digest = challenges.iter().find(|c| c.is("Digest")).unwrap();
+        assert!(digest::supports(digest));
+
+        let header = digest::respond_with_cnonce(
+            digest,
+            "Mufasa",
+            "Circle Of Life",
+            "GET",
+            "/dir/index.html",
+            "0a4f113b",
+        )
+        .unwrap();
+        assert_eq!(
+            header,
+            "Digest username=\"Mufasa\", realm=\"testrealm@host.com\", \
+             nonce=\"dcd98b7102dd2f0e8b11d0f600bfb0c093\", uri=\"/dir/index.html\", \
+             response=\"6629fae49393a05397450978507c4ef1\", \
+             opaque=\"5ccc069c403ebaf9f0171e9517f40e41\", \
+             qop=auth, nc=00000001, cnonce=\"0a4f113b\""
+        );
+
+        let header = respond(
+            &challenges,
+            &|_| Some(("Mufasa".into(), "Circle Of Life".into())),
+            "example.com:443",
+        )
+        .unwrap();
+        assert!(header.to_str().unwrap().starts_with("Digest "));
+    }
+}
@@ -0,0 +1,356 @@
+//! NTLMv2 authentication, as described in [MS-NLMP].
+//!
+//! [MS-NLMP]: https://learn.microsoft.com/en-us/openspecs/windows_protocols/ms-nlmp/b38c36ed-2804-4868-a9ff-8dd3182128e4
+
+use std::convert::TryInto;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use hmac::{Hmac, Mac};
+use md4::{Digest, Md4};
+use md5::Md5;
+
+use crate::error::BoxError;
+
+const SIGNATURE: &[u8; 8] = b"NTLMSSP\0";
+
+const NEGOTIATE_UNICODE: u32 = 0x0000_0001;
+const NEGOTIATE_OEM: u32 = 0x0000_0002;
+const REQUEST_TARGET: u32 = 0x0000_0004;
+const NEGOTIATE_NTLM: u32 = 0x0000_0200;
+const NEGOTIATE_ALWAYS_SIGN: u32 = 0x0000_8000;
+const NEGOTIATE_EXTENDED_SESSIONSECURITY: u32 = 0x0008_0000;
+
+const NEGOTIATE_FLAGS: u32 = NEGOTIATE_UNICODE
+    | NEGOTIATE_OEM
+    | REQUEST_TARGET
+    | NEGOTIATE_NTLM
+    | NEGOTIATE_ALWAYS_SIGN
+    | NEGOTIATE_EXTENDED_SESSIONSECURITY;
+
+const AV_EOL: u16 = 0;
+const AV_TIMESTAMP: u16 = 7;
+
+/// Seconds from 1601-01-01, the epoch of NTLM timestamps, to the Unix epoch.
+const UNIX_EPOCH_OFFSET: u64 = 11_644_473_600;
+
+#[derive(Clone)]
+pub(crate) struct Credentials {
+    pub(crate) domain: String,
+    pub(crate) username: String,
+    pub(crate) password: String,
+}
+
+pub(super) struct Handshake {
+    credentials: Credentials,
+    state: State,
+}
+
+enum State {
+    Start,
+    Negotiated,
+    Authenticated,
+}
+
+impl Handshake {
+    pub(super) fn new(credentials: Credentials) -> Handshake {
+        Handshake {
+            credentials,
+            state: State::Start,
+        }
+    }
+}
+
+impl super::Handshake for Handshake {
+    fn scheme(&self) -> &'static str {
+        "NTLM"
+    }
+
+    fn step(&mut self, challenge: Option<&[u8]>) -> Result<Vec<u8>, BoxError> {
+        match (&self.state, challenge) {
+            (State::Start, _) => {
+                self.state = State::Negotiated;
+                Ok(negotiate_message())
+            }
+            (State::Negotiated, Some(challenge)) => {
+                let challenge = Challenge::parse(challenge)?;
+                self.state = State::Authenticated;
+                let mut client_challenge = [0; 8];
+                getrandom::getrandom(&mut client_challenge)
+                    .map_err(|e| format!("generating the NTLM client challenge failed: {e}"))?;
+                Ok(authenticate_message(
+                    &self.credentials,
+                    &challenge,
+                    client_challenge,
+                    now(),
+                ))
+            }
+            _ => Err("proxy rejected the NTLM handshake".into()),
+        }
+    }
+}
+
+fn negotiate_message() -> Vec<u8> {
+    let mut msg = Vec::with_capacity(32);
+    msg.extend_from_slice(SIGNATURE);
+    msg.extend_from_slice(&1u32.to_le_bytes());
+    msg.extend_from_slice(&NEGOTIATE_FLAGS.to_le_bytes());
+    // No domain or workstation is supplied.
+    msg.extend_from_slice(&[0; 16]);
+    msg
+}
+
+struct Challenge {
+    flags: u32,
+    server_challenge: [u8; 8],
+    target_info: Vec<u8>,
+}
+
+impl Challenge {
+    fn parse(msg: &[u8]) -> Result<Challenge, BoxError> {
+        let invalid = || BoxError::from("invalid NTLM challenge from proxy");
+        if msg.len() < 32 || &msg[..8] != SIGNATURE || u32_at(msg, 8) != 2 {
+            return Err(invalid());
+        }
+        let target_info = if msg.len() >= 48 {
+            field(msg, 40).ok_or_else(invalid)?.to_vec()
+        } else {
+            Vec::new()
+        };
+        Ok(Challenge {
+            flags: u32_at(msg, 20),
+            server_challenge: msg[24..32].try_into().expect("length checked"),
+            target_info,
+        })
+    }
+
+    /// Returns the server's `MsvAvTimestamp`, from the target info.
+    fn timestamp(&self) -> Option<u64> {
+        let mut pairs = &self.target_info[..];
+        while pairs.len() >= 4 {
+            let id = u16_at(pairs, 0);
+            let len = usize::from(u16_at(pairs, 2));
+            let value = pairs.get(4..4 + len)?;
+            match id {
+                AV_EOL => break,
+                AV_TIMESTAMP => return value.try_into().ok().map(u64::from_le_bytes),
+                _ => pairs = &pairs[4 + len..],
+            }
+        }
+        None
+    }
+}
+
+fn authenticate_message(
+    credentials: &Credentials,
+    challenge: &Challenge,
+    client_challenge: [u8; 8],
+    now: u64,
+) -> Vec<u8> {
+    let unicode = challenge.flags & NEGOTIATE_UNICODE != 0;
+    let encode = |s: &str| {
+        if unicode {
+            utf16le(s)
+        } else {
+            s.as_bytes().to_vec()
+        }
+    };
+
+    let key = ntowfv2(
+        &credentials.username,
+        &credentials.password,
+        &credentials.domain,
+    );
+    let (timestamp, lm_response) = match challenge.timestamp() {
+        // A server that sends its time expects an empty LMv2 response.
+        Some(timestamp) => (timestamp, vec![0; 24]),
+        None => (
+            now,
+            lmv2_response(&key, &challenge.server_challenge, &client_challenge),
+        ),
+    };
+    let nt_response = ntv2_response(
+        &key,
+        &challenge.server_challenge,
+        &client_challenge,
+        timestamp,
+        &challenge.target_info,
+    );
+
+    let payloads = [
+        lm_response,
+        nt_response,
+        encode(&credentials.domain),
+        encode(&credentials.username),
+        // The workstation, and the encrypted session key.
+        Vec::new(),
+        Vec::new(),
+    ];
+
+    let mut msg = Vec::new();
+    msg.extend_from_slice(SIGNATURE);
+    msg.extend_from_slice(&3u32.to_le_bytes());
+    let mut offset = 12 + 8 * payloads.len() + 4;
+    for payload in &payloads {
+        let len = payload.len() as u16;
+        msg.extend_from_slice(&len.to_le_bytes());
+        msg.extend_from_slice(&len.to_le_bytes());
+        msg.extend_from_slice(&(offset as u32).to_le_bytes());
+        offset += payload.len();
+    }
+    msg.extend_from_slice(&(NEGOTIATE_FLAGS & challenge.flags).to_le_bytes());
+    for payload in &payloads {
+        msg.extend_from_slice(payload);
+    }
+    msg
+}
+
+fn ntowfv2(username: &str, password: &str, domain: &str) -> [u8; 16] {
+    let nt_hash = Md4::digest(utf16le(password));
+    let identity = utf16le(&format!("{}{domain}", username.to_uppercase()));
+    hmac_md5(&nt_hash, &[&identity])
+}
+
+fn lmv2_response(key: &[u8; 16], server_challenge: &[u8; 8], client_challenge: &[u8; 8]) -> Vec<u8> {
+    let mut response = hmac_md5(key, &[server_challenge, client_challenge]).to_vec();
+    response.extend_from_slice(client_challenge);
+    response
+}
+
+fn ntv2_response(
+    key: &[u8; 16],
+    server_challenge: &[u8; 8],
+    client_challenge: &[u8; 8],
+    timestamp: u64,
+    target_info: &[u8],
+) -> Vec<u8> {
+    let mut blob = vec![1, 1, 0, 0, 0, 0, 0, 0];
+    blob.extend_from_slice(&timestamp.to_le_bytes());
+    blob.extend_from_slice(client_challenge);
+    blob.extend_from_slice(&[0; 4]);
+    blob.extend_from_slice(target_info);
+    blob.extend_from_slice(&[0; 4]);
+
+    let mut response = hmac_md5(key, &[server_challenge, &blob]).to_vec();
+    response.extend_from_slice(&blob);
+    response
+}
+
+fn hmac_md5(key: &[u8], data: &[&[u8]]) -> [u8; 16] {
+    let mut mac = Hmac::<Md5>::new_from_slice(key).expect("HMAC takes keys of any size");
+    for data in data {
+        mac.update(data);
+    }
+    mac.finalize().into_bytes().into()
+}
+
+fn utf16le(s: &str) -> Vec<u8> {
+    s.encode_utf16().flat_map(u16::to_le_bytes).collect()
+}
+
+/// The current time, in tenths of microseconds since 1601-01-01.
+fn now() -> u64 {
+    let since_unix = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default();
+    (since_unix.as_secs() + UNIX_EPOCH_OFFSET) * 10_000_000
+        + u64::from(since_unix.subsec_nanos() / 100)
+}
+
+fn u16_at(buf: &[u8], offset: usize) -> u16 {
+    u16::from_le_bytes([buf[offset], buf[offset + 1]])
+}
+
+fn u32_at(buf: &[u8], offset: usize) -> u32 {
+    u32::from_le_bytes(buf[offset..offset + 4].try_into().expect("4 bytes"))
+}
+
+/// Returns the payload of the security buffer at `offset`.
+fn field(msg: &[u8], offset: usize) -> Option<&[u8]> {
+    let len = usize::from(u16_at(msg, offset));
+    let start = u32_at(msg, offset + 4) as usize;
+    msg.get(start..start.checked_add(len)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // The values of MS-NLMP section 4.2.4, "NTLMv2 Authentication".
+    const SERVER_CHALLENGE: [u8; 8] = [0x01, 0x23, 0x45, 0x67, 0x89, 0xab, 0xcd, 0xef];
+    const CLIENT_CHALLENGE: [u8; 8] = [0xaa; 8];
+    const TARGET_INFO: &[u8] = b"\x02\x00\x0c\x00D\x00o\x00m\x00a\x00i\x00n\x00\
+        \x01\x00\x0c\x00S\x00e\x00r\x00v\x00e\x00r\x00\
+        \x00\x00\x00\x00";
+
+    fn key() -> [u8; 16] {
+        ntowfv2("User", "Password", "Domain")
+    }
+
+    #[test]
+    fn ntowfv2_matches_spec() {
+        assert_eq!(
+            key(),
+            [
+                0x0c, 0x86, 0x8a, 0x40, 0x3b, 0xfd, 0x7a, 0x93, 0xa3, 0x00, 0x1e, 0xf2, 0x2e, 0xf0,
+                0x2e, 0x3f
+            ]
+        );
+    }
+
+    #[test]
+    fn responses_match_spec() {
+        let lm = lmv2_response(&key(), &SERVER_CHALLENGE, &CLIENT_CHALLENGE);
+        assert_eq!(
+            lm,
+            [
+                0x86, 0xc3, 0x50, 0x97, 0xac, 0x9c, 0xec, 0x10, 0x25, 0x54, 0x76, 0x4a, 0x57, 0xcc,
+                0xcc, 0x19, 0xaa, 0xaa, 0xaa, 0xaa, 0xaa, 0xaa, 0xaa, 0xaa
+            ]
+        );
+
+        let nt = ntv2_response(&key(), &SERVER_CHALLENGE, &CLIENT_CHALLENGE, 0, TARGET_INFO);
+        assert_eq!(
+            nt[..16],
+            [
+                0x68, 0xcd, 0x0a, 0xb8, 0x51, 0xe5, 0x1c, 0x96, 0xaa, 0xbc, 0x92, 0x7b, 0xeb, 0xef,
+                0x6a, 0x1c
+            ]
+        );
+        assert_eq!(&nt[44..nt.len() - 4], TARGET_INFO);
+    }
+
+    #[test]
+    fn handshake_messages() {
+        let credentials = Credentials {
+            domain: "Domain".into(),
+            username: "User".into(),
+            password: "Password".into(),
+        };
+        let mut handshake = Handshake::new(credentials);
+        let negotiate = super::super::Handshake::step(&mut handshake, None).unwrap();
+        assert_eq!(&negotiate[..8], SIGNATURE);
+        assert_eq!(u32_at(&negotiate, 8), 1);
+
+        let mut challenge = SIGNATURE.to_vec();
+        challenge.extend_from_slice(&2u32.to_le_bytes());
+        challenge.extend_from_slice(&[0, 0, 0, 0, 48, 0, 0, 0]);
+        challenge.extend_from_slice(&NEGOTIATE_FLAGS.to_le_bytes());
+        challenge.extend_from_slice(&SERVER_CHALLENGE);
+        challenge.extend_from_slice(&[0; 8]);
+        let len = TARGET_INFO.len() as u16;
+        challenge.extend_from_slice(&len.to_le_bytes());
+        challenge.extend_from_slice(&len.to_le_bytes());
+        challenge.extend_from_slice(&48u32.to_le_bytes());
+        challenge.extend_from_slice(TARGET_INFO);
+
+        let authenticate = super::super::Handshake::step(&mut handshake, Some(&challenge)).unwrap();
+        assert_eq!(&authenticate[..8], SIGNATURE);
+        assert_eq!(u32_at(&authenticate, 8), 3);
+        assert_eq!(field(&authenticate, 28), Some(&utf16le("Domain")[..]));
+        assert_eq!(field(&authenticate, 36), Some(&utf16le("User")[..]));
+        assert_eq!(field(&authenticate, 20).unwrap().len(), 16 + 28 + TARGET_INFO.len() + 4);
+
+        // The handshake is over, whatever the proxy says next.
+        assert!(super::super::Handshake::step(&mut handshake, Some(&challenge)).is_err());
+    }
+}
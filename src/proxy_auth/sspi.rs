@@ -0,0 +1,186 @@
+//! Single sign-on with the credentials of the logged-on user, through SSPI.
+
+use std::ptr;
+
+use windows_sys::Win32::Foundation::{
+    SEC_E_OK, SEC_I_COMPLETE_AND_CONTINUE, SEC_I_COMPLETE_NEEDED, SEC_I_CONTINUE_NEEDED,
+};
+use windows_sys::Win32::Security::Authentication::Identity::{
+    AcquireCredentialsHandleW, CompleteAuthToken, DeleteSecurityContext, FreeContextBuffer,
+    FreeCredentialsHandle, InitializeSecurityContextW, SecBuffer, SecBufferDesc,
+    ISC_REQ_ALLOCATE_MEMORY, ISC_REQ_CONNECTION, SECBUFFER_TOKEN, SECBUFFER_VERSION,
+    SECPKG_CRED_OUTBOUND, SECURITY_NATIVE_DREP,
+};
+use windows_sys::Win32::Security::Credentials::SecHandle;
+
+use crate::error::BoxError;
+
+pub(super) struct Handshake {
+    scheme: &'static str,
+    target: Vec<u16>,
+    credentials: SecHandle,
+    context: Option<SecHandle>,
+}
+
+impl Handshake {
+    /// Acquires the logged-on user's credentials for `package`, to
+    /// authenticate to the service principal `target`.
+    pub(super) fn new(package: &'static str, target: &str) -> Result<Handshake, BoxError> {
+        let package_w = wide(package);
+        let mut credentials = SecHandle {
+            dwLower: 0,
+            dwUpper: 0,
+        };
+        let mut expiry = 0;
+        // SAFETY: the package name is NUL-terminated, and the handle and
+        // expiry are valid for writes.
+        let status = unsafe {
+            AcquireCredentialsHandleW(
+                ptr::null(),
+                package_w.as_ptr(),
+                SECPKG_CRED_OUTBOUND,
+                ptr::null(),
+                ptr::null(),
+                None,
+                ptr::null(),
+                &mut credentials,
+                &mut expiry,
+            )
+        };
+        if status != SEC_E_OK {
+            return Err(format!("failed to acquire {package} credentials: {status:#x}").into());
+        }
+
+        Ok(Handshake {
+            scheme: package,
+            target: wide(target),
+            credentials,
+            context: None,
+        })
+    }
+}
+
+impl super::Handshake for Handshake {
+    fn scheme(&self) -> &'static str {
+        self.scheme
+    }
+
+    fn step(&mut self, challenge: Option<&[u8]>) -> Result<Vec<u8>, BoxError> {
+        if self.context.is_some() && challenge.is_none() {
+            return Err(format!("proxy rejected the {} handshake", self.scheme).into());
+        }
+
+        let mut input_buffer = SecBuffer {
+            cbBuffer: challenge.map_or(0, |c| c.len() as u32),
+            BufferType: SECBUFFER_TOKEN,
+            pvBuffer: challenge.map_or(ptr::null_mut(), |c| c.as_ptr() as *mut _),
+        };
+        let input = SecBufferDesc {
+            ulVersion: SECBUFFER_VERSION,
+            cBuffers: 1,
+            pBuffers: &mut input_buffer,
+        };
+        let mut output_buffer = SecBuffer {
+            cbBuffer: 0,
+            BufferType: SECBUFFER_TOKEN,
+            pvBuffer: ptr::null_mut(),
+        };
+        let mut output = SecBufferDesc {
+            ulVersion: SECBUFFER_VERSION,
+            cBuffers: 1,
+            pBuffers: &mut output_buffer,
+        };
+        let mut context = self.context.unwrap_or(SecHandle {
+            dwLower: 0,
+            dwUpper: 0,
+        });
+        let mut attributes = 0;
+        let mut expiry = 0;
+
+        // SAFETY: every pointer is valid for the call, and SSPI allocates the
+        // output token, which is freed below.
+        let status = unsafe {
+            InitializeSecurityContextW(
+                &self.credentials,
+                self.context
+                    .as_ref()
+                    .map_or(ptr::null(), |context| context as *const _),
+                self.target.as_ptr(),
+                ISC_REQ_CONNECTION | ISC_REQ_ALLOCATE_MEMORY,
+                0,
+                SECURITY_NATIVE_DREP,
+                if challenge.is_some() {
+                    &input
+                } else {
+                    ptr::null()
+                },
+                0,
+                &mut context,
+                &mut output,
+                &mut attributes,
+                &mut expiry,
+            )
+        };
+        if self.context.is_none()
+            && matches!(
+                status,
+                SEC_E_OK | SEC_I_CONTINUE_NEEDED | SEC_I_COMPLETE_NEEDED | SEC_I_COMPLETE_AND_CONTINUE
+            )
+        {
+            self.context = Some(context);
+        }
+
+        let completed = match status {
+            SEC_I_COMPLETE_NEEDED | SEC_I_COMPLETE_AND_CONTINUE => {
+                // SAFETY: the context was just initialized, and the output
+                // token isn't freed yet.
+                unsafe { CompleteAuthToken(&context, &output) }
+            }
+            _ => SEC_E_OK,
+        };
+
+        let token = if output_buffer.pvBuffer.is_null() {
+            Vec::new()
+        } else {
+            // SAFETY: SSPI returned a buffer of `cbBuffer` bytes, which we
+            // own until it is freed.
+            unsafe {
+                let token = std::slice::from_raw_parts(
+                    output_buffer.pvBuffer as *const u8,
+                    output_buffer.cbBuffer as usize,
+                )
+                .to_vec();
+                FreeContextBuffer(output_buffer.pvBuffer);
+                token
+            }
+        };
+
+        match status {
+            SEC_E_OK | SEC_I_CONTINUE_NEEDED | SEC_I_COMPLETE_NEEDED | SEC_I_COMPLETE_AND_CONTINUE
+                if completed == SEC_E_OK =>
+            {
+                Ok(token)
+            }
+            SEC_E_OK | SEC_I_CONTINUE_NEEDED | SEC_I_COMPLETE_NEEDED | SEC_I_COMPLETE_AND_CONTINUE => {
+                Err(format!("failed to complete {} token: {completed:#x}", self.scheme).into())
+            }
+            status => Err(format!("{} handshake failed: {status:#x}", self.scheme).into()),
+        }
+    }
+}
+
+impl Drop for Handshake {
+    fn drop(&mut self) {
+        // SAFETY: the handles were initialized by SSPI, and are released once.
+        unsafe {
+            if let Some(context) = &self.context {
+                DeleteSecurityContext(context);
+            }
+            FreeCredentialsHandle(&self.credentials);
+        }
+    }
+}
+
+fn wide(s: &str) -> Vec<u16> {
+    s.encode_utf16().chain(Some(0)).collect()
+}
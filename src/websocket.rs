@@ -0,0 +1,133 @@
+//! WebSocket support.
+//!
+//! [`Client::websocket`] performs the opening handshake and returns a
+//! [`WebSocket`], a [`tokio_tungstenite::WebSocketStream`] over the upgraded
+//! connection. Messages are sent and received through its `Sink` and
+//! `Stream` implementations.
+//!
+//! [`tokio_tungstenite::WebSocketStream`]: https://docs.rs/tokio-tungstenite
+
+use http::header::{
+    HeaderValue, CONNECTION, SEC_WEBSOCKET_ACCEPT, SEC_WEBSOCKET_KEY, SEC_WEBSOCKET_VERSION,
+    UPGRADE,
+};
+use http::{StatusCode, Version};
+use tokio_tungstenite::tungstenite::handshake::client::generate_key;
+use tokio_tungstenite::tungstenite::handshake::derive_accept_key;
+use tokio_tungstenite::tungstenite::protocol::Role;
+use tokio_tungstenite::WebSocketStream;
+
+pub use tokio_tungstenite::tungstenite::{self, Message};
+
+use crate::{Client, IntoUrl, Response, Upgraded};
+
+/// A WebSocket connection established by a `Client`.
+pub type WebSocket = WebSocketStream<Upgraded>;
+
+impl Client {
+    /// Open a WebSocket connection to the given URL.
+    ///
+    /// Both `ws`/`wss` and `http`/`https` URLs are accepted. The opening
+    /// handshake is sent as an HTTP/1.1 `GET` request, and the returned
+    /// future resolves once the server has switched protocols.
+    ///
+    /// # Optional
+    ///
+    /// This requires the optional `websocket` feature enabled.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use futures_util::{SinkExt, StreamExt};
+    /// use reqwest::websocket::Message;
+    ///
+    /// # async fn run() -> Result<(), Box<dyn std::error::Error>> {
+    /// let mut ws = reqwest::Client::new()
+    ///     .websocket("wss://echo.websocket.org")
+    ///     .await?;
+    ///
+    /// ws.send(Message::text("hello")).await?;
+    /// let reply = ws.next().await;
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// This method fails if the request fails, if the server doesn't respond
+    /// with `101 Switching Protocols`, or if its `Sec-WebSocket-Accept`
+    /// header doesn't match the key that was sent.
+    pub async fn websocket<U: IntoUrl>(&self, url: U) -> crate::Result<WebSocket> {
+        let mut url = url.into_url()?;
+        let scheme = match url.scheme() {
+            "ws" => Some("http"),
+            "wss" => Some("https"),
+            _ => None,
+        };
+        if let Some(scheme) = scheme {
+            if url.set_scheme(scheme).is_err() {
+                return Err(crate::error::url_bad_scheme(url));
+            }
+        }
+
+        let key = generate_key();
+        let res = self
+            .get(url)
+            .version(Version::HTTP_11)
+            .header(CONNECTION, HeaderValue::from_static("upgrade"))
+            .header(UPGRADE, HeaderValue::from_static("websocket"))
+            .header(SEC_WEBSOCKET_VERSION, HeaderValue::from_static("13"))
+            .header(SEC_WEBSOCKET_KEY, key.as_str())
+            .send()
+            .await?;
+
+        if res.status() == StatusCode::SWITCHING_PROTOCOLS {
+            let expected = derive_accept_key(key.as_bytes());
+            let accepted = res
+                .headers()
+                .get(SEC_WEBSOCKET_ACCEPT)
+                .map_or(false, |v| v.as_bytes() == expected.as_bytes());
+            if !accepted {
+                return Err(crate::error::upgrade(
+                    "websocket handshake: invalid `Sec-WebSocket-Accept` header",
+                ));
+            }
+        }
+
+        res.upgrade_websocket().await
+    }
+}
+
+impl Response {
+    /// Consumes a `101 Switching Protocols` response to a WebSocket
+    /// handshake, and returns the WebSocket connection.
+    ///
+    /// This is useful when the handshake request was built manually, for
+    /// instance to add authentication headers. Prefer
+    /// [`Client::websocket`], which also verifies the server's
+    /// `Sec-WebSocket-Accept` header.
+    ///
+    /// # Optional
+    ///
+    /// This requires the optional `websocket` feature enabled.
+    pub async fn upgrade_websocket(self) -> crate::Result<WebSocket> {
+        if self.status() != StatusCode::SWITCHING_PROTOCOLS {
+            return Err(crate::error::upgrade(format!(
+                "websocket handshake: unexpected status {}",
+                self.status()
+            )));
+        }
+        let is_websocket = self
+            .headers()
+            .get(UPGRADE)
+            .map_or(false, |v| v.as_bytes().eq_ignore_ascii_case(b"websocket"));
+        if !is_websocket {
+            return Err(crate::error::upgrade(
+                "websocket handshake: missing `Upgrade: websocket` header",
+            ));
+        }
+
+        let upgraded = self.upgrade().await?;
+        Ok(WebSocketStream::from_raw_socket(upgraded, Role::Client, None).await)
+    }
+}
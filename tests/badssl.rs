@@ -103,3 +103,67 @@ async fn test_badssl_wrong_host() {
 
     assert!(result.is_err());
 }
+
+#[cfg(feature = "__tls")]
+#[tokio::test]
+async fn test_badssl_custom_verifier() {
+    let text = reqwest::Client::builder()
+        .dangerous_custom_verifier(|chain, host| {
+            assert!(!chain.is_empty());
+            if host == "self-signed.badssl.com" {
+                Ok(())
+            } else {
+                Err(format!("unexpected host {host}").into())
+            }
+        })
+        .no_proxy()
+        .build()
+        .unwrap()
+        .get("https://self-signed.badssl.com/")
+        .send()
+        .await
+        .unwrap()
+        .text()
+        .await
+        .unwrap();
+
+    assert!(text.contains("<title>self-signed.badssl.com</title>"));
+
+    let result = reqwest::Client::builder()
+        .dangerous_custom_verifier(|_, _| Err("untrusted".into()))
+        .no_proxy()
+        .build()
+        .unwrap()
+        .get("https://mozilla-modern.badssl.com/")
+        .send()
+        .await;
+
+    assert!(result.is_err());
+}
+
+#[cfg(feature = "__rustls")]
+#[tokio::test]
+async fn test_rustls_badssl_custom_verifier() {
+    let text = reqwest::Client::builder()
+        .use_rustls_tls()
+        .dangerous_custom_verifier(|chain, host| {
+            assert!(!chain.is_empty());
+            if host == "self-signed.badssl.com" {
+                Ok(())
+            } else {
+                Err(format!("unexpected host {host}").into())
+            }
+        })
+        .no_proxy()
+        .build()
+        .unwrap()
+        .get("https://self-signed.badssl.com/")
+        .send()
+        .await
+        .unwrap()
+        .text()
+        .await
+        .unwrap();
+
+    assert!(text.contains("<title>self-signed.badssl.com</title>"));
+}
@@ -92,6 +92,17 @@ fn test_response_copy_to() {
     assert_eq!(dst, b"Hello");
 }
 
+#[test]
+fn test_response_consume() {
+    let server = server::http(move |_req| async { http::Response::new("Hello".into()) });
+
+    let url = format!("http://{}/1", server.addr());
+    let res = reqwest::blocking::get(&url).unwrap();
+    assert_eq!(res.status(), reqwest::StatusCode::OK);
+
+    res.consume().unwrap();
+}
+
 #[test]
 fn test_get() {
     let server = server::http(move |_req| async { http::Response::default() });
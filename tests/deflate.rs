@@ -61,6 +61,55 @@ async fn test_accept_header_is_not_changed_if_set() {
     assert_eq!(res.status(), reqwest::StatusCode::OK);
 }
 
+#[tokio::test]
+async fn test_deflate_sets_accept_encoding() {
+    let server = server::http(move |req| async move {
+        assert_eq!(req.headers()["accept-encoding"], "deflate");
+        http::Response::default()
+    });
+
+    let client = reqwest::Client::builder()
+        .no_gzip()
+        .no_brotli()
+        .no_zstd()
+        .deflate(true)
+        .build()
+        .unwrap();
+
+    let res = client
+        .get(&format!("http://{}/accept-encoding", server.addr()))
+        .send()
+        .await
+        .unwrap();
+
+    assert_eq!(res.status(), reqwest::StatusCode::OK);
+}
+
+#[cfg(feature = "gzip")]
+#[tokio::test]
+async fn test_deflate_accept_encoding_composes_with_gzip() {
+    let server = server::http(move |req| async move {
+        assert_eq!(req.headers()["accept-encoding"], "gzip, deflate");
+        http::Response::default()
+    });
+
+    let client = reqwest::Client::builder()
+        .no_brotli()
+        .no_zstd()
+        .gzip(true)
+        .deflate(true)
+        .build()
+        .unwrap();
+
+    let res = client
+        .get(&format!("http://{}/accept-encoding", server.addr()))
+        .send()
+        .await
+        .unwrap();
+
+    assert_eq!(res.status(), reqwest::StatusCode::OK);
+}
+
 #[tokio::test]
 async fn test_accept_encoding_header_is_not_changed_if_set() {
     let server = server::http(move |req| async move {
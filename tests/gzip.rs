@@ -85,6 +85,84 @@ async fn test_accept_encoding_header_is_not_changed_if_set() {
     assert_eq!(res.status(), reqwest::StatusCode::OK);
 }
 
+#[tokio::test]
+async fn test_auto_accept_encoding_disabled() {
+    let mut encoder = libflate::gzip::Encoder::new(Vec::new()).unwrap();
+    encoder.write_all(b"test request").unwrap();
+    let gzipped_content = encoder.finish().into_result().unwrap();
+
+    let server = server::http(move |req| {
+        assert!(req.headers().get("accept-encoding").is_none());
+
+        let gzipped = gzipped_content.clone();
+        async move {
+            http::Response::builder()
+                .header("content-encoding", "gzip")
+                .body(gzipped.into())
+                .unwrap()
+        }
+    });
+
+    let client = reqwest::Client::builder()
+        .auto_accept_encoding(false)
+        .build()
+        .unwrap();
+
+    let res = client
+        .get(&format!("http://{}/gzip", server.addr()))
+        .send()
+        .await
+        .unwrap();
+
+    assert_eq!(res.text().await.unwrap(), "test request");
+}
+
+#[tokio::test]
+async fn test_manual_decompress() {
+    let mut encoder = libflate::gzip::Encoder::new(Vec::new()).unwrap();
+    encoder.write_all(b"test request").unwrap();
+    let gzipped_content = encoder.finish().into_result().unwrap();
+    let truncated_content = gzipped_content[..gzipped_content.len() - 4].to_vec();
+
+    let server = server::http(move |req| {
+        let body = if req.uri() == "/truncated" {
+            truncated_content.clone()
+        } else {
+            gzipped_content.clone()
+        };
+        async move {
+            http::Response::builder()
+                .header("content-encoding", "gzip")
+                .body(body.into())
+                .unwrap()
+        }
+    });
+
+    let client = reqwest::Client::builder().no_gzip().build().unwrap();
+
+    let res = client
+        .get(&format!("http://{}/gzip", server.addr()))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.headers()["content-encoding"], "gzip");
+
+    let res = res.decompress();
+    assert!(res.headers().get("content-encoding").is_none());
+    assert_eq!(res.text().await.unwrap(), "test request");
+
+    let err = client
+        .get(&format!("http://{}/truncated", server.addr()))
+        .send()
+        .await
+        .unwrap()
+        .decompress()
+        .text()
+        .await
+        .unwrap_err();
+    assert!(err.is_decode());
+}
+
 async fn gzip_case(response_size: usize, chunk_size: usize) {
     use futures_util::stream::StreamExt;
 
@@ -175,3 +175,47 @@ fn blocking_file_part() {
     assert_eq!(res.url().as_str(), &url);
     assert_eq!(res.status(), reqwest::StatusCode::OK);
 }
+
+#[tokio::test]
+async fn multipart_fields() {
+    let _ = env_logger::try_init();
+
+    let server = server::http(move |mut req| async move {
+        let ct = req.headers()["content-type"].to_str().unwrap().to_owned();
+        let boundary = ct
+            .strip_prefix("multipart/form-data; boundary=")
+            .expect("multipart content-type");
+
+        let mut full: Vec<u8> = Vec::new();
+        while let Some(item) = req.body_mut().frame().await {
+            full.extend(&*item.unwrap().into_data().unwrap());
+        }
+
+        let expected_body = format!(
+            "\
+             --{0}\r\n\
+             Content-Disposition: form-data; name=\"foo\"\r\n\r\n\
+             bar\r\n\
+             --{0}\r\n\
+             Content-Disposition: form-data; name=\"baz\"\r\n\r\n\
+             qux\r\n\
+             --{0}--\r\n\
+             ",
+            boundary
+        );
+        assert_eq!(full, expected_body.as_bytes());
+
+        http::Response::default()
+    });
+
+    let url = format!("http://{}/multipart/fields", server.addr());
+
+    let res = reqwest::Client::new()
+        .post(&url)
+        .multipart_fields([("foo", "bar"), ("baz", "qux")])
+        .send()
+        .await
+        .unwrap();
+
+    assert_eq!(res.status(), reqwest::StatusCode::OK);
+}
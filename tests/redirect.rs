@@ -256,6 +256,39 @@ async fn test_redirect_policy_can_stop_redirects_without_an_error() {
     assert_eq!(res.status(), reqwest::StatusCode::FOUND);
 }
 
+#[tokio::test]
+async fn test_per_request_redirect_policy() {
+    let server = server::http(move |req| async move {
+        let n: usize = req.uri().path()[1..].parse().unwrap();
+        http::Response::builder()
+            .status(302)
+            .header("location", format!("/{}", n + 1))
+            .body(Body::default())
+            .unwrap()
+    });
+
+    let client = reqwest::Client::new();
+    let url = format!("http://{}/0", server.addr());
+
+    let res = client.get(&url).no_redirect().send().await.unwrap();
+    assert_eq!(res.url().as_str(), url);
+    assert_eq!(res.status(), reqwest::StatusCode::FOUND);
+
+    let err = client.get(&url).max_redirects(2).send().await.unwrap_err();
+    assert!(err.is_redirect());
+
+    // the same limit as when it's set on the client
+    let expected = reqwest::Client::builder()
+        .redirect(reqwest::redirect::Policy::limited(2))
+        .build()
+        .unwrap()
+        .get(&url)
+        .send()
+        .await
+        .unwrap_err();
+    assert_eq!(err.url(), expected.url());
+}
+
 #[tokio::test]
 async fn test_referer_is_not_set_if_disabled() {
     let server = server::http(move |req| async move {
@@ -376,3 +409,50 @@ async fn test_redirect_https_only_enforced_gh1312() {
     let err = res.unwrap_err();
     assert!(err.is_redirect());
 }
+
+#[tokio::test]
+async fn test_redirect_netrc_auth_not_sent_over_http() {
+    let server = server::http(move |req| async move {
+        if req.uri().path() == "/start" {
+            assert_eq!(req.headers()["authorization"], "Basic YW5vbjpndWVzdA==");
+            let port = req.headers()["host"]
+                .to_str()
+                .unwrap()
+                .rsplit(':')
+                .next()
+                .unwrap()
+                .to_owned();
+            http::Response::builder()
+                .status(302)
+                .header("location", format!("http://localhost:{port}/end"))
+                .body(Body::default())
+                .unwrap()
+        } else {
+            assert_eq!(req.headers().get("authorization"), None);
+            http::Response::default()
+        }
+    });
+
+    let netrc = std::env::temp_dir().join(format!(
+        "reqwest-redirect-netrc-{}",
+        std::process::id()
+    ));
+    std::fs::write(
+        &netrc,
+        "machine localhost login alice password s3cret\ndefault login anon password guest\n",
+    )
+    .unwrap();
+
+    let res = reqwest::Client::builder()
+        .netrc(netrc.clone())
+        .build()
+        .unwrap()
+        .get(format!("http://{}/start", server.addr()))
+        .send()
+        .await;
+    std::fs::remove_file(&netrc).unwrap();
+
+    let res = res.unwrap();
+    assert_eq!(res.url().path(), "/end");
+    assert_eq!(res.status(), reqwest::StatusCode::OK);
+}
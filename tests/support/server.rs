@@ -112,6 +112,76 @@ where
     .unwrap()
 }
 
+/// Serve raw bytes over TCP, for responses hyper's server would refuse to write.
+///
+/// `do_response` is called with the raw request head once it has been read.
+#[allow(unused)]
+pub fn low_level_with_response<F>(do_response: F) -> Server
+where
+    for<'c> F: Fn(&'c [u8], &'c mut tokio::net::TcpStream) -> Box<dyn Future<Output = ()> + Send + 'c>
+        + Clone
+        + Send
+        + 'static,
+{
+    use tokio::io::AsyncReadExt;
+
+    // Spawn new runtime in thread to prevent reactor execution context conflict
+    let test_name = thread::current().name().unwrap_or("<unknown>").to_string();
+    thread::spawn(move || {
+        let rt = runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .expect("new rt");
+        let listener = rt.block_on(async move {
+            tokio::net::TcpListener::bind(&std::net::SocketAddr::from(([127, 0, 0, 1], 0)))
+                .await
+                .unwrap()
+        });
+        let addr = listener.local_addr().unwrap();
+
+        let (shutdown_tx, mut shutdown_rx) = oneshot::channel();
+        let (panic_tx, panic_rx) = std_mpsc::channel();
+        let tname = format!("test({})-support-server", test_name);
+        thread::Builder::new()
+            .name(tname)
+            .spawn(move || {
+                rt.block_on(async move {
+                    loop {
+                        tokio::select! {
+                            _ = &mut shutdown_rx => {
+                                break;
+                            }
+                            accepted = listener.accept() => {
+                                let (mut io, _) = accepted.expect("accepted");
+                                let do_response = do_response.clone();
+                                tokio::spawn(async move {
+                                    let mut buf = Vec::new();
+                                    let mut chunk = [0u8; 1024];
+                                    while !buf.windows(4).any(|w| w == b"\r\n\r\n") {
+                                        match io.read(&mut chunk).await {
+                                            Ok(0) | Err(_) => return,
+                                            Ok(n) => buf.extend_from_slice(&chunk[..n]),
+                                        }
+                                    }
+                                    Box::into_pin(do_response(&buf, &mut io)).await;
+                                });
+                            }
+                        }
+                    }
+                    let _ = panic_tx.send(());
+                });
+            })
+            .expect("thread spawn");
+        Server {
+            addr,
+            panic_rx,
+            shutdown_tx: Some(shutdown_tx),
+        }
+    })
+    .join()
+    .unwrap()
+}
+
 #[cfg(feature = "http3")]
 pub fn http3<F1, Fut>(func: F1) -> Server
 where
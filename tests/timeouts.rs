@@ -87,6 +87,134 @@ async fn connect_timeout() {
     assert!(err.is_connect() && err.is_timeout());
 }
 
+fn slow_proxy(delay: Duration) -> reqwest::Proxy {
+    use futures_util::FutureExt;
+
+    let connector = reqwest::CustomProxyConnector::new(move |_uri| {
+        async move {
+            tokio::time::sleep(delay).await;
+            Err("slow proxy should have timed out".into())
+        }
+        .boxed()
+    });
+    reqwest::Proxy::all(connector).unwrap()
+}
+
+#[tokio::test]
+async fn request_connect_timeout() {
+    let _ = env_logger::try_init();
+
+    let client = reqwest::Client::builder()
+        .proxy(slow_proxy(Duration::from_secs(1)))
+        .build()
+        .unwrap();
+
+    let url = "http://hyper.rs/slow";
+
+    let err = client
+        .get(url)
+        .connect_timeout(Duration::from_millis(100))
+        .timeout(Duration::from_millis(500))
+        .send()
+        .await
+        .unwrap_err();
+
+    assert!(err.is_connect() && err.is_timeout());
+}
+
+#[tokio::test]
+async fn request_connect_timeout_overrides_client() {
+    let _ = env_logger::try_init();
+
+    let client = reqwest::Client::builder()
+        .proxy(slow_proxy(Duration::from_millis(300)))
+        .connect_timeout(Duration::from_secs(5))
+        .build()
+        .unwrap();
+
+    let url = "http://hyper.rs/slow";
+
+    let err = client
+        .get(url)
+        .connect_timeout(Duration::from_millis(100))
+        .send()
+        .await
+        .unwrap_err();
+
+    assert!(err.is_connect() && err.is_timeout());
+}
+
+#[tokio::test]
+async fn request_connect_timeout_longer_than_client() {
+    let _ = env_logger::try_init();
+
+    let client = reqwest::Client::builder()
+        .proxy(slow_proxy(Duration::from_millis(300)))
+        .connect_timeout(Duration::from_millis(100))
+        .build()
+        .unwrap();
+
+    // The slow proxy gives up before the longer timeout of the request.
+    let err = client
+        .get("http://hyper.rs/slow")
+        .connect_timeout(Duration::from_secs(5))
+        .send()
+        .await
+        .unwrap_err();
+    assert!(err.is_connect() && !err.is_timeout(), "{err:?}");
+}
+
+#[tokio::test]
+async fn request_connect_timeouts_share_connections() {
+    let _ = env_logger::try_init();
+
+    let server = server::http(move |_req| async { http::Response::default() });
+    let client = reqwest::Client::builder()
+        .no_proxy()
+        .connect_timeout(Duration::from_secs(5))
+        .build()
+        .unwrap();
+    let url = format!("http://{}/", server.addr());
+
+    let res = client.get(&url).send().await.unwrap();
+    assert!(!res.connection_info().unwrap().is_reused());
+    res.bytes().await.unwrap();
+
+    for ms in [1000, 2000, 3000] {
+        let res = client
+            .get(&url)
+            .connect_timeout(Duration::from_millis(ms))
+            .send()
+            .await
+            .unwrap();
+        assert!(res.connection_info().unwrap().is_reused());
+        res.bytes().await.unwrap();
+    }
+}
+
+#[tokio::test]
+async fn connect_timeout_for_host() {
+    let _ = env_logger::try_init();
+
+    let client = reqwest::Client::builder()
+        .proxy(slow_proxy(Duration::from_millis(300)))
+        .connect_timeout(Duration::from_millis(100))
+        .connect_timeout_for("*.hyper.rs", Duration::from_secs(5))
+        .build()
+        .unwrap();
+
+    // The slow proxy gives up before the longer timeout of matching hosts.
+    let err = client
+        .get("http://far.HYPER.rs/slow")
+        .send()
+        .await
+        .unwrap_err();
+    assert!(!err.is_timeout());
+
+    let err = client.get("http://hyper.rs/slow").send().await.unwrap_err();
+    assert!(err.is_connect() && err.is_timeout());
+}
+
 #[cfg(not(target_arch = "wasm32"))]
 #[tokio::test]
 async fn connect_many_timeout_succeeds() {
@@ -275,6 +403,118 @@ async fn read_timeout_allows_slow_response_body() {
     assert_eq!(body, "012");
 }
 
+#[tokio::test]
+async fn response_read_timeout_applies_to_response() {
+    let _ = env_logger::try_init();
+
+    let server = server::http(move |_req| {
+        async {
+            // delay returning the response
+            tokio::time::sleep(Duration::from_millis(300)).await;
+            http::Response::default()
+        }
+    });
+
+    let client = reqwest::Client::builder()
+        .response_read_timeout(Duration::from_millis(100))
+        .no_proxy()
+        .build()
+        .unwrap();
+
+    let url = format!("http://{}/slow", server.addr());
+
+    let err = client.get(&url).send().await.unwrap_err();
+
+    assert!(err.is_timeout());
+    assert_eq!(err.url().map(|u| u.as_str()), Some(url.as_str()));
+}
+
+#[cfg(feature = "stream")]
+#[tokio::test]
+async fn response_read_timeout_starts_after_request_body() {
+    use http_body_util::BodyExt;
+
+    let _ = env_logger::try_init();
+
+    let server = server::http(move |req| async move {
+        let body = req.into_body().collect().await.unwrap().to_bytes();
+        assert_eq!(&*body, b"Hello");
+        http::Response::default()
+    });
+
+    let client = reqwest::Client::builder()
+        .response_read_timeout(Duration::from_millis(200))
+        .no_proxy()
+        .build()
+        .unwrap();
+
+    // a slow upload shouldn't count against the read timeout
+    let body = reqwest::Body::wrap_stream(futures_util::stream::once(async {
+        tokio::time::sleep(Duration::from_millis(300)).await;
+        Ok::<_, std::convert::Infallible>("Hello")
+    }));
+
+    let url = format!("http://{}/upload", server.addr());
+    let res = client.post(&url).body(body).send().await.unwrap();
+
+    assert_eq!(res.status(), reqwest::StatusCode::OK);
+}
+
+#[cfg(feature = "stream")]
+#[tokio::test]
+async fn request_write_timeout_applies_to_request_body() {
+    use http_body_util::BodyExt;
+
+    let _ = env_logger::try_init();
+
+    let server = server::http(move |req| async move {
+        let _ = req.into_body().collect().await;
+        http::Response::default()
+    });
+
+    let client = reqwest::Client::builder()
+        .request_write_timeout(Duration::from_millis(100))
+        .no_proxy()
+        .build()
+        .unwrap();
+
+    let body = reqwest::Body::wrap_stream(futures_util::stream::once(async {
+        tokio::time::sleep(Duration::from_millis(300)).await;
+        Ok::<_, std::convert::Infallible>("Hello")
+    }));
+
+    let url = format!("http://{}/upload", server.addr());
+    let err = client.post(&url).body(body).send().await.unwrap_err();
+
+    assert!(err.is_timeout());
+}
+
+#[tokio::test]
+async fn request_write_timeout_starts_once_connected() {
+    use tower::util::MapFutureLayer;
+
+    let _ = env_logger::try_init();
+
+    let server = server::http(move |_req| async { http::Response::default() });
+
+    // a slow connect shouldn't count against the write timeout
+    let client = reqwest::Client::builder()
+        .request_write_timeout(Duration::from_millis(100))
+        .connector_layer(MapFutureLayer::new(|connecting| async move {
+            tokio::time::sleep(Duration::from_millis(300)).await;
+            let res: Result<_, Box<dyn std::error::Error + Send + Sync>> = connecting.await;
+            res
+        }))
+        .no_proxy()
+        .build()
+        .unwrap();
+
+    let url = format!("http://{}/upload", server.addr());
+    let res = client.post(&url).body("Hello").send().await.unwrap();
+
+    assert_eq!(res.status(), reqwest::StatusCode::OK);
+}
+
 /// Tests that internal client future cancels when the oneshot channel
 /// is canceled.
 #[cfg(feature = "blocking")]
@@ -423,3 +663,172 @@ async fn response_body_timeout_forwards_size_hint() {
 
     assert_eq!(res.content_length(), Some(5));
 }
+
+#[cfg(not(target_arch = "wasm32"))]
+#[tokio::test]
+async fn fallback_to_ipv4_on_ipv6_failure() {
+    let _ = env_logger::try_init();
+
+    let server = server::http(move |_req| async { http::Response::default() });
+    let port = server.addr().port();
+
+    // 100::/64 is a discard-only prefix, so the IPv6 attempt never succeeds.
+    let client = reqwest::Client::builder()
+        .resolve_to_addrs(
+            "dual_stack",
+            &[format!("[100::1]:{port}").parse().unwrap(), server.addr()],
+        )
+        .fallback_to_ipv4_on_ipv6_failure(true)
+        .build()
+        .unwrap();
+
+    let url = format!("http://dual_stack:{port}/fallback");
+
+    let res = client
+        .get(url)
+        .timeout(Duration::from_millis(1000))
+        .send()
+        .await
+        .unwrap();
+
+    assert_eq!(res.status(), reqwest::StatusCode::OK);
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+#[tokio::test]
+async fn fallback_to_ipv4_keeps_to_resolved_addresses() {
+    let _ = env_logger::try_init();
+
+    let server = server::http(move |_req| async { http::Response::default() });
+    let port = server.addr().port();
+
+    // The resolver only returns an IPv6 address for the host, so there is
+    // no IPv4 address to fall back to, even though the system resolver
+    // would give one for `localhost`.
+    let client = reqwest::Client::builder()
+        .resolve_to_addrs("localhost", &[format!("[100::1]:{port}").parse().unwrap()])
+        .fallback_to_ipv4_on_ipv6_failure(true)
+        .build()
+        .unwrap();
+
+    let err = client
+        .get(format!("http://localhost:{port}/fallback"))
+        .timeout(Duration::from_millis(1000))
+        .send()
+        .await
+        .unwrap_err();
+    assert!(err.is_connect() || err.is_timeout());
+}
+
+// Linux drops connection attempts to a listener whose backlog is full, so
+// they hang until they time out.
+#[cfg(target_os = "linux")]
+#[tokio::test]
+async fn connect_timeout_per_address() {
+    let _ = env_logger::try_init();
+
+    let server = server::http(move |_req| async { http::Response::default() });
+    let port = server.addr().port();
+
+    // The one connection the backlog has room for, never accepted.
+    let socket = tokio::net::TcpSocket::new_v4().unwrap();
+    socket.bind(([127, 0, 0, 2], port).into()).unwrap();
+    let full = socket.listen(0).unwrap();
+    let _queued = std::net::TcpStream::connect(full.local_addr().unwrap()).unwrap();
+
+    let client = reqwest::Client::builder()
+        .resolve_to_addrs(
+            "multi_address",
+            &[full.local_addr().unwrap(), server.addr()],
+        )
+        .connect_timeout(Duration::from_secs(10))
+        .connect_timeout_per_address(Duration::from_millis(200))
+        .pool_max_idle_per_host(0)
+        .no_proxy()
+        .build()
+        .unwrap();
+
+    let url = format!("http://multi_address:{port}/failover");
+    let start = std::time::Instant::now();
+    for _ in 0..2 {
+        let res = client.get(&url).send().await.unwrap();
+        assert_eq!(res.status(), reqwest::StatusCode::OK);
+    }
+    assert!(start.elapsed() < Duration::from_secs(3));
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+#[tokio::test]
+async fn connect_timeout_per_address_refused() {
+    let _ = env_logger::try_init();
+
+    let server = server::http(move |_req| async { http::Response::default() });
+    let port = server.addr().port();
+
+    // Nothing listens on the port of the server on another loopback
+    // address, so connecting to it is refused.
+    let client = reqwest::Client::builder()
+        .resolve_to_addrs(
+            "multi_address",
+            &[([127, 0, 0, 4], port).into(), server.addr()],
+        )
+        .connect_timeout_per_address(Duration::from_secs(5))
+        .pool_max_idle_per_host(0)
+        .no_proxy()
+        .build()
+        .unwrap();
+
+    let url = format!("http://multi_address:{port}/failover");
+    for _ in 0..2 {
+        let res = client.get(&url).send().await.unwrap();
+        assert_eq!(res.status(), reqwest::StatusCode::OK);
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+#[tokio::test]
+async fn connect_timeout_per_address_round_robin() {
+    use std::io::{Read, Write};
+
+    let _ = env_logger::try_init();
+
+    // The same port on two loopback addresses, each answering with its own.
+    let first = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    let port = first.local_addr().unwrap().port();
+    let second = std::net::TcpListener::bind(("127.0.0.3", port)).unwrap();
+    for listener in [first, second] {
+        std::thread::spawn(move || {
+            for stream in listener.incoming() {
+                let mut stream = stream.unwrap();
+                let mut head = Vec::new();
+                let mut byte = [0];
+                while !head.ends_with(b"\r\n\r\n") && stream.read(&mut byte).unwrap_or(0) == 1 {
+                    head.push(byte[0]);
+                }
+                let ip = stream.local_addr().unwrap().ip().to_string();
+                let _ = write!(
+                    stream,
+                    "HTTP/1.1 200 OK\r\ncontent-length: {}\r\nconnection: close\r\n\r\n{ip}",
+                    ip.len()
+                );
+            }
+        });
+    }
+
+    let client = reqwest::Client::builder()
+        .resolve_to_addrs(
+            "round_robin",
+            &[([127, 0, 0, 1], port).into(), ([127, 0, 0, 3], port).into()],
+        )
+        .connect_timeout_per_address(Duration::from_secs(1))
+        .no_proxy()
+        .build()
+        .unwrap();
+
+    let url = format!("http://round_robin:{port}/");
+    let mut ips = Vec::new();
+    for _ in 0..4 {
+        ips.push(client.get(&url).send().await.unwrap().text().await.unwrap());
+    }
+    assert_eq!(ips, ["127.0.0.1", "127.0.0.3", "127.0.0.1", "127.0.0.3"]);
+}
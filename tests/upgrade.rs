@@ -50,3 +50,64 @@ async fn http_upgrade() {
     upgraded.read_to_end(&mut buf).await.unwrap();
     assert_eq!(buf, b"bar=foo");
 }
+
+#[cfg(feature = "websocket")]
+#[tokio::test]
+async fn websocket_echo() {
+    use futures_util::{SinkExt, StreamExt};
+    use reqwest::websocket::Message;
+    use tokio_tungstenite::tungstenite::handshake::derive_accept_key;
+    use tokio_tungstenite::tungstenite::protocol::Role;
+
+    let server = server::http(move |req| {
+        assert_eq!(req.method(), "GET");
+        assert_eq!(req.headers()["upgrade"], "websocket");
+        assert_eq!(req.headers()["sec-websocket-version"], "13");
+        let accept = derive_accept_key(req.headers()["sec-websocket-key"].as_bytes());
+
+        tokio::spawn(async move {
+            let upgraded = hyper_util::rt::TokioIo::new(hyper::upgrade::on(req).await.unwrap());
+            let mut ws =
+                tokio_tungstenite::WebSocketStream::from_raw_socket(upgraded, Role::Server, None)
+                    .await;
+            while let Some(Ok(msg)) = ws.next().await {
+                if msg.is_text() {
+                    ws.send(msg).await.unwrap();
+                }
+            }
+        });
+
+        async move {
+            http::Response::builder()
+                .status(http::StatusCode::SWITCHING_PROTOCOLS)
+                .header(http::header::CONNECTION, "upgrade")
+                .header(http::header::UPGRADE, "websocket")
+                .header(http::header::SEC_WEBSOCKET_ACCEPT, accept)
+                .body(reqwest::Body::default())
+                .unwrap()
+        }
+    });
+
+    let mut ws = reqwest::Client::new()
+        .websocket(format!("ws://{}/echo", server.addr()))
+        .await
+        .unwrap();
+
+    ws.send(Message::text("hello")).await.unwrap();
+    let msg = ws.next().await.unwrap().unwrap();
+    assert_eq!(msg, Message::text("hello"));
+    ws.close(None).await.unwrap();
+}
+
+#[cfg(feature = "websocket")]
+#[tokio::test]
+async fn websocket_rejects_non_upgrade_response() {
+    let server = server::http(move |_req| async { http::Response::default() });
+
+    let err = reqwest::Client::new()
+        .websocket(format!("ws://{}/echo", server.addr()))
+        .await
+        .unwrap_err();
+
+    assert!(err.to_string().starts_with("error upgrading connection"));
+}